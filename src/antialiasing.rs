@@ -0,0 +1,66 @@
+// Modo de antialiasing elegido para la ejecución
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AntiAliasing {
+    None,
+    // Cobertura por muestra, un solo sombreado por píxel
+    Msaa(usize),
+    // Render a `factor` veces la resolución y reducción con filtro de caja
+    Ssaa(usize),
+}
+
+impl AntiAliasing {
+    // "none", "msaa2", "msaa4", "msaa8", "ssaa2", "ssaa3", "ssaa4"
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "none" | "off" => Ok(AntiAliasing::None),
+            "msaa2" => Ok(AntiAliasing::Msaa(2)),
+            "msaa4" => Ok(AntiAliasing::Msaa(4)),
+            "msaa8" => Ok(AntiAliasing::Msaa(8)),
+            "ssaa2" => Ok(AntiAliasing::Ssaa(2)),
+            "ssaa3" => Ok(AntiAliasing::Ssaa(3)),
+            "ssaa4" => Ok(AntiAliasing::Ssaa(4)),
+            _ => Err(format!(
+                "modo de antialiasing desconocido: {} (opciones: none, msaa2, msaa4, msaa8, ssaa2, ssaa3, ssaa4)",
+                text
+            )),
+        }
+    }
+
+    pub fn msaa_samples(self) -> usize {
+        match self {
+            AntiAliasing::Msaa(samples) => samples,
+            _ => 1,
+        }
+    }
+
+    pub fn ssaa_factor(self) -> usize {
+        match self {
+            AntiAliasing::Ssaa(factor) => factor,
+            _ => 1,
+        }
+    }
+}
+
+// Posiciones de muestra estándar (en píxeles, relativas al centro del píxel)
+pub fn sample_offsets(samples: usize) -> &'static [(f32, f32)] {
+    const ONE: [(f32, f32); 1] = [(0.0, 0.0)];
+    const TWO: [(f32, f32); 2] = [(0.25, 0.25), (-0.25, -0.25)];
+    const FOUR: [(f32, f32); 4] = [(-0.125, -0.375), (0.375, -0.125), (-0.375, 0.125), (0.125, 0.375)];
+    const EIGHT: [(f32, f32); 8] = [
+        (0.0625, -0.1875),
+        (-0.0625, 0.1875),
+        (0.3125, 0.0625),
+        (-0.1875, -0.3125),
+        (-0.3125, 0.3125),
+        (-0.4375, -0.0625),
+        (0.1875, 0.4375),
+        (0.4375, -0.4375),
+    ];
+
+    match samples {
+        2 => &TWO,
+        4 => &FOUR,
+        8 => &EIGHT,
+        _ => &ONE,
+    }
+}
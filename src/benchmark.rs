@@ -0,0 +1,115 @@
+use crate::camera::Camera;
+use crate::math::Vec3;
+use crate::profiler::FrameCounters;
+use std::time::Duration;
+
+// Cuadros medidos si no se pasa --frames: 10 segundos a 60 por segundo
+pub const BENCHMARK_FRAMES: usize = 600;
+// Cuadros que se dibujan antes de medir, para que cachés y tangentes estén listos
+pub const WARMUP_FRAMES: usize = 10;
+
+// Recorrido fijo de la cámara (ojo, punto mirado): vista general, el Sol de cerca, los planetas
+// interiores, el cinturón de asteroides, Júpiter y de vuelta desde arriba
+const FLIGHT: [(Vec3, Vec3); 7] = [
+    (Vec3::new(0.0, 15.0, 35.0), Vec3::new(0.0, 0.0, 0.0)),
+    (Vec3::new(6.0, 3.0, 8.0), Vec3::new(0.0, 0.0, 0.0)),
+    (Vec3::new(12.0, 1.5, 4.0), Vec3::new(10.0, 0.0, 0.0)),
+    (Vec3::new(16.0, 1.0, -4.0), Vec3::new(15.0, 0.0, 0.0)),
+    (Vec3::new(20.0, 4.0, -10.0), Vec3::new(0.0, 0.0, 0.0)),
+    (Vec3::new(0.0, 30.0, -5.0), Vec3::new(0.0, 0.0, 0.0)),
+    (Vec3::new(0.0, 15.0, 35.0), Vec3::new(0.0, 0.0, 0.0)),
+];
+
+// Cámara en el punto `t` (0..1) del recorrido, con curvas Catmull-Rom entre los puntos
+pub fn flight_camera(t: f32) -> Camera {
+    let segments = FLIGHT.len() - 1;
+    let position = t.clamp(0.0, 1.0) * segments as f32;
+    let segment = (position as usize).min(segments - 1);
+    let local = position - segment as f32;
+
+    let point = |i: isize| FLIGHT[i.clamp(0, segments as isize) as usize];
+    let i = segment as isize;
+    let (p0, p1, p2, p3) = (point(i - 1), point(i), point(i + 1), point(i + 2));
+
+    let eye = catmull_rom(p0.0, p1.0, p2.0, p3.0, local);
+    let center = catmull_rom(p0.1, p1.1, p2.1, p3.1, local);
+    Camera::new(eye, center, Vec3::new(0.0, 1.0, 0.0))
+}
+
+fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p1 * 2.0 + (p2 - p0) * t + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2 + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+        * 0.5
+}
+
+// Tiempos de render de cada cuadro medido y el trabajo que hizo el rasterizador
+pub struct BenchmarkStats {
+    frame_times: Vec<Duration>,
+    triangles_drawn: u64,
+    fragments_shaded: u64,
+}
+
+impl BenchmarkStats {
+    pub fn new() -> Self {
+        BenchmarkStats {
+            frame_times: Vec::new(),
+            triangles_drawn: 0,
+            fragments_shaded: 0,
+        }
+    }
+
+    pub fn add_frame(&mut self, time: Duration, counters: &FrameCounters) {
+        self.frame_times.push(time);
+        self.triangles_drawn += counters.triangles_drawn;
+        self.fragments_shaded += counters.fragments_shaded;
+    }
+
+    pub fn frames(&self) -> usize {
+        self.frame_times.len()
+    }
+
+    fn total(&self) -> Duration {
+        self.frame_times.iter().sum()
+    }
+
+    pub fn min_ms(&self) -> f32 {
+        self.frame_times.iter().min().map_or(0.0, |d| to_ms(*d))
+    }
+
+    pub fn max_ms(&self) -> f32 {
+        self.frame_times.iter().max().map_or(0.0, |d| to_ms(*d))
+    }
+
+    pub fn average_ms(&self) -> f32 {
+        if self.frame_times.is_empty() {
+            return 0.0;
+        }
+        to_ms(self.total()) / self.frame_times.len() as f32
+    }
+
+    // Percentil por rango más cercano: el cuadro más lento después de descartar el (100 - p)%
+    pub fn percentile_ms(&self, percentile: f32) -> f32 {
+        if self.frame_times.is_empty() {
+            return 0.0;
+        }
+        let mut sorted = self.frame_times.clone();
+        sorted.sort();
+        let rank = (percentile / 100.0 * sorted.len() as f32).ceil() as usize;
+        to_ms(sorted[rank.clamp(1, sorted.len()) - 1])
+    }
+
+    // Cuadros, triángulos y fragmentos por segundo de render
+    pub fn throughput(&self) -> (f32, f32, f32) {
+        let seconds = self.total().as_secs_f32().max(1e-6);
+        (
+            self.frame_times.len() as f32 / seconds,
+            self.triangles_drawn as f32 / seconds,
+            self.fragments_shaded as f32 / seconds,
+        )
+    }
+}
+
+fn to_ms(duration: Duration) -> f32 {
+    duration.as_secs_f32() * 1000.0
+}
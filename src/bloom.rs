@@ -0,0 +1,527 @@
+use crate::framebuffer::{Color, Framebuffer};
+
+// Imagen HDR auxiliar para la pirámide de bloom
+struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    fn new(width: usize, height: usize) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![Color::new(0, 0, 0); width * height],
+        }
+    }
+
+    fn get(&self, x: i32, y: i32) -> Color {
+        let x = x.clamp(0, self.width as i32 - 1) as usize;
+        let y = y.clamp(0, self.height as i32 - 1) as usize;
+        self.pixels[y * self.width + x]
+    }
+
+    // Muestreo bilineal en coordenadas de píxel (el centro del píxel 0 está en 0.5)
+    fn sample(&self, x: f32, y: f32) -> Color {
+        let fx = x - 0.5;
+        let fy = y - 0.5;
+        let x0 = fx.floor();
+        let y0 = fy.floor();
+        let tx = fx - x0;
+        let ty = fy - y0;
+        let (x0, y0) = (x0 as i32, y0 as i32);
+
+        let top = self.get(x0, y0).lerp(&self.get(x0 + 1, y0), tx);
+        let bottom = self.get(x0, y0 + 1).lerp(&self.get(x0 + 1, y0 + 1), tx);
+        top.lerp(&bottom, ty)
+    }
+}
+
+fn add(a: Color, b: Color) -> Color {
+    Color::from_float(a.r + b.r, a.g + b.g, a.b + b.b)
+}
+
+fn scaled(c: Color, k: f32) -> Color {
+    Color::from_float(c.r * k, c.g * k, c.b * k)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlurKind {
+    Gaussian,
+    DualKawase,
+}
+
+pub struct Bloom {
+    pub enabled: bool,
+    pub blur: BlurKind,
+    // Luminancia a partir de la cual un píxel empieza a brillar
+    pub threshold: f32,
+    // Ancho de la transición suave alrededor del umbral
+    pub knee: f32,
+    pub intensity: f32,
+    pub levels: usize,
+}
+
+impl Bloom {
+    pub fn new() -> Self {
+        Bloom {
+            enabled: true,
+            blur: BlurKind::DualKawase,
+            threshold: 1.0,
+            knee: 0.5,
+            intensity: 0.6,
+            levels: 5,
+        }
+    }
+
+    // Off -> Gaussiano -> Dual Kawase -> Off
+    pub fn cycle_mode(&mut self) {
+        match (self.enabled, self.blur) {
+            (false, _) => {
+                self.enabled = true;
+                self.blur = BlurKind::Gaussian;
+            }
+            (true, BlurKind::Gaussian) => self.blur = BlurKind::DualKawase,
+            (true, BlurKind::DualKawase) => self.enabled = false,
+        }
+    }
+
+    pub fn mode_name(&self) -> &'static str {
+        match (self.enabled, self.blur) {
+            (false, _) => "off",
+            (true, BlurKind::Gaussian) => "Gaussiano",
+            (true, BlurKind::DualKawase) => "Dual Kawase",
+        }
+    }
+
+    // Extrae las zonas brillantes, las difumina y las suma al buffer HDR
+    pub fn apply(&self, framebuffer: &mut Framebuffer) {
+        if !self.enabled || framebuffer.width < 4 || framebuffer.height < 4 {
+            return;
+        }
+
+        let bright = self.extract_highlights(framebuffer);
+        let blurred = match self.blur {
+            BlurKind::Gaussian => self.gaussian_pyramid(bright),
+            BlurKind::DualKawase => self.dual_kawase(bright),
+        };
+
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                let glow = blurred.sample((x as f32 + 0.5) * 0.5, (y as f32 + 0.5) * 0.5);
+                let index = y * framebuffer.width + x;
+                framebuffer.color_buffer[index] = add(framebuffer.color_buffer[index], scaled(glow, self.intensity));
+            }
+        }
+    }
+
+    // Umbral con rodilla suave, reduciendo a media resolución
+    fn extract_highlights(&self, framebuffer: &Framebuffer) -> Image {
+        let mut half = Image::new(framebuffer.width / 2, framebuffer.height / 2);
+        let knee = self.knee.max(0.0001);
+
+        for y in 0..half.height {
+            for x in 0..half.width {
+                let mut sum = Color::new(0, 0, 0);
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let index = (y * 2 + dy) * framebuffer.width + x * 2 + dx;
+                    sum = add(sum, framebuffer.color_buffer[index]);
+                }
+                let color = scaled(sum, 0.25);
+
+                let brightness = color.r.max(color.g).max(color.b);
+                let soft = (brightness - self.threshold + knee).clamp(0.0, 2.0 * knee);
+                let soft = soft * soft / (4.0 * knee);
+                let contribution = soft.max(brightness - self.threshold) / brightness.max(0.0001);
+
+                half.pixels[y * half.width + x] = scaled(color, contribution);
+            }
+        }
+
+        half
+    }
+
+    fn dual_kawase(&self, base: Image) -> Image {
+        let mut chain = vec![base];
+        for _ in 1..self.levels {
+            let src = chain.last().unwrap();
+            if src.width < 4 || src.height < 4 {
+                break;
+            }
+            let next = kawase_down(src);
+            chain.push(next);
+        }
+
+        let mut current = chain.pop().unwrap();
+        while let Some(target) = chain.pop() {
+            let mut up = kawase_up(&current, target.width, target.height);
+            for (pixel, original) in up.pixels.iter_mut().zip(target.pixels.iter()) {
+                *pixel = add(*pixel, *original);
+            }
+            current = up;
+        }
+
+        let levels = self.levels.max(1) as f32;
+        for pixel in current.pixels.iter_mut() {
+            *pixel = scaled(*pixel, 1.0 / levels);
+        }
+        current
+    }
+
+    fn gaussian_pyramid(&self, base: Image) -> Image {
+        let mut chain = vec![gaussian_blur(&base)];
+        for _ in 1..self.levels {
+            let src = chain.last().unwrap();
+            if src.width < 4 || src.height < 4 {
+                break;
+            }
+            let next = gaussian_blur(&box_down(src));
+            chain.push(next);
+        }
+
+        let mut current = chain.pop().unwrap();
+        while let Some(target) = chain.pop() {
+            let mut up = Image::new(target.width, target.height);
+            let sx = current.width as f32 / target.width as f32;
+            let sy = current.height as f32 / target.height as f32;
+            for y in 0..target.height {
+                for x in 0..target.width {
+                    let index = y * target.width + x;
+                    let low = current.sample((x as f32 + 0.5) * sx, (y as f32 + 0.5) * sy);
+                    up.pixels[index] = add(low, target.pixels[index]);
+                }
+            }
+            current = up;
+        }
+
+        let levels = self.levels.max(1) as f32;
+        for pixel in current.pixels.iter_mut() {
+            *pixel = scaled(*pixel, 1.0 / levels);
+        }
+        current
+    }
+}
+
+fn box_down(src: &Image) -> Image {
+    let mut dst = Image::new(src.width / 2, src.height / 2);
+    for y in 0..dst.height {
+        for x in 0..dst.width {
+            dst.pixels[y * dst.width + x] = src.sample(x as f32 * 2.0 + 1.0, y as f32 * 2.0 + 1.0);
+        }
+    }
+    dst
+}
+
+// Gaussiano separable de 9 taps (sigma ~2 píxeles)
+fn gaussian_blur(src: &Image) -> Image {
+    const WEIGHTS: [f32; 5] = [0.2270, 0.1945, 0.1216, 0.0540, 0.0162];
+
+    let mut horizontal = Image::new(src.width, src.height);
+    for y in 0..src.height {
+        for x in 0..src.width {
+            let mut sum = scaled(src.get(x as i32, y as i32), WEIGHTS[0]);
+            for (offset, &weight) in WEIGHTS.iter().enumerate().skip(1) {
+                let offset = offset as i32;
+                sum = add(sum, scaled(src.get(x as i32 - offset, y as i32), weight));
+                sum = add(sum, scaled(src.get(x as i32 + offset, y as i32), weight));
+            }
+            horizontal.pixels[y * src.width + x] = sum;
+        }
+    }
+
+    let mut result = Image::new(src.width, src.height);
+    for y in 0..src.height {
+        for x in 0..src.width {
+            let mut sum = scaled(horizontal.get(x as i32, y as i32), WEIGHTS[0]);
+            for (offset, &weight) in WEIGHTS.iter().enumerate().skip(1) {
+                let offset = offset as i32;
+                sum = add(sum, scaled(horizontal.get(x as i32, y as i32 - offset), weight));
+                sum = add(sum, scaled(horizontal.get(x as i32, y as i32 + offset), weight));
+            }
+            result.pixels[y * src.width + x] = sum;
+        }
+    }
+
+    result
+}
+
+// Filtro de bajada de Kawase: centro x4 + cuatro esquinas, a mitad de resolución
+fn kawase_down(src: &Image) -> Image {
+    let mut dst = Image::new(src.width / 2, src.height / 2);
+    for y in 0..dst.height {
+        for x in 0..dst.width {
+            let cx = (x as f32 + 0.5) * 2.0;
+            let cy = (y as f32 + 0.5) * 2.0;
+            let mut sum = scaled(src.sample(cx, cy), 4.0);
+            for (dx, dy) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+                sum = add(sum, src.sample(cx + dx, cy + dy));
+            }
+            dst.pixels[y * dst.width + x] = scaled(sum, 1.0 / 8.0);
+        }
+    }
+    dst
+}
+
+// Filtro de subida de Kawase: 4 taps en los ejes y 4 diagonales con doble peso
+fn kawase_up(src: &Image, width: usize, height: usize) -> Image {
+    let mut dst = Image::new(width, height);
+    let sx = src.width as f32 / width as f32;
+    let sy = src.height as f32 / height as f32;
+
+    for y in 0..height {
+        for x in 0..width {
+            let cx = (x as f32 + 0.5) * sx;
+            let cy = (y as f32 + 0.5) * sy;
+            let mut sum = Color::new(0, 0, 0);
+            for (dx, dy) in [(-1.0, 0.0), (1.0, 0.0), (0.0, -1.0), (0.0, 1.0)] {
+                sum = add(sum, src.sample(cx + dx, cy + dy));
+            }
+            for (dx, dy) in [(-0.5, -0.5), (0.5, -0.5), (-0.5, 0.5), (0.5, 0.5)] {
+                sum = add(sum, scaled(src.sample(cx + dx, cy + dy), 2.0));
+            }
+            dst.pixels[y * width + x] = scaled(sum, 1.0 / 12.0);
+        }
+    }
+    dst
+}
+
+// Posición del Sol en pantalla para los destellos de lente
+#[derive(Debug, Clone, Copy)]
+pub struct ScreenSun {
+    pub x: f32,
+    pub y: f32,
+    // Profundidad del punto del Sol más cercano a la cámara
+    pub depth: f32,
+    pub radius: f32,
+}
+
+// Fracción del disco solar que no está tapada por otros objetos, según el z-buffer
+pub fn sun_screen_visibility(framebuffer: &Framebuffer, sun: &ScreenSun) -> f32 {
+    const RINGS: usize = 3;
+    const SAMPLES_PER_RING: usize = 12;
+
+    let mut visible = 0;
+    let mut total = 0;
+    for ring in 0..=RINGS {
+        let r = sun.radius * ring as f32 / RINGS as f32 * 0.9;
+        let samples = if ring == 0 { 1 } else { SAMPLES_PER_RING };
+        for i in 0..samples {
+            let angle = i as f32 / samples as f32 * std::f32::consts::TAU;
+            let x = sun.x + r * angle.cos();
+            let y = sun.y + r * angle.sin();
+            total += 1;
+
+            if x < 0.0 || y < 0.0 || x >= framebuffer.width as f32 || y >= framebuffer.height as f32 {
+                continue;
+            }
+            // Un objeto delante del Sol deja una profundidad menor que la superficie solar
+            if framebuffer.depth_at(x as usize, y as usize) >= sun.depth - 1e-4 {
+                visible += 1;
+            }
+        }
+    }
+
+    visible as f32 / total as f32
+}
+
+struct Ghost {
+    // Posición sobre la línea Sol -> centro de pantalla (0 = Sol, 1 = centro, 2 = reflejo opuesto)
+    offset: f32,
+    radius: f32,
+    color: Color,
+}
+
+pub struct LensFlare {
+    pub enabled: bool,
+    pub intensity: f32,
+    ghosts: Vec<Ghost>,
+}
+
+impl LensFlare {
+    pub fn new() -> Self {
+        LensFlare {
+            enabled: true,
+            intensity: 1.0,
+            ghosts: vec![
+                Ghost { offset: 0.5, radius: 0.04, color: Color::from_float(0.25, 0.2, 0.1) },
+                Ghost { offset: 1.2, radius: 0.025, color: Color::from_float(0.1, 0.25, 0.15) },
+                Ghost { offset: 1.5, radius: 0.07, color: Color::from_float(0.08, 0.12, 0.3) },
+                Ghost { offset: 1.8, radius: 0.035, color: Color::from_float(0.3, 0.12, 0.1) },
+                Ghost { offset: 2.2, radius: 0.1, color: Color::from_float(0.06, 0.08, 0.15) },
+            ],
+        }
+    }
+
+    // Suma halo, estrías horizontales y fantasmas al buffer HDR, atenuados por la visibilidad del Sol
+    pub fn apply(&self, framebuffer: &mut Framebuffer, sun: &ScreenSun) {
+        if !self.enabled {
+            return;
+        }
+
+        let visibility = sun_screen_visibility(framebuffer, sun);
+        if visibility <= 0.0 {
+            return;
+        }
+        let strength = visibility * self.intensity;
+
+        let width = framebuffer.width as f32;
+        let height = framebuffer.height as f32;
+        let diagonal = (width * width + height * height).sqrt();
+        let center_x = width * 0.5;
+        let center_y = height * 0.5;
+
+        // Más fuerte cuando el Sol está cerca del centro de la imagen
+        let off_center = ((sun.x - center_x).powi(2) + (sun.y - center_y).powi(2)).sqrt() / (diagonal * 0.5);
+        let ghost_strength = strength * (1.0 - off_center * 0.5).max(0.0);
+
+        let glare_radius = sun.radius * 4.0 + diagonal * 0.03;
+        let streak_length = diagonal * 0.35;
+        let streak_height = (sun.radius * 0.15).max(1.5);
+
+        // El destello es suave: se dibuja a media resolución y solo dentro del rectángulo de cada elemento
+        let mut flare = Image::new(framebuffer.width.div_ceil(2), framebuffer.height.div_ceil(2));
+
+        // Halo alrededor del Sol y estría anamórfica horizontal
+        let reach_x = glare_radius.max(streak_length);
+        let reach_y = glare_radius.max(streak_height * 4.0);
+        splat(&mut flare, sun.x - reach_x, sun.y - reach_y, sun.x + reach_x, sun.y + reach_y, |px, py| {
+            let dx = px - sun.x;
+            let dy = py - sun.y;
+            let mut light = 0.0;
+
+            let distance = (dx * dx + dy * dy).sqrt();
+            if distance < glare_radius {
+                let falloff = 1.0 - distance / glare_radius;
+                light += falloff * falloff * 0.6;
+            }
+
+            if dx.abs() < streak_length && dy.abs() < streak_height * 4.0 {
+                let along = 1.0 - dx.abs() / streak_length;
+                let across = (-(dy * dy) / (2.0 * streak_height * streak_height)).exp();
+                light += along * along * across * 0.5;
+            }
+
+            let light = light * strength;
+            Color::from_float(light, light * 0.85, light * 0.6)
+        });
+
+        // Fantasmas reflejados a lo largo del eje óptico
+        for ghost in &self.ghosts {
+            let gx = sun.x + (center_x - sun.x) * ghost.offset;
+            let gy = sun.y + (center_y - sun.y) * ghost.offset;
+            let radius = ghost.radius * diagonal;
+            splat(&mut flare, gx - radius, gy - radius, gx + radius, gy + radius, |px, py| {
+                let d = ((px - gx).powi(2) + (py - gy).powi(2)).sqrt();
+                if d >= radius {
+                    return Color::new(0, 0, 0);
+                }
+                let edge = 1.0 - d / radius;
+                // Disco con borde más brillante, como un reflejo del diafragma
+                let shape = (edge * 4.0).min(1.0) * (0.6 + 0.4 * (1.0 - edge));
+                scaled(ghost.color, shape * ghost_strength)
+            });
+        }
+
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                let color = flare.sample((x as f32 + 0.5) * 0.5, (y as f32 + 0.5) * 0.5);
+                if color.r > 0.0 || color.g > 0.0 || color.b > 0.0 {
+                    let index = y * framebuffer.width + x;
+                    framebuffer.color_buffer[index] = add(framebuffer.color_buffer[index], color);
+                }
+            }
+        }
+    }
+}
+
+// Suma `light` (evaluada en coordenadas de pantalla completa) a los píxeles de la imagen a media
+// resolución que caen dentro del rectángulo dado
+fn splat(image: &mut Image, min_x: f32, min_y: f32, max_x: f32, max_y: f32, light: impl Fn(f32, f32) -> Color) {
+    let x0 = (min_x * 0.5).floor().max(0.0) as usize;
+    let y0 = (min_y * 0.5).floor().max(0.0) as usize;
+    let x1 = ((max_x * 0.5).ceil().max(0.0) as usize).min(image.width);
+    let y1 = ((max_y * 0.5).ceil().max(0.0) as usize).min(image.height);
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let color = light((x as f32 + 0.5) * 2.0, (y as f32 + 0.5) * 2.0);
+            let index = y * image.width + x;
+            image.pixels[index] = add(image.pixels[index], color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: usize = 64;
+
+    fn hdr(value: f32) -> Color {
+        Color { r: value, g: value, b: value }
+    }
+
+    // Cuadro negro con un punto de 2x2 muy brillante en el centro
+    fn bright_spot() -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+        for (x, y) in [(32, 32), (33, 32), (32, 33), (33, 33)] {
+            framebuffer.color_buffer[y * SIZE + x] = hdr(50.0);
+        }
+        framebuffer
+    }
+
+    fn brightness(framebuffer: &Framebuffer, x: usize, y: usize) -> f32 {
+        framebuffer.color_buffer[y * framebuffer.width + x].r
+    }
+
+    #[test]
+    fn bright_spot_blooms_around_it() {
+        for blur in [BlurKind::Gaussian, BlurKind::DualKawase] {
+            let mut bloom = Bloom::new();
+            bloom.blur = blur;
+            let mut framebuffer = bright_spot();
+            bloom.apply(&mut framebuffer);
+
+            // Los vecinos reciben luz que decae con la distancia; las esquinas quedan casi negras
+            let near = brightness(&framebuffer, 36, 32);
+            let far = brightness(&framebuffer, 44, 32);
+            assert!(near > 0.01, "{:?}: {}", blur, near);
+            assert!(far > 0.0 && far < near, "{:?}: {} {}", blur, near, far);
+            assert!(brightness(&framebuffer, 0, 0) < far, "{:?}", blur);
+            // El punto conserva su brillo original
+            assert!(brightness(&framebuffer, 32, 32) >= 50.0);
+        }
+    }
+
+    #[test]
+    fn black_frame_stays_black() {
+        for blur in [BlurKind::Gaussian, BlurKind::DualKawase] {
+            let mut bloom = Bloom::new();
+            bloom.blur = blur;
+            let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+            bloom.apply(&mut framebuffer);
+            assert!(framebuffer.color_buffer.iter().all(|c| c.r == 0.0 && c.g == 0.0 && c.b == 0.0));
+        }
+    }
+
+    #[test]
+    fn lens_flare_follows_sun_visibility() {
+        let sun = ScreenSun { x: 16.0, y: 16.0, depth: 100.0, radius: 3.0 };
+        let flare = LensFlare::new();
+
+        let mut visible = Framebuffer::new(SIZE, SIZE);
+        flare.apply(&mut visible, &sun);
+        assert!(brightness(&visible, 17, 16) > 0.1);
+        // El primer fantasma cae a mitad de camino hacia el centro
+        assert!(brightness(&visible, 24, 24) > 0.0);
+
+        // Un objeto delante de todo el disco solar apaga el destello
+        let mut hidden = Framebuffer::new(SIZE, SIZE);
+        for depth in hidden.zbuffer.iter_mut() {
+            *depth = 1.0;
+        }
+        flare.apply(&mut hidden, &sun);
+        assert!(hidden.color_buffer.iter().all(|c| c.r == 0.0 && c.g == 0.0 && c.b == 0.0));
+    }
+}
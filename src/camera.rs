@@ -1,4 +1,112 @@
-use crate::math::{Vec3, Mat4, look_at};
+use crate::math::{Vec3, Mat4, Quat, look_at, translate, ray_sphere};
+use crate::scene::Obstacle;
+
+// Distancia al centro permitida en modo órbita; lo que frena a la cámara frente a un cuerpo es la
+// distancia mínima a su superficie
+const MIN_DISTANCE: f32 = 1.0;
+const MAX_DISTANCE: f32 = 100.0;
+// La elevación no llega a ±90° para que `up` no quede paralelo a la mirada
+const MAX_ELEVATION: f32 = 1.5;
+
+// Vuelo libre: la velocidad máxima es aceleración / amortiguación
+const FLY_ACCELERATION: f32 = 30.0;
+const FLY_DAMPING: f32 = 3.0;
+const FLY_BOOST: f32 = 4.0;
+const TURN_ACCELERATION: f32 = 8.0;
+const TURN_DAMPING: f32 = 6.0;
+
+// Segundos que tarda la mirada en acomodarse al cambiar de modo
+const MODE_BLEND_TIME: f32 = 0.5;
+
+// Qué tan rápido llega la cámara a su lugar al seguir un cuerpo (1/segundos)
+const FOLLOW_STIFFNESS: f32 = 4.0;
+// Elevación de la cámara de persecución sobre el plano de la órbita
+const CHASE_ELEVATION: f32 = 0.35;
+
+// Distancia mínima entre la cámara y la superficie de cualquier cuerpo
+pub const BODY_CLEARANCE: f32 = 0.3;
+// Superficies contra las que se desliza la cámara en un mismo cuadro
+const MAX_SLIDES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraMode {
+    // Gira alrededor de `center` a `distance`, con `angle` y `elevation`
+    Orbit,
+    // Seis grados de libertad: se mueve y gira en sus propios ejes
+    FreeFly,
+    // Pegada a un cuerpo que se mueve, según `follow_style`
+    Follow,
+}
+
+impl CameraMode {
+    pub fn next(self) -> Self {
+        match self {
+            CameraMode::Orbit => CameraMode::FreeFly,
+            CameraMode::FreeFly => CameraMode::Follow,
+            CameraMode::Follow => CameraMode::Orbit,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CameraMode::Orbit => "órbita",
+            CameraMode::FreeFly => "vuelo libre",
+            CameraMode::Follow => "seguimiento",
+        }
+    }
+}
+
+// Dónde se ubica la cámara respecto del cuerpo que sigue
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FollowStyle {
+    // Desplazamiento fijo en el mundo; se puede orbitar alrededor del cuerpo
+    FixedOffset,
+    // Detrás del cuerpo según hacia dónde se mueve, un poco por encima
+    Chase,
+    // Entre el cuerpo y aquello alrededor de lo que orbita: se ve la cara iluminada de los planetas
+    FromParent,
+}
+
+impl FollowStyle {
+    pub fn next(self) -> Self {
+        match self {
+            FollowStyle::FixedOffset => FollowStyle::Chase,
+            FollowStyle::Chase => FollowStyle::FromParent,
+            FollowStyle::FromParent => FollowStyle::FixedOffset,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FollowStyle::FixedOffset => "fijo",
+            FollowStyle::Chase => "persecución",
+            FollowStyle::FromParent => "desde el padre",
+        }
+    }
+}
+
+// Lo que la cámara necesita saber del cuerpo que sigue
+pub struct FollowTarget {
+    pub position: Vec3,
+    // Centro de su órbita (el Sol o su planeta)
+    pub parent_position: Vec3,
+}
+
+// Mandos del vuelo libre en [-1, 1], en los ejes de la cámara
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlyInput {
+    // x = derecha, y = arriba, z = adelante
+    pub thrust: Vec3,
+    // x = cabeceo (arriba), y = guiñada (izquierda), z = alabeo (antihorario)
+    pub turn: Vec3,
+    pub boost: bool,
+}
+
+// Transición de la mirada entre la orientación anterior y la del modo nuevo
+struct Blend {
+    from: Quat,
+    progress: f32,
+}
 
 pub struct Camera {
     pub eye: Vec3,
@@ -6,48 +114,316 @@ pub struct Camera {
     pub up: Vec3,
     pub has_changed: bool,
     pub angle: f32,
+    pub elevation: f32,
     pub distance: f32,
+    pub mode: CameraMode,
+    // Vuelo libre, en el mundo
+    pub velocity: Vec3,
+    // Vuelo libre, en radianes por segundo sobre los ejes de `FlyInput::turn`
+    pub angular_velocity: Vec3,
+    pub follow_style: FollowStyle,
+    // Posición del cuerpo seguido en el cuadro anterior y su última dirección de movimiento
+    follow_last: Option<Vec3>,
+    chase_direction: Vec3,
+    blend: Option<Blend>,
 }
 
 impl Camera {
     pub fn new(eye: Vec3, center: Vec3, up: Vec3) -> Self {
-        let angle = 0.0;
-        let distance = (eye - center).magnitude();
-        
-        Camera {
+        let mut camera = Camera {
             eye,
             center,
             up,
             has_changed: true,
-            angle,
-            distance,
+            angle: 0.0,
+            elevation: 0.0,
+            distance: 0.0,
+            mode: CameraMode::Orbit,
+            velocity: Vec3::zeros(),
+            angular_velocity: Vec3::zeros(),
+            follow_style: FollowStyle::FixedOffset,
+            follow_last: None,
+            chase_direction: Vec3::new(0.0, 0.0, -1.0),
+            blend: None,
+        };
+        camera.sync_orbit();
+        camera
+    }
+
+    // Recalcula ángulo, elevación y distancia a partir de `eye` y `center`, para seguir orbitando
+    // desde donde está la cámara después de moverla por otro lado
+    pub fn sync_orbit(&mut self) {
+        let offset = self.eye - self.center;
+        self.distance = offset.magnitude();
+        if self.distance > 0.0 {
+            self.angle = offset.z.atan2(offset.x);
+            self.elevation = (offset.y / self.distance).clamp(-1.0, 1.0).asin();
         }
     }
 
-    pub fn orbit(&mut self, delta_angle: f32, _vertical: f32) {
-        self.angle += delta_angle;
-        
-        self.eye.x = self.center.x + self.distance * self.angle.cos();
-        self.eye.z = self.center.z + self.distance * self.angle.sin();
-        
+    fn place_on_orbit(&mut self) {
+        // Siguiendo un cuerpo, follow() acerca la cámara de a poco a su nuevo lugar
+        if self.mode == CameraMode::Follow {
+            self.has_changed = true;
+            return;
+        }
+        let horizontal = self.distance * self.elevation.cos();
+        self.eye = self.center
+            + Vec3::new(
+                horizontal * self.angle.cos(),
+                self.distance * self.elevation.sin(),
+                horizontal * self.angle.sin(),
+            );
         self.has_changed = true;
     }
 
+    pub fn orbit(&mut self, delta_angle: f32, vertical: f32) {
+        self.angle += delta_angle;
+        self.elevation = (self.elevation + vertical).clamp(-MAX_ELEVATION, MAX_ELEVATION);
+        self.place_on_orbit();
+    }
+
     pub fn orbit_around_center(&mut self, delta_angle: f32) {
         self.orbit(delta_angle, 0.0);
     }
 
     pub fn zoom(&mut self, delta: f32) {
-        self.distance = (self.distance + delta).max(5.0).min(100.0);
-        
-        self.eye.x = self.center.x + self.distance * self.angle.cos();
-        self.eye.z = self.center.z + self.distance * self.angle.sin();
-        
+        self.distance = (self.distance + delta).clamp(MIN_DISTANCE, MAX_DISTANCE);
+        self.place_on_orbit();
+    }
+
+    // Cambia de modo sin saltos: la posición se mantiene y la mirada gira de a poco
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        if mode == self.mode {
+            return;
+        }
+        let from = self.view_orientation();
+        match mode {
+            CameraMode::Orbit => {
+                // Se orbita alrededor del punto que estaba mirando, con el horizonte derecho
+                self.up = Vec3::new(0.0, 1.0, 0.0);
+                self.sync_orbit();
+                self.elevation = self.elevation.clamp(-MAX_ELEVATION, MAX_ELEVATION);
+                self.place_on_orbit();
+            }
+            CameraMode::FreeFly => {
+                self.velocity = Vec3::zeros();
+                self.angular_velocity = Vec3::zeros();
+            }
+            CameraMode::Follow => {
+                // El primer follow() centra la cámara en el cuerpo desde donde esté
+                self.up = Vec3::new(0.0, 1.0, 0.0);
+                self.follow_last = None;
+            }
+        }
+        self.mode = mode;
+        self.blend = Some(Blend { from, progress: 0.0 });
+        self.has_changed = true;
+    }
+
+    // Vuelve a órbita sin transición, para quien anime la cámara por su cuenta (el warp)
+    pub fn reset_to_orbit(&mut self) {
+        self.mode = CameraMode::Orbit;
+        self.velocity = Vec3::zeros();
+        self.angular_velocity = Vec3::zeros();
+        self.blend = None;
+    }
+
+    // Mueve y gira la cámara en vuelo libre con aceleración y amortiguación
+    pub fn fly(&mut self, input: &FlyInput, delta_time: f32) {
+        if self.mode != CameraMode::FreeFly {
+            return;
+        }
+        let orientation = self.orientation();
+        let damping = |rate: f32| (-rate * delta_time).exp();
+
+        let boost = if input.boost { FLY_BOOST } else { 1.0 };
+        let thrust = Vec3::new(input.thrust.x, input.thrust.y, -input.thrust.z);
+        self.velocity += orientation * thrust * (FLY_ACCELERATION * boost * delta_time);
+        self.velocity *= damping(FLY_DAMPING);
+
+        self.angular_velocity += input.turn * (TURN_ACCELERATION * delta_time);
+        self.angular_velocity *= damping(TURN_DAMPING);
+
+        // Los giros son sobre los ejes de la cámara: no hay gimbal lock mirando hacia arriba
+        let turn = self.angular_velocity * delta_time;
+        let orientation = orientation
+            * Quat::from_axis_angle(&Vec3::new(0.0, 1.0, 0.0), turn.y)
+            * Quat::from_axis_angle(&Vec3::new(1.0, 0.0, 0.0), turn.x)
+            * Quat::from_axis_angle(&Vec3::new(0.0, 0.0, 1.0), turn.z);
+
+        self.eye += self.velocity * delta_time;
+        // El centro va delante a la distancia de órbita, por si se vuelve a orbitar
+        self.set_orientation(orientation.normalize(), self.distance.clamp(MIN_DISTANCE, MAX_DISTANCE));
+    }
+
+    // Mantiene la cámara pegada al cuerpo. Se suaviza el desplazamiento relativo al cuerpo y no la
+    // posición, así el cuerpo nunca se queda atrás aunque vaya rápido.
+    pub fn follow(&mut self, target: &FollowTarget, delta_time: f32) {
+        if self.mode != CameraMode::Follow {
+            return;
+        }
+        let Some(last) = self.follow_last.replace(target.position) else {
+            // Recién enganchada: se sigue orbitando desde donde está la cámara
+            self.center = target.position;
+            self.sync_orbit();
+            self.elevation = self.elevation.clamp(-MAX_ELEVATION, MAX_ELEVATION);
+            return;
+        };
+        let motion = target.position - last;
+        if motion.magnitude() > 1e-6 {
+            self.chase_direction = motion.normalize();
+        }
+
+        let offset = self.eye - self.center;
+        let desired = match self.follow_style {
+            FollowStyle::FixedOffset => {
+                let horizontal = self.distance * self.elevation.cos();
+                Vec3::new(
+                    horizontal * self.angle.cos(),
+                    self.distance * self.elevation.sin(),
+                    horizontal * self.angle.sin(),
+                )
+            }
+            FollowStyle::Chase => {
+                let behind = -self.chase_direction * (self.distance * CHASE_ELEVATION.cos());
+                behind + Vec3::new(0.0, self.distance * CHASE_ELEVATION.sin(), 0.0)
+            }
+            FollowStyle::FromParent => {
+                let from_parent = target.position - target.parent_position;
+                // El Sol no tiene padre: se queda del lado donde está la cámara
+                let direction = if from_parent.magnitude() > 1e-6 { -from_parent.normalize() } else { offset.normalize() };
+                direction * self.distance
+            }
+        };
+
+        let t = 1.0 - (-FOLLOW_STIFFNESS * delta_time).exp();
+        self.center = target.position;
+        self.eye = self.center + offset.lerp(&desired, t);
+        self.has_changed = true;
+    }
+
+    // Avanza la transición entre modos
+    pub fn update(&mut self, delta_time: f32) {
+        if let Some(blend) = self.blend.as_mut() {
+            blend.progress += delta_time / MODE_BLEND_TIME;
+            if blend.progress >= 1.0 {
+                self.blend = None;
+            }
+            self.has_changed = true;
+        }
+    }
+
+    // Hacia dónde mira la cámara, con `up` como arriba
+    pub fn orientation(&self) -> Quat {
+        Quat::look_rotation(&(self.center - self.eye), &self.up)
+    }
+
+    // La orientación que se ve, incluida la transición entre modos
+    pub fn view_orientation(&self) -> Quat {
+        match &self.blend {
+            Some(blend) => {
+                let t = blend.progress.clamp(0.0, 1.0);
+                blend.from.slerp(&self.orientation(), t * t * (3.0 - 2.0 * t))
+            }
+            None => self.orientation(),
+        }
+    }
+
+    // Gira la cámara sin moverla; el centro queda a `distance` del ojo en la nueva dirección
+    pub fn set_orientation(&mut self, orientation: Quat, distance: f32) {
+        self.center = self.eye + orientation * Vec3::new(0.0, 0.0, -distance);
+        self.up = orientation * Vec3::new(0.0, 1.0, 0.0);
         self.has_changed = true;
     }
 
     pub fn get_view_matrix(&self) -> Mat4 {
-        look_at(&self.eye, &self.center, &self.up)
+        if self.blend.is_none() {
+            return look_at(&self.eye, &self.center, &self.up);
+        }
+        // La vista es la rotación inversa de la cámara aplicada después de llevar el ojo al origen
+        self.view_orientation().conjugate().to_mat4() * translate(&-self.eye)
+    }
+
+    // Vuelo libre: gira la mirada al instante, sin la inercia de las teclas
+    pub fn look(&mut self, yaw: f32, pitch: f32) {
+        if self.mode != CameraMode::FreeFly {
+            return;
+        }
+        let orientation = self.orientation()
+            * Quat::from_axis_angle(&Vec3::new(0.0, 1.0, 0.0), yaw)
+            * Quat::from_axis_angle(&Vec3::new(1.0, 0.0, 0.0), pitch);
+        self.set_orientation(orientation.normalize(), self.distance.clamp(MIN_DISTANCE, MAX_DISTANCE));
+    }
+
+    // Desplaza ojo y centro en el plano de la pantalla
+    pub fn pan(&mut self, right: f32, up: f32) {
+        let orientation = self.orientation();
+        let offset = orientation * Vec3::new(right, up, 0.0);
+        self.move_center(offset);
+    }
+
+    // Avanza ojo y centro en la dirección de la mirada
+    pub fn dolly(&mut self, amount: f32) {
+        let forward = (self.center - self.eye).normalize();
+        self.move_center(forward * amount);
+    }
+
+    // Recorre el movimiento del ojo desde `from` y lo frena a BODY_CLEARANCE de la superficie del
+    // primer cuerpo que cruza; lo que faltaba se sigue moviendo sobre el plano tangente, así la
+    // cámara se desliza sobre el cuerpo en vez de atravesarlo
+    pub fn avoid_bodies(&mut self, from: Vec3, obstacles: &[Obstacle]) {
+        let mut start = from;
+        let mut eye = self.eye;
+        for _ in 0..MAX_SLIDES {
+            let motion = eye - start;
+            let length = motion.magnitude();
+            if length < 1e-6 {
+                break;
+            }
+            let direction = motion / length;
+            let hit = obstacles
+                .iter()
+                .filter_map(|obstacle| {
+                    let radius = obstacle.radius + BODY_CLEARANCE;
+                    ray_sphere(&start, &direction, &obstacle.position, radius).map(|t| (t, obstacle))
+                })
+                .filter(|(t, _)| *t <= length)
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+            let Some((t, obstacle)) = hit else {
+                break;
+            };
+            let contact = start + direction * t;
+            let normal = (contact - obstacle.position).normalize();
+            let remaining = eye - contact;
+            eye = contact + remaining - normal * remaining.dot(&normal).min(0.0);
+            self.velocity -= normal * self.velocity.dot(&normal).min(0.0);
+            start = contact;
+        }
+        // Un cuerpo que se movió hasta la cámara la empuja hacia afuera
+        for obstacle in obstacles {
+            let radius = obstacle.radius + BODY_CLEARANCE;
+            let offset = eye - obstacle.position;
+            if offset.magnitude() < radius {
+                let normal = if offset.magnitude() > 1e-6 { offset.normalize() } else { Vec3::new(0.0, 1.0, 0.0) };
+                eye = obstacle.position + normal * radius;
+                self.velocity -= normal * self.velocity.dot(&normal).min(0.0);
+            }
+        }
+
+        let correction = eye - self.eye;
+        if correction.magnitude() < 1e-6 {
+            return;
+        }
+        if self.mode == CameraMode::FreeFly {
+            // En vuelo libre la mirada no cambia
+            self.move_center(correction);
+        } else {
+            // Orbitando se sigue mirando al centro, desde un poco más lejos
+            self.eye = eye;
+            self.sync_orbit();
+            self.has_changed = true;
+        }
     }
 
     pub fn move_center(&mut self, direction: Vec3) {
@@ -56,3 +432,93 @@ impl Camera {
         self.has_changed = true;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).magnitude() < 1e-3, "{:?} != {:?}", a, b);
+    }
+
+    // Sigue durante 5 s a una luna que avanza en línea recta junto con su planeta, a 5 unidades
+    // de la cámara; devuelve dónde quedó la cámara respecto de la luna
+    fn follow_offset(style: FollowStyle) -> Vec3 {
+        let start = Vec3::new(10.0, 0.0, 0.0);
+        let velocity = Vec3::new(2.0, 0.0, 0.0);
+        let mut camera = Camera::new(start + Vec3::new(0.0, 3.0, 4.0), start, Vec3::new(0.0, 1.0, 0.0));
+        camera.set_mode(CameraMode::Follow);
+        camera.follow_style = style;
+        for step in 0..300 {
+            let position = start + velocity * (step as f32 / 60.0);
+            let target = FollowTarget { position, parent_position: position - Vec3::new(5.0, 0.0, 0.0) };
+            camera.follow(&target, 1.0 / 60.0);
+            assert_eq!(camera.center, target.position);
+        }
+        camera.eye - camera.center
+    }
+
+    const SUN: Obstacle = Obstacle { position: Vec3::new(0.0, 0.0, 0.0), radius: 1.0 };
+
+    // Cámara en vuelo libre que se movió de `from` a `to` en un cuadro
+    fn fly_through(from: Vec3, to: Vec3, velocity: Vec3) -> Camera {
+        let mut camera = Camera::new(from, from + Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0));
+        camera.set_mode(CameraMode::FreeFly);
+        camera.move_center(to - from);
+        camera.velocity = velocity;
+        camera.avoid_bodies(from, &[SUN]);
+        camera
+    }
+
+    #[test]
+    fn straight_move_stops_at_the_clearance() {
+        let camera = fly_through(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(5.0, 0.0, 0.0), Vec3::new(10.0, 0.0, 0.0));
+        assert_close(camera.eye, Vec3::new(-1.0 - BODY_CLEARANCE, 0.0, 0.0));
+        // La mirada se mueve con el ojo
+        assert_close(camera.center - camera.eye, Vec3::new(0.0, 0.0, -1.0));
+        assert_close(camera.velocity, Vec3::zeros());
+    }
+
+    #[test]
+    fn leftover_motion_slides_along_the_surface() {
+        let radius = 1.0 + BODY_CLEARANCE;
+        let camera = fly_through(Vec3::new(-5.0, 0.5, 0.0), Vec3::new(5.0, 0.5, 0.0), Vec3::new(10.0, 0.0, 0.0));
+        let contact = Vec3::new(-(radius * radius - 0.25).sqrt(), 0.5, 0.0);
+        let normal = contact / radius;
+        let slide = camera.eye - contact;
+        assert!(slide.dot(&normal).abs() < 1e-4);
+        // Se conserva lo que faltaba recorrer, sin la parte que iba hacia adentro
+        let remaining = Vec3::new(5.0, 0.5, 0.0) - contact;
+        assert!((slide.magnitude() - (remaining - normal * remaining.dot(&normal)).magnitude()).abs() < 1e-4);
+        assert!(camera.eye.magnitude() >= radius - 1e-4);
+        assert!(camera.velocity.dot(&normal) > -1e-4);
+    }
+
+    #[test]
+    fn body_moving_onto_the_camera_pushes_it_out() {
+        let eye = Vec3::new(0.5, 0.0, 0.0);
+        let center = Vec3::new(0.5, 0.0, -5.0);
+        let mut camera = Camera::new(eye, center, Vec3::new(0.0, 1.0, 0.0));
+        camera.avoid_bodies(eye, &[SUN]);
+        assert_close(camera.eye, Vec3::new(1.0 + BODY_CLEARANCE, 0.0, 0.0));
+        // Orbitando sigue mirando al mismo centro
+        assert_eq!(camera.center, center);
+        assert!((camera.distance - camera.eye.distance(&center)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn follow_keeps_the_fixed_offset() {
+        assert_close(follow_offset(FollowStyle::FixedOffset), Vec3::new(0.0, 3.0, 4.0));
+    }
+
+    #[test]
+    fn chase_ends_behind_and_above() {
+        let expected = Vec3::new(-5.0 * CHASE_ELEVATION.cos(), 5.0 * CHASE_ELEVATION.sin(), 0.0);
+        assert_close(follow_offset(FollowStyle::Chase), expected);
+    }
+
+    #[test]
+    fn from_parent_ends_between_body_and_parent() {
+        assert_close(follow_offset(FollowStyle::FromParent), Vec3::new(-5.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,248 @@
+use crate::image::{save_image, Y4mWriter};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Cuadros por segundo de los videos Y4M; la ventana no va a ritmo fijo, así que se repiten o saltean
+// cuadros para que el video dure lo mismo que lo grabado
+const RECORD_FPS: u32 = 60;
+// Repeticiones máximas de un mismo cuadro; tras una pausa larga el video salta en vez de congelar el render
+const MAX_REPEATS: usize = RECORD_FPS as usize / 4;
+
+// Cómo se guarda una grabación
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordFormat {
+    // Una imagen BMP numerada por cuadro dentro de una carpeta
+    Sequence,
+    // Un solo archivo YUV4MPEG2 sin comprimir
+    Y4m,
+}
+
+impl RecordFormat {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "sequence" | "bmp" => Ok(RecordFormat::Sequence),
+            "y4m" => Ok(RecordFormat::Y4m),
+            _ => Err(format!("formato de grabación desconocido: {} (opciones: sequence, y4m)", text)),
+        }
+    }
+}
+
+// Fecha y hora UTC para nombres de archivo: `20261018-190612-345`
+pub fn timestamp() -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = now.as_secs();
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let time_of_day = seconds % 86_400;
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}-{:03}",
+        year, month, day,
+        time_of_day / 3600, time_of_day / 60 % 60, time_of_day % 60,
+        now.subsec_millis()
+    )
+}
+
+// Días desde 1970-01-01 a fecha del calendario gregoriano (algoritmo de Howard Hinnant)
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// Guarda la imagen a resolución de render y la escalada a la ventana; devuelve las rutas
+pub fn save_screenshot(
+    dir: &Path,
+    native: (&[u32], usize, usize),
+    scaled: (&[u32], usize, usize),
+) -> io::Result<(PathBuf, PathBuf)> {
+    std::fs::create_dir_all(dir)?;
+    let stamp = timestamp();
+    let native_path = dir.join(format!("captura_{}_nativa.bmp", stamp));
+    let scaled_path = dir.join(format!("captura_{}.bmp", stamp));
+    save_image(&native_path, native.0, native.1, native.2)?;
+    save_image(&scaled_path, scaled.0, scaled.1, scaled.2)?;
+    Ok((native_path, scaled_path))
+}
+
+enum Sink {
+    Sequence { dir: PathBuf },
+    Y4m(Y4mWriter),
+}
+
+// Grabación de la imagen que se muestra en la ventana, cuadro por cuadro
+pub struct Recorder {
+    pub format: RecordFormat,
+    dir: PathBuf,
+    sink: Option<Sink>,
+    path: PathBuf,
+    frames: usize,
+    // Segundos grabados hasta ahora
+    elapsed: f32,
+}
+
+impl Recorder {
+    pub fn new(dir: &Path, format: RecordFormat) -> Self {
+        Recorder {
+            format,
+            dir: dir.to_path_buf(),
+            sink: None,
+            path: PathBuf::new(),
+            frames: 0,
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    // Empieza una grabación nueva con nombre según la hora; devuelve dónde se guarda
+    pub fn start(&mut self, width: usize, height: usize) -> io::Result<PathBuf> {
+        self.stop()?;
+        std::fs::create_dir_all(&self.dir)?;
+        let name = format!("grabacion_{}", timestamp());
+
+        let (path, sink) = match self.format {
+            RecordFormat::Sequence => {
+                let dir = self.dir.join(name);
+                std::fs::create_dir_all(&dir)?;
+                (dir.clone(), Sink::Sequence { dir })
+            }
+            RecordFormat::Y4m => {
+                let path = self.dir.join(format!("{}.y4m", name));
+                let writer = Y4mWriter::create(&path, width, height, RECORD_FPS)?;
+                (path, Sink::Y4m(writer))
+            }
+        };
+
+        self.sink = Some(sink);
+        self.path = path.clone();
+        self.frames = 0;
+        self.elapsed = 0.0;
+        Ok(path)
+    }
+
+    // Termina la grabación en curso; devuelve la ruta y los cuadros guardados
+    pub fn stop(&mut self) -> io::Result<Option<(PathBuf, usize)>> {
+        match self.sink.take() {
+            Some(Sink::Y4m(writer)) => writer.finish()?,
+            Some(Sink::Sequence { .. }) => {}
+            None => return Ok(None),
+        }
+        Ok(Some((self.path.clone(), self.frames)))
+    }
+
+    // Agrega la imagen que se mostró durante `delta_time` segundos. La secuencia guarda cada cuadro
+    // una vez; el Y4M la guarda tantas veces como cuadros de RECORD_FPS caben hasta ahora, o ninguna
+    // si el cuadro anterior ya los cubrió
+    pub fn add_frame(&mut self, pixels: &[u32], width: usize, height: usize, delta_time: f32) -> io::Result<()> {
+        match self.sink {
+            None => Ok(()),
+            Some(Sink::Sequence { .. }) => self.write_frame(pixels, width, height),
+            Some(Sink::Y4m(_)) => {
+                self.elapsed += delta_time;
+                let due = (self.elapsed * RECORD_FPS as f32).round() as usize;
+                let repeats = due.saturating_sub(self.frames);
+                if repeats > MAX_REPEATS {
+                    // El tiempo que no se grabó se descarta para no arrastrarlo a los cuadros siguientes
+                    self.elapsed = (self.frames + MAX_REPEATS) as f32 / RECORD_FPS as f32;
+                }
+                for _ in 0..repeats.min(MAX_REPEATS) {
+                    self.write_frame(pixels, width, height)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn write_frame(&mut self, pixels: &[u32], width: usize, height: usize) -> io::Result<()> {
+        match &mut self.sink {
+            Some(Sink::Sequence { dir }) => {
+                save_image(&dir.join(format!("cuadro_{:05}.bmp", self.frames)), pixels, width, height)?;
+            }
+            Some(Sink::Y4m(writer)) => {
+                // Un video no puede cambiar de tamaño a mitad de camino
+                if writer.size() != (width, height) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "la ventana cambió de tamaño durante la grabación Y4M",
+                    ));
+                }
+                writer.write_frame(pixels)?;
+            }
+            None => return Ok(()),
+        }
+        self.frames += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_keeps_real_time() {
+        let dir = std::env::temp_dir().join(format!("grabacion_prueba_{}", std::process::id()));
+        let mut recorder = Recorder::new(&dir, RecordFormat::Y4m);
+        let pixels = vec![0u32; 4 * 2];
+        recorder.start(4, 2).unwrap();
+        // Un segundo a 30 cuadros por segundo, otro a 120
+        for _ in 0..30 {
+            recorder.add_frame(&pixels, 4, 2, 1.0 / 30.0).unwrap();
+        }
+        for _ in 0..120 {
+            recorder.add_frame(&pixels, 4, 2, 1.0 / 120.0).unwrap();
+        }
+        let (path, frames) = recorder.stop().unwrap().unwrap();
+        assert_eq!(frames, 2 * RECORD_FPS as usize);
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_dir(dir);
+    }
+
+    #[test]
+    fn long_pause_does_not_stall_recording() {
+        let dir = std::env::temp_dir().join(format!("grabacion_pausa_{}", std::process::id()));
+        let mut recorder = Recorder::new(&dir, RecordFormat::Y4m);
+        let pixels = vec![0u32; 4 * 2];
+        recorder.start(4, 2).unwrap();
+        // Diez segundos de pausa se recortan; después sigue a tiempo real
+        recorder.add_frame(&pixels, 4, 2, 10.0).unwrap();
+        for _ in 0..60 {
+            recorder.add_frame(&pixels, 4, 2, 1.0 / 60.0).unwrap();
+        }
+        let (path, frames) = recorder.stop().unwrap().unwrap();
+        assert_eq!(frames, MAX_REPEATS + 60);
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_dir(dir);
+    }
+
+    #[test]
+    fn sequence_keeps_every_frame_once() {
+        let dir = std::env::temp_dir().join(format!("grabacion_secuencia_{}", std::process::id()));
+        let mut recorder = Recorder::new(&dir, RecordFormat::Sequence);
+        let pixels = vec![0u32; 4 * 2];
+        let sequence = recorder.start(4, 2).unwrap();
+        // Cuadros rápidos, lentos y una pausa larga: uno por archivo
+        for delta_time in [1.0 / 120.0, 1.0 / 120.0, 1.0 / 20.0, 2.0, 1.0 / 60.0] {
+            recorder.add_frame(&pixels, 4, 2, delta_time).unwrap();
+        }
+        let (_, frames) = recorder.stop().unwrap().unwrap();
+        assert_eq!(frames, 5);
+        let mut files: Vec<String> = std::fs::read_dir(&sequence)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        let expected: Vec<String> = (0..5).map(|i| format!("cuadro_{:05}.bmp", i)).collect();
+        assert_eq!(files, expected);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,383 @@
+use crate::antialiasing::AntiAliasing;
+use crate::benchmark::BENCHMARK_FRAMES;
+use crate::capture::RecordFormat;
+use crate::debug_view::DebugOptions;
+use crate::image::{is_supported_format, is_video_format};
+use crate::mouse::DEFAULT_MOUSE_SENSITIVITY;
+use crate::postprocess::PostChain;
+use crate::renderer::render_size;
+use crate::upscale::{UpscaleFilter, MIN_RENDER_SCALE, MAX_RENDER_SCALE};
+use std::path::PathBuf;
+
+pub const DEFAULT_WINDOW_WIDTH: usize = 800;
+pub const DEFAULT_WINDOW_HEIGHT: usize = 600;
+// Resolución de render relativa a la ventana (700x525 para 800x600)
+pub const DEFAULT_RENDER_SCALE: f32 = 0.875;
+pub const DEFAULT_TARGET_FRAME_MS: f32 = 16.0;
+// Lado máximo de la ventana o de la imagen; evita reservar buffers absurdos por un error de tipeo
+pub const MAX_RESOLUTION: usize = 16384;
+
+// Opciones que llevan un valor; se reconocen antes de leerlo para reportar bien las desconocidas
+const VALUE_OPTIONS: &[&str] = &[
+    "--scene", "--assets", "--resolution", "--render-scale", "--upscale", "--target-frame-ms", "--aa",
+    "--post", "--debug", "--start-time", "--time-scale", "--camera", "--mouse-sensitivity", "--output",
+    "--frames", "--capture-dir", "--record-format", "--profile",
+];
+
+// Opciones de ejecución leídas de la línea de comandos
+pub struct Config {
+    pub scene: Option<PathBuf>,
+    pub assets: PathBuf,
+    pub resolution: (usize, usize),
+    pub fullscreen: bool,
+    pub render_scale: f32,
+    pub upscale: UpscaleFilter,
+    // Si está, activa la resolución dinámica con este tiempo por cuadro
+    pub target_frame_ms: Option<f32>,
+    pub anti_aliasing: AntiAliasing,
+    pub post_chain: PostChain,
+    pub debug: DebugOptions,
+    pub start_time: f32,
+    pub time_scale: f32,
+    pub camera: String,
+    // Multiplica los giros, desplazamientos y el zoom con el mouse
+    pub mouse_sensitivity: f32,
+    pub invert_y: bool,
+    // Sin ventana: se renderizan `frames` cuadros a archivos de imagen
+    pub output: Option<PathBuf>,
+    pub frames: usize,
+    // Recorrido fijo sin ventana que informa los tiempos por cuadro
+    pub benchmark: bool,
+    // Capturas (tecla C) y grabaciones (tecla V) desde la ventana
+    pub capture_dir: PathBuf,
+    pub record_format: RecordFormat,
+    // Perfil de tiempos por cuadro que se guarda al terminar (.csv o .json)
+    pub profile: Option<PathBuf>,
+    pub help: bool,
+}
+
+impl Config {
+    pub fn default_config() -> Self {
+        Config {
+            scene: None,
+            assets: PathBuf::from("assets"),
+            resolution: (DEFAULT_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT),
+            fullscreen: false,
+            render_scale: DEFAULT_RENDER_SCALE,
+            upscale: UpscaleFilter::Bilinear,
+            target_frame_ms: None,
+            anti_aliasing: AntiAliasing::None,
+            post_chain: PostChain::default_chain(),
+            debug: DebugOptions::new(),
+            start_time: 0.0,
+            time_scale: 1.0,
+            camera: "default".to_string(),
+            mouse_sensitivity: DEFAULT_MOUSE_SENSITIVITY,
+            invert_y: false,
+            output: None,
+            frames: 1,
+            benchmark: false,
+            capture_dir: PathBuf::from("capturas"),
+            record_format: RecordFormat::Sequence,
+            profile: None,
+            help: false,
+        }
+    }
+
+    // Acepta `--opción valor` y `--opción=valor`
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut config = Config::default_config();
+        let mut frames_given = false;
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name.to_string(), Some(value.to_string())),
+                _ => (arg.clone(), None),
+            };
+
+            match name.as_str() {
+                "-h" | "--help" => config.help = true,
+                "--fullscreen" => config.fullscreen = true,
+                "--benchmark" => config.benchmark = true,
+                "--invert-y" => config.invert_y = true,
+                _ if !VALUE_OPTIONS.contains(&name.as_str()) => return Err(format!("opción desconocida: {}", name)),
+                _ => {
+                    let value = match inline_value {
+                        Some(value) => value,
+                        None => args.next().ok_or_else(|| format!("{}: falta el valor", name))?,
+                    };
+                    let value = value.as_str();
+                    let result = match name.as_str() {
+                        "--scene" => {
+                            config.scene = Some(PathBuf::from(value));
+                            Ok(())
+                        }
+                        "--assets" => {
+                            config.assets = PathBuf::from(value);
+                            Ok(())
+                        }
+                        "--resolution" => parse_resolution(value).map(|r| config.resolution = r),
+                        "--render-scale" => parse_f32(value).and_then(|scale| {
+                            if (MIN_RENDER_SCALE..=MAX_RENDER_SCALE).contains(&scale) {
+                                config.render_scale = scale;
+                                Ok(())
+                            } else {
+                                Err(format!("fuera de rango: {} ({}..{})", value, MIN_RENDER_SCALE, MAX_RENDER_SCALE))
+                            }
+                        }),
+                        "--upscale" => UpscaleFilter::parse(value).map(|f| config.upscale = f),
+                        "--target-frame-ms" => parse_positive(value).map(|ms| config.target_frame_ms = Some(ms)),
+                        "--aa" => AntiAliasing::parse(value).map(|aa| config.anti_aliasing = aa),
+                        "--post" => PostChain::from_spec(value).map(|chain| config.post_chain = chain),
+                        "--debug" => DebugOptions::from_spec(value).map(|debug| config.debug = debug),
+                        "--start-time" => parse_f32(value).map(|t| config.start_time = t),
+                        "--time-scale" => parse_f32(value).map(|s| config.time_scale = s),
+                        "--camera" => {
+                            config.camera = value.to_string();
+                            Ok(())
+                        }
+                        "--mouse-sensitivity" => parse_positive(value).map(|s| config.mouse_sensitivity = s),
+                        "--output" => {
+                            let path = PathBuf::from(value);
+                            if is_supported_format(&path) || is_video_format(&path) {
+                                config.output = Some(path);
+                                Ok(())
+                            } else {
+                                Err(format!("formato no soportado: {} (usa .ppm, .bmp o .y4m)", value))
+                            }
+                        }
+                        "--frames" => match value.parse::<usize>() {
+                            Ok(frames) if frames > 0 => {
+                                config.frames = frames;
+                                frames_given = true;
+                                Ok(())
+                            }
+                            _ => Err(format!("número de cuadros inválido: {}", value)),
+                        },
+                        "--capture-dir" => {
+                            config.capture_dir = PathBuf::from(value);
+                            Ok(())
+                        }
+                        "--record-format" => RecordFormat::parse(value).map(|f| config.record_format = f),
+                        "--profile" => {
+                            let path = PathBuf::from(value);
+                            match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+                                Some("csv") | Some("json") => {
+                                    config.profile = Some(path);
+                                    Ok(())
+                                }
+                                _ => Err(format!("formato no soportado: {} (usa .csv o .json)", value)),
+                            }
+                        }
+                        _ => unreachable!("{} está en VALUE_OPTIONS sin tratarse", name),
+                    };
+                    result.map_err(|e| format!("{}: {}", name, e))?;
+                }
+            }
+        }
+
+        if config.benchmark && config.output.is_some() {
+            return Err("--benchmark: no se puede combinar con --output".to_string());
+        }
+        if frames_given && config.output.is_none() && !config.benchmark {
+            return Err("--frames: solo tiene sentido junto con --output o --benchmark".to_string());
+        }
+        if config.benchmark && !frames_given {
+            config.frames = BENCHMARK_FRAMES;
+        }
+        // La geometría se dibuja a la resolución escalada y, con SSAA, multiplicada por el factor
+        let (width, height) = render_size(config.resolution, config.render_scale);
+        let factor = config.anti_aliasing.ssaa_factor();
+        if width.max(height) * factor > MAX_RESOLUTION {
+            return Err(format!(
+                "--resolution: {}x{} con --render-scale {} y SSAA x{} da un render de {}x{} (máximo {} por lado)",
+                config.resolution.0, config.resolution.1, config.render_scale, factor,
+                width * factor, height * factor, MAX_RESOLUTION
+            ));
+        }
+        Ok(config)
+    }
+
+    pub fn scene_path(&self) -> PathBuf {
+        self.scene.clone().unwrap_or_else(|| self.assets.join("solar_system.scene"))
+    }
+
+    // Con varias imágenes se numeran: `captura.ppm` -> `captura_0000.ppm`, `captura_0001.ppm`...
+    pub fn output_path(&self, frame: usize) -> Option<PathBuf> {
+        let output = self.output.as_ref()?;
+        if self.frames == 1 || is_video_format(output) {
+            return Some(output.clone());
+        }
+        let stem = output.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+        let extension = output.extension().map(|e| e.to_string_lossy()).unwrap_or_default();
+        Some(output.with_file_name(format!("{}_{:04}.{}", stem, frame, extension)))
+    }
+}
+
+pub fn usage() -> String {
+    format!(
+        "Sistema solar en Rust
+
+Uso: proyecto3_space [opciones]
+
+Escena:
+  --scene ARCHIVO          Archivo de escena (por defecto ASSETS/solar_system.scene)
+  --assets DIR             Carpeta de mallas y escenas (por defecto assets)
+  --start-time SEG         Tiempo de simulación inicial (por defecto 0)
+  --time-scale X           Velocidad de la simulación (por defecto 1; negativo va hacia atrás)
+  --camera NOMBRE          default, overview, top o el nombre de un cuerpo (p. ej. Tierra)
+
+Mouse:
+  --mouse-sensitivity X    Multiplica giros, desplazamientos y zoom con el mouse (por defecto {})
+  --invert-y               Arrastrar hacia arriba baja la cámara (o la mirada) en vez de subirla
+
+Imagen:
+  --resolution ANCHOxALTO  Tamaño de la ventana o de la imagen (por defecto {}x{}, máximo {} por lado)
+  --fullscreen             Pantalla completa: ventana sin bordes del tamaño del monitor
+  --render-scale X         Escala de render respecto a la ventana, {}..{} (por defecto {})
+  --upscale FILTRO         nearest, bilinear, bicubic, lanczos o edge (por defecto bilinear)
+  --target-frame-ms MS     Activa la resolución dinámica con ese tiempo por cuadro
+  --aa MODO                none, msaa2, msaa4, msaa8, ssaa2, ssaa3 o ssaa4
+  --post LISTA             Cadena de post-proceso, p. ej. lut=cool,fxaa,vignette=0.5
+  --debug LISTA            Vistas de depuración: una de normals, depth, overdraw, ids o uv,
+                           más wireframe, gizmos y/o axes (p. ej. depth,wireframe)
+
+Capturas:
+  --capture-dir DIR        Carpeta de capturas (C) y grabaciones (V) (por defecto capturas)
+  --record-format FORMATO  sequence (un BMP por cuadro) o y4m (video sin comprimir)
+  --profile ARCHIVO        Al terminar guarda los tiempos por etapa y los contadores de cada
+                           cuadro en .csv o como traza de Chrome .json (con ventana, el último minuto)
+
+Sin ventana:
+  --output ARCHIVO         Renderiza a .ppm, .bmp o un video .y4m y termina
+  --frames N               Cuadros a renderizar a 60 por segundo (las imágenes se numeran si N > 1)
+  --benchmark              Recorrido fijo de la cámara sin ventana; informa tiempos mínimo,
+                           promedio y p99 por cuadro (por defecto {} cuadros)
+
+  -h, --help               Muestra esta ayuda",
+        DEFAULT_MOUSE_SENSITIVITY, DEFAULT_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT, MAX_RESOLUTION, MIN_RENDER_SCALE, MAX_RENDER_SCALE, DEFAULT_RENDER_SCALE,
+        BENCHMARK_FRAMES
+    )
+}
+
+fn parse_f32(text: &str) -> Result<f32, String> {
+    text.parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| format!("número inválido: {}", text))
+}
+
+fn parse_positive(text: &str) -> Result<f32, String> {
+    parse_f32(text).and_then(|value| {
+        if value > 0.0 {
+            Ok(value)
+        } else {
+            Err(format!("tiene que ser mayor que 0: {}", text))
+        }
+    })
+}
+
+// `1280x720`
+fn parse_resolution(text: &str) -> Result<(usize, usize), String> {
+    let (w, h) = text
+        .split_once(['x', 'X'])
+        .ok_or_else(|| format!("resolución inválida: {} (formato ANCHOxALTO)", text))?;
+    match (w.parse::<usize>(), h.parse::<usize>()) {
+        (Ok(w), Ok(h)) if w > 0 && h > 0 => {
+            if w <= MAX_RESOLUTION && h <= MAX_RESOLUTION {
+                Ok((w, h))
+            } else {
+                Err(format!("resolución fuera de rango: {} (máximo {} por lado)", text, MAX_RESOLUTION))
+            }
+        }
+        _ => Err(format!("resolución inválida: {}", text)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Config, String> {
+        Config::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn error(args: &[&str]) -> String {
+        parse(args).err().expect("se esperaba un error")
+    }
+
+    #[test]
+    fn accepts_both_value_styles() {
+        let config = parse(&["--resolution", "640x480", "--time-scale=2.5", "--fullscreen"]).unwrap();
+        assert_eq!(config.resolution, (640, 480));
+        assert_eq!(config.time_scale, 2.5);
+        assert!(config.fullscreen);
+        assert_eq!(parse(&["--resolution=320X200"]).unwrap().resolution, (320, 200));
+    }
+
+    #[test]
+    fn rejects_unknown_options_and_missing_values() {
+        assert_eq!(error(&["--colour"]), "opción desconocida: --colour");
+        assert_eq!(error(&["--colour", "red"]), "opción desconocida: --colour");
+        assert_eq!(error(&["--colour=red"]), "opción desconocida: --colour");
+        assert_eq!(error(&["--scene"]), "--scene: falta el valor");
+        assert!(parse(&["--render-scale", "9"]).is_err());
+        assert!(parse(&["--mouse-sensitivity", "0"]).is_err());
+        assert!(parse(&["--time-scale", "inf"]).is_err());
+        assert!(parse(&["--output", "captura.png"]).is_err());
+        assert!(parse(&["--profile", "perfil.txt"]).is_err());
+        assert!(parse(&["--frames", "0", "--output", "a.ppm"]).is_err());
+    }
+
+    #[test]
+    fn every_value_option_is_handled() {
+        // Si una opción de VALUE_OPTIONS no tuviera su rama, esto entraría en unreachable!
+        for option in VALUE_OPTIONS {
+            let _ = parse(&[option, "1"]);
+        }
+    }
+
+    #[test]
+    fn rejects_bad_resolutions() {
+        assert_eq!(parse_resolution("1920x1080"), Ok((1920, 1080)));
+        for text in ["1920", "0x600", "800x0", "-800x600", "800x", "x600", "ochocientosx600", "800x600x2"] {
+            assert!(parse_resolution(text).is_err(), "{}", text);
+        }
+        assert!(error(&["--resolution", "800*600"]).starts_with("--resolution:"));
+        // Un error de tipeo no debe reservar gigas de memoria
+        assert_eq!(parse_resolution("16384x16384"), Ok((MAX_RESOLUTION, MAX_RESOLUTION)));
+        assert!(error(&["--resolution", "800000x600000"]).contains("fuera de rango"));
+        assert!(parse_resolution("800x16385").is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_effective_render() {
+        // Cada opción por separado es válida; juntas piden un render de 131072x131072
+        let message = error(&["--resolution", "16384x16384", "--render-scale", "2", "--aa", "ssaa4"]);
+        assert!(message.starts_with("--resolution:"), "{}", message);
+        assert!(message.contains("131072x131072"), "{}", message);
+        assert!(error(&["--resolution", "4096x2048", "--aa", "ssaa4", "--render-scale", "1.5"]).starts_with("--resolution:"));
+        // El orden de las opciones no importa y el límite se alcanza exacto
+        let config = parse(&["--aa", "ssaa4", "--render-scale", "2", "--resolution", "2048x1024"]).unwrap();
+        assert_eq!(render_size(config.resolution, config.render_scale), (4096, 2048));
+        assert!(parse(&["--resolution", "16384x16384", "--render-scale", "1"]).is_ok());
+    }
+
+    #[test]
+    fn checks_option_combinations() {
+        assert!(error(&["--frames", "10"]).starts_with("--frames:"));
+        assert_eq!(parse(&["--frames", "10", "--output", "a.ppm"]).unwrap().frames, 10);
+        assert!(error(&["--benchmark", "--output", "a.ppm"]).starts_with("--benchmark:"));
+        assert_eq!(parse(&["--benchmark"]).unwrap().frames, BENCHMARK_FRAMES);
+        assert_eq!(parse(&["--benchmark", "--frames", "5"]).unwrap().frames, 5);
+    }
+
+    #[test]
+    fn numbers_output_frames() {
+        let config = parse(&["--output", "dir/captura.ppm", "--frames", "3"]).unwrap();
+        assert_eq!(config.output_path(2), Some(PathBuf::from("dir/captura_0002.ppm")));
+        let video = parse(&["--output", "video.y4m", "--frames", "3"]).unwrap();
+        assert_eq!(video.output_path(2), Some(PathBuf::from("video.y4m")));
+    }
+}
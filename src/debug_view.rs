@@ -0,0 +1,289 @@
+use crate::framebuffer::{Color, Framebuffer};
+use crate::math::{Vec3, Vec4};
+use crate::obj::Obj;
+use crate::scene::CelestialBody;
+use crate::shaders::{color_fragment_shader, project_point, vertex_shader, Fragment, FragmentShader, Uniforms};
+
+// Profundidad del fondo de estrellas; todo lo que está más lejos no es geometría
+const SKYBOX_DEPTH: f32 = 1000.0;
+// Las aristas se acercan este porcentaje de su distancia a la cámara para ganarle a su propia cara
+const WIREFRAME_DEPTH_BIAS: f32 = 0.005;
+const WIREFRAME_COLOR: Color = Color::new(255, 255, 255);
+const NORMAL_GIZMO_COLOR: Color = Color::new(80, 160, 255);
+const TANGENT_GIZMO_COLOR: Color = Color::new(255, 90, 90);
+// Largo de los gizmos relativo al radio del objeto
+const GIZMO_LENGTH: f32 = 0.15;
+const NORTH_AXIS_COLOR: Color = Color::new(255, 220, 90);
+const SOUTH_AXIS_COLOR: Color = Color::new(160, 90, 40);
+const EQUATOR_COLOR: Color = Color::new(90, 220, 255);
+// Cuánto sobresale el eje de la superficie y a qué altura va el ecuador, en radios
+const AXIS_LENGTH: f32 = 1.6;
+const EQUATOR_RADIUS: f32 = 1.02;
+const EQUATOR_SEGMENTS: usize = 64;
+
+// Qué se muestra en lugar del sombreado normal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DebugView {
+    Off,
+    // Normales como color (color_fragment_shader)
+    Normals,
+    // Z-buffer en escala de grises: blanco = cerca
+    Depth,
+    // Fragmentos sombreados por píxel: azul 1, verde 2, amarillo 3, rojo 4 o más
+    Overdraw,
+    // Un color distinto por triángulo
+    TriangleIds,
+    // Tablero de ajedrez sobre las coordenadas UV
+    UvChecker,
+}
+
+impl DebugView {
+    pub fn next(self) -> Self {
+        match self {
+            DebugView::Off => DebugView::Normals,
+            DebugView::Normals => DebugView::Depth,
+            DebugView::Depth => DebugView::Overdraw,
+            DebugView::Overdraw => DebugView::TriangleIds,
+            DebugView::TriangleIds => DebugView::UvChecker,
+            DebugView::UvChecker => DebugView::Off,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DebugView::Off => "off",
+            DebugView::Normals => "normales",
+            DebugView::Depth => "profundidad",
+            DebugView::Overdraw => "overdraw",
+            DebugView::TriangleIds => "IDs de triángulo",
+            DebugView::UvChecker => "tablero UV",
+        }
+    }
+
+    // Shader que reemplaza al del cuerpo, si esta vista lo hace
+    pub fn shader(self) -> Option<FragmentShader> {
+        match self {
+            DebugView::Normals => Some(color_fragment_shader),
+            DebugView::TriangleIds => Some(triangle_id_shader),
+            DebugView::UvChecker => Some(uv_checker_shader),
+            // Profundidad y overdraw solo miran los buffers: el sombreado da igual
+            DebugView::Depth | DebugView::Overdraw => Some(flat_shader),
+            DebugView::Off => None,
+        }
+    }
+}
+
+// Vistas de depuración activas; el wireframe y los gizmos se pueden sumar a cualquier vista
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DebugOptions {
+    pub view: DebugView,
+    pub wireframe: bool,
+    // Normales (azul) y tangentes (rojo) de cada vértice
+    pub gizmos: bool,
+    // Eje de rotación y ecuador de cada cuerpo
+    pub axes: bool,
+}
+
+impl DebugOptions {
+    pub fn new() -> Self {
+        DebugOptions {
+            view: DebugView::Off,
+            wireframe: false,
+            gizmos: false,
+            axes: false,
+        }
+    }
+
+    // "normals,wireframe,gizmos,axes": a lo sumo una vista más los agregados
+    pub fn from_spec(spec: &str) -> Result<Self, String> {
+        let mut options = DebugOptions::new();
+        for name in spec.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            let view = match name {
+                "wireframe" => {
+                    options.wireframe = true;
+                    continue;
+                }
+                "gizmos" => {
+                    options.gizmos = true;
+                    continue;
+                }
+                "axes" => {
+                    options.axes = true;
+                    continue;
+                }
+                "normals" => DebugView::Normals,
+                "depth" => DebugView::Depth,
+                "overdraw" => DebugView::Overdraw,
+                "ids" => DebugView::TriangleIds,
+                "uv" => DebugView::UvChecker,
+                _ => {
+                    return Err(format!(
+                        "vista de depuración desconocida: {} (opciones: normals, depth, overdraw, ids, uv, wireframe, gizmos, axes)",
+                        name
+                    ))
+                }
+            };
+            if options.view != DebugView::Off {
+                return Err("solo se puede elegir una vista (normals, depth, overdraw, ids o uv)".to_string());
+            }
+            options.view = view;
+        }
+        Ok(options)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.view != DebugView::Off || self.wireframe || self.gizmos || self.axes
+    }
+
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.view != DebugView::Off {
+            parts.push(self.view.name());
+        }
+        if self.wireframe {
+            parts.push("wireframe");
+        }
+        if self.gizmos {
+            parts.push("gizmos");
+        }
+        if self.axes {
+            parts.push("ejes");
+        }
+        if parts.is_empty() {
+            "off".to_string()
+        } else {
+            parts.join(" + ")
+        }
+    }
+}
+
+fn flat_shader(_fragment: &Fragment, _uniforms: &Uniforms) -> Color {
+    Color::new(128, 128, 128)
+}
+
+// Colores bien separados para triángulos vecinos (hash entero de `triangle_id`)
+pub fn triangle_id_shader(_fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let mut hash = (uniforms.triangle_id as u32).wrapping_mul(0x9E37_79B9);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x85EB_CA6B);
+    hash ^= hash >> 13;
+    Color::new((hash >> 16) as u8 | 0x30, (hash >> 8) as u8 | 0x30, hash as u8 | 0x30)
+}
+
+// Casillas de 1/8 de UV; el tinte muestra la dirección de u (rojo) y v (verde)
+pub fn uv_checker_shader(fragment: &Fragment, _uniforms: &Uniforms) -> Color {
+    let u = fragment.tex_coords.x;
+    let v = fragment.tex_coords.y;
+    let checker = ((u * 8.0).floor() + (v * 8.0).floor()) as i32 % 2 == 0;
+    let base = if checker { 0.9 } else { 0.25 };
+    Color::from_float(
+        base * (0.6 + 0.4 * u.fract()),
+        base * (0.6 + 0.4 * v.fract()),
+        base * 0.6,
+    )
+}
+
+// Reemplaza el color por la distancia a la cámara, normalizada entre lo más cercano y lo más lejano del
+// cuadro. El z-buffer guarda z de NDC, que casi no cambia lejos de la cámara: primero se linealiza.
+pub fn apply_depth_view(framebuffer: &mut Framebuffer, near_plane: f32, far_plane: f32) {
+    let distance = |depth: f32| {
+        2.0 * far_plane * near_plane / ((far_plane + near_plane) - depth * (far_plane - near_plane))
+    };
+    let geometry = |depth: &f32| depth.is_finite() && *depth < SKYBOX_DEPTH;
+    let (near, far) = framebuffer
+        .zbuffer
+        .iter()
+        .filter(|d| geometry(d))
+        .map(|&d| distance(d))
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(near, far), d| (near.min(d), far.max(d)));
+    let range = (far - near).max(1e-6);
+
+    for (color, depth) in framebuffer.color_buffer.iter_mut().zip(framebuffer.zbuffer.iter()) {
+        let value = if geometry(depth) { 1.0 - (distance(*depth) - near) / range } else { 0.0 };
+        *color = Color::from_float(value, value, value);
+    }
+}
+
+// Mapa de calor con la cuenta de fragmentos sombreados por píxel
+pub fn apply_overdraw_view(framebuffer: &mut Framebuffer) {
+    let ramp = [
+        Color::new(0, 0, 0),
+        Color::new(30, 60, 200),
+        Color::new(40, 190, 60),
+        Color::new(240, 220, 40),
+        Color::new(230, 40, 30),
+    ];
+    for (color, &count) in framebuffer.color_buffer.iter_mut().zip(framebuffer.overdraw.iter()) {
+        *color = ramp[(count as usize).min(ramp.len() - 1)];
+    }
+}
+
+// Aristas de todos los triángulos, tapadas por lo que esté delante
+pub fn draw_wireframe(obj: &Obj, framebuffer: &mut Framebuffer, uniforms: &Uniforms) {
+    let projected: Vec<Vec3> = obj
+        .vertices
+        .iter()
+        .map(|v| {
+            let p = vertex_shader(v, uniforms).position;
+            Vec3::new(p.x, p.y, p.z - WIREFRAME_DEPTH_BIAS * (1.0 - p.z).max(0.0))
+        })
+        .collect();
+
+    for triangle in obj.indices.chunks_exact(3) {
+        let corners = [projected[triangle[0]], projected[triangle[1]], projected[triangle[2]]];
+        // Igual que al rasterizar: se descartan los triángulos que cruzan el plano de la cámara
+        if corners.iter().any(|c| c.z < 0.0) {
+            continue;
+        }
+        for i in 0..3 {
+            framebuffer.line_3d(corners[i], corners[(i + 1) % 3], WIREFRAME_COLOR);
+        }
+    }
+}
+
+// Normal y tangente de cada vértice como segmentos en el mundo
+pub fn draw_gizmos(obj: &Obj, tangents: &[Vec3], framebuffer: &mut Framebuffer, uniforms: &Uniforms, scale: f32) {
+    let length = obj.bounding_radius() * scale * GIZMO_LENGTH;
+    let to_world = |v: Vec3, w: f32| {
+        let p = uniforms.model_matrix * Vec4::new(v.x, v.y, v.z, w);
+        Vec3::new(p.x, p.y, p.z)
+    };
+
+    for (vertex, tangent) in obj.vertices.iter().zip(tangents) {
+        let origin = to_world(vertex.position, 1.0);
+        let normal = to_world(vertex.normal, 0.0).normalize();
+        let tangent = to_world(*tangent, 0.0).normalize();
+
+        for (direction, color) in [(normal, NORMAL_GIZMO_COLOR), (tangent, TANGENT_GIZMO_COLOR)] {
+            if let (Some(from), Some(to)) = (
+                project_point(origin, uniforms),
+                project_point(origin + direction * length, uniforms),
+            ) {
+                framebuffer.line_3d(from, to, color);
+            }
+        }
+    }
+}
+
+// Eje de rotación (norte claro, sur oscuro) y ecuador; el cuerpo tapa la parte de atrás
+pub fn draw_rotation_axis(body: &CelestialBody, framebuffer: &mut Framebuffer, uniforms: &Uniforms) {
+    let radius = body.radius();
+    let mut segment = |from: Vec3, to: Vec3, color: Color| {
+        if let (Some(from), Some(to)) = (project_point(from, uniforms), project_point(to, uniforms)) {
+            framebuffer.line_3d(from, to, color);
+        }
+    };
+
+    let axis = body.rotation_axis() * (radius * AXIS_LENGTH);
+    segment(body.position, body.position + axis, NORTH_AXIS_COLOR);
+    segment(body.position, body.position - axis, SOUTH_AXIS_COLOR);
+
+    let orientation = body.axis_orientation();
+    let equator_point = |i: usize| {
+        let angle = i as f32 / EQUATOR_SEGMENTS as f32 * std::f32::consts::TAU;
+        body.position + orientation * Vec3::new(angle.cos(), 0.0, angle.sin()) * (radius * EQUATOR_RADIUS)
+    };
+    for i in 0..EQUATOR_SEGMENTS {
+        segment(equator_point(i), equator_point(i + 1), EQUATOR_COLOR);
+    }
+}
@@ -0,0 +1,40 @@
+// Tamaño del monitor principal en píxeles, o None si no se puede consultar
+#[cfg(windows)]
+pub fn primary_size() -> Option<(usize, usize)> {
+    use winapi::um::winuser::{GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN};
+
+    let (width, height) = unsafe { (GetSystemMetrics(SM_CXSCREEN), GetSystemMetrics(SM_CYSCREEN)) };
+    positive(width, height)
+}
+
+// En X11 (o XWayland) se lee el tamaño de la pantalla por defecto del display
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn primary_size() -> Option<(usize, usize)> {
+    use x11_dl::xlib::Xlib;
+
+    let xlib = Xlib::open().ok()?;
+    unsafe {
+        let display = (xlib.XOpenDisplay)(std::ptr::null());
+        if display.is_null() {
+            return None;
+        }
+        let screen = (xlib.XDefaultScreen)(display);
+        let width = (xlib.XDisplayWidth)(display, screen);
+        let height = (xlib.XDisplayHeight)(display, screen);
+        (xlib.XCloseDisplay)(display);
+        positive(width, height)
+    }
+}
+
+#[cfg(not(any(windows, all(unix, not(target_os = "macos")))))]
+pub fn primary_size() -> Option<(usize, usize)> {
+    None
+}
+
+fn positive(width: i32, height: i32) -> Option<(usize, usize)> {
+    if width > 0 && height > 0 {
+        Some((width as usize, height as usize))
+    } else {
+        None
+    }
+}
@@ -1,51 +1,83 @@
 use std::fmt;
+use crate::antialiasing::sample_offsets;
+use crate::font::{self, CELL_WIDTH, CELL_HEIGHT, GLYPH_WIDTH};
+use crate::math::Vec3;
+use crate::profiler::FrameCounters;
 
+// Color lineal en punto flotante. Puede pasar de 1.0 (HDR); se recorta al convertir a u32.
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
-    pub r: u8,
-    pub g: u8,
-    pub b: u8,
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
 }
 
 impl Color {
     pub const fn new(r: u8, g: u8, b: u8) -> Self {
-        Color { r, g, b }
+        Color {
+            r: r as f32 / 255.0,
+            g: g as f32 / 255.0,
+            b: b as f32 / 255.0,
+        }
+    }
+
+    pub fn to_u32(self) -> u32 {
+        let r = (self.r.clamp(0.0, 1.0) * 255.0) as u32;
+        let g = (self.g.clamp(0.0, 1.0) * 255.0) as u32;
+        let b = (self.b.clamp(0.0, 1.0) * 255.0) as u32;
+        (r << 16) | (g << 8) | b
     }
 
-    pub fn to_u32(&self) -> u32 {
-        ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
+    pub fn from_u32(value: u32) -> Self {
+        Color::new((value >> 16) as u8, (value >> 8) as u8, value as u8)
     }
 
+    // No recorta: los valores mayores a 1.0 se conservan hasta el tone mapping
     pub fn from_float(r: f32, g: f32, b: f32) -> Self {
         Color {
-            r: (r.clamp(0.0, 1.0) * 255.0) as u8,
-            g: (g.clamp(0.0, 1.0) * 255.0) as u8,
-            b: (b.clamp(0.0, 1.0) * 255.0) as u8,
+            r: r.max(0.0),
+            g: g.max(0.0),
+            b: b.max(0.0),
         }
     }
 
     pub fn lerp(&self, other: &Color, t: f32) -> Color {
         Color {
-            r: (self.r as f32 + (other.r as f32 - self.r as f32) * t) as u8,
-            g: (self.g as f32 + (other.g as f32 - self.g as f32) * t) as u8,
-            b: (self.b as f32 + (other.b as f32 - self.b as f32) * t) as u8,
+            r: self.r + (other.r - self.r) * t,
+            g: self.g + (other.g - self.g) * t,
+            b: self.b + (other.b - self.b) * t,
         }
     }
+
+    // Luminancia relativa (Rec. 709)
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
 }
 
 impl fmt::Display for Color {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Color({}, {}, {})", self.r, self.g, self.b)
+        write!(f, "Color({:.3}, {:.3}, {:.3})", self.r, self.g, self.b)
     }
 }
 
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
+    // Resultado final (0RGB) que se muestra; se llena con resolve()
     pub buffer: Vec<u32>,
+    // Color HDR donde escriben los shaders
+    pub color_buffer: Vec<Color>,
     pub zbuffer: Vec<f32>,
+    // MSAA: color y profundidad por muestra; vacíos cuando samples == 1
+    pub samples: usize,
+    pub sample_colors: Vec<Color>,
+    pub sample_depths: Vec<f32>,
+    // Fragmentos sombreados por píxel; vacío salvo en la vista de overdraw
+    pub overdraw: Vec<u16>,
+    // Triángulos y fragmentos procesados desde el último clear()
+    pub counters: FrameCounters,
     background_color: Color,
-    current_color: Color,
 }
 
 impl Framebuffer {
@@ -54,9 +86,48 @@ impl Framebuffer {
             width,
             height,
             buffer: vec![0; width * height],
+            color_buffer: vec![Color::new(0, 0, 0); width * height],
             zbuffer: vec![f32::INFINITY; width * height],
+            samples: 1,
+            sample_colors: Vec::new(),
+            sample_depths: Vec::new(),
+            overdraw: Vec::new(),
+            counters: FrameCounters::default(),
+            background_color: Color::new(0, 0, 0),
+        }
+    }
+
+    // Framebuffer sin color, solo z-buffer (mapas de sombra)
+    pub fn new_depth_only(width: usize, height: usize) -> Self {
+        Framebuffer {
+            width,
+            height,
+            buffer: Vec::new(),
+            color_buffer: Vec::new(),
+            zbuffer: vec![f32::INFINITY; width * height],
+            samples: 1,
+            sample_colors: Vec::new(),
+            sample_depths: Vec::new(),
+            overdraw: Vec::new(),
+            counters: FrameCounters::default(),
+            background_color: Color::new(0, 0, 0),
+        }
+    }
+
+    // Solo el buffer final, sin color HDR ni profundidad: la imagen escalada a la ventana y el HUD
+    pub fn new_overlay(width: usize, height: usize) -> Self {
+        Framebuffer {
+            width,
+            height,
+            buffer: vec![0; width * height],
+            color_buffer: Vec::new(),
+            zbuffer: Vec::new(),
+            samples: 1,
+            sample_colors: Vec::new(),
+            sample_depths: Vec::new(),
+            overdraw: Vec::new(),
+            counters: FrameCounters::default(),
             background_color: Color::new(0, 0, 0),
-            current_color: Color::new(255, 255, 255),
         }
     }
 
@@ -65,51 +136,568 @@ impl Framebuffer {
         for pixel in self.buffer.iter_mut() {
             *pixel = color;
         }
+        for pixel in self.color_buffer.iter_mut() {
+            *pixel = self.background_color;
+        }
         for depth in self.zbuffer.iter_mut() {
             *depth = f32::INFINITY;
         }
+        for pixel in self.sample_colors.iter_mut() {
+            *pixel = self.background_color;
+        }
+        for depth in self.sample_depths.iter_mut() {
+            *depth = f32::INFINITY;
+        }
+        for count in self.overdraw.iter_mut() {
+            *count = 0;
+        }
+        self.counters = FrameCounters::default();
     }
 
-    pub fn set_background_color(&mut self, color: Color) {
-        self.background_color = color;
+    pub fn set_overdraw_tracking(&mut self, enabled: bool) {
+        if enabled {
+            self.overdraw.resize(self.width * self.height, 0);
+        } else {
+            self.overdraw = Vec::new();
+        }
+    }
+
+    // Lo llama el rasterizador por cada fragmento que sombrea
+    pub fn count_fragment(&mut self, x: usize, y: usize) {
+        self.counters.fragments_shaded += 1;
+        if let Some(count) = self.overdraw.get_mut(y * self.width + x) {
+            *count = count.saturating_add(1);
+        }
+    }
+
+    // Activa MSAA con 2, 4 u 8 muestras por píxel (1 = desactivado)
+    pub fn set_samples(&mut self, samples: usize) {
+        self.samples = sample_offsets(samples).len();
+        if self.samples > 1 {
+            let count = self.width * self.height * self.samples;
+            self.sample_colors = vec![self.background_color; count];
+            self.sample_depths = vec![f32::INFINITY; count];
+        } else {
+            self.sample_colors = Vec::new();
+            self.sample_depths = Vec::new();
+        }
+    }
+
+    // Devuelve si la muestra pasó la prueba de profundidad
+    pub fn sample_point(&mut self, x: usize, y: usize, sample: usize, depth: f32, color: Color) -> bool {
+        if x >= self.width || y >= self.height || sample >= self.samples || !depth.is_finite() {
+            return false;
+        }
+
+        let index = (y * self.width + x) * self.samples + sample;
+        if depth < self.sample_depths[index] {
+            self.sample_colors[index] = color;
+            self.sample_depths[index] = depth;
+            return true;
+        }
+        false
     }
 
-    pub fn set_current_color(&mut self, color: Color) {
-        self.current_color = color;
+    // Promedia las muestras MSAA en el buffer de color; el z-buffer queda con la muestra más cercana
+    pub fn resolve_samples(&mut self) {
+        if self.samples <= 1 {
+            return;
+        }
+
+        let n = self.samples;
+        let weight = 1.0 / n as f32;
+        for i in 0..self.width * self.height {
+            let (mut r, mut g, mut b) = (0.0, 0.0, 0.0);
+            let mut depth = f32::INFINITY;
+            for s in 0..n {
+                let c = self.sample_colors[i * n + s];
+                r += c.r;
+                g += c.g;
+                b += c.b;
+                depth = depth.min(self.sample_depths[i * n + s]);
+            }
+            self.color_buffer[i] = Color::from_float(r * weight, g * weight, b * weight);
+            self.zbuffer[i] = depth;
+        }
     }
 
-    pub fn point(&mut self, x: usize, y: usize, depth: f32) {
-        if x < self.width && y < self.height {
-            let index = y * self.width + x;
-            
-            if depth < self.zbuffer[index] {
-                self.buffer[index] = self.current_color.to_u32();
+    // SSAA: reduce `src` (factor veces más grande) con un filtro de caja
+    pub fn downsample_from(&mut self, src: &Framebuffer, factor: usize) {
+        let weight = 1.0 / (factor * factor) as f32;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (mut r, mut g, mut b) = (0.0, 0.0, 0.0);
+                let mut depth = f32::INFINITY;
+                for sy in 0..factor {
+                    for sx in 0..factor {
+                        let index = (y * factor + sy).min(src.height - 1) * src.width + (x * factor + sx).min(src.width - 1);
+                        let c = src.color_buffer[index];
+                        r += c.r;
+                        g += c.g;
+                        b += c.b;
+                        depth = depth.min(src.zbuffer[index]);
+                    }
+                }
+                let index = y * self.width + x;
+                self.color_buffer[index] = Color::from_float(r * weight, g * weight, b * weight);
                 self.zbuffer[index] = depth;
             }
         }
     }
 
-    pub fn point_with_color(&mut self, x: usize, y: usize, depth: f32, color: Color) {
+    pub fn set_background_color(&mut self, color: Color) {
+        self.background_color = color;
+    }
+
+    // Devuelve si el punto pasó la prueba de profundidad (con MSAA, en alguna muestra)
+    pub fn point_with_color(&mut self, x: usize, y: usize, depth: f32, color: Color) -> bool {
         // Validar que las coordenadas estén en rango
         if x >= self.width || y >= self.height {
-            return; // Píxel fuera de rango
+            return false; // Píxel fuera de rango
         }
         
         // Validar que depth sea un número válido
         if !depth.is_finite() {
-            return; // Depth inválido
+            return false; // Depth inválido
         }
         
+        // Con MSAA los puntos y líneas cubren todas las muestras del píxel
+        if self.samples > 1 {
+            let mut written = false;
+            for sample in 0..self.samples {
+                written |= self.sample_point(x, y, sample, depth, color);
+            }
+            return written;
+        }
+
         let index = y * self.width + x;
         
         // Verificar que el índice no exceda el buffer
-        if index >= self.buffer.len() {
-            return; // Índice fuera de buffer
+        if index >= self.color_buffer.len() {
+            return false; // Índice fuera de buffer
         }
         
         if depth < self.zbuffer[index] {
-            self.buffer[index] = color.to_u32();
+            self.color_buffer[index] = color;
             self.zbuffer[index] = depth;
+            return true;
         }
+        false
+    }
+
+    pub fn depth_point(&mut self, x: usize, y: usize, depth: f32) {
+        if x >= self.width || y >= self.height || !depth.is_finite() {
+            return;
+        }
+
+        let index = y * self.width + x;
+        if depth < self.zbuffer[index] {
+            self.zbuffer[index] = depth;
+        }
+    }
+
+    pub fn depth_at(&self, x: usize, y: usize) -> f32 {
+        if x >= self.width || y >= self.height {
+            return f32::INFINITY;
+        }
+        self.zbuffer[y * self.width + x]
+    }
+
+    // Línea con profundidad interpolada entre los extremos (x, y en píxeles, z como en el z-buffer).
+    // Se recorta a la pantalla antes de recorrerla, así que no importa qué tan lejos caigan los extremos.
+    pub fn line_3d(&mut self, from: Vec3, to: Vec3, color: Color) {
+        let Some((from, to)) = clip_segment(from, to, self.width as f32 - 1.0, self.height as f32 - 1.0) else {
+            return;
+        };
+
+        let steps = (to.x - from.x).abs().max((to.y - from.y).abs()).ceil().max(1.0) as usize;
+        for step in 0..=steps {
+            let t = step as f32 / steps as f32;
+            let x = from.x + (to.x - from.x) * t;
+            let y = from.y + (to.y - from.y) * t;
+            let z = from.z + (to.z - from.z) * t;
+            self.point_with_color(x.round() as usize, y.round() as usize, z, color);
+        }
+    }
+
+    // Empaqueta el buffer de color en el buffer final 0RGB
+    pub fn resolve(&mut self) {
+        for (pixel, color) in self.buffer.iter_mut().zip(self.color_buffer.iter()) {
+            *pixel = color.to_u32();
+        }
+    }
+
+    // Superposición 2D: escribe en el buffer final, así que va después de resolve()
+
+    // Mezcla `color` sobre el píxel con opacidad `alpha`; fuera de pantalla no hace nada
+    pub fn blend_pixel(&mut self, x: i32, y: i32, color: Color, alpha: f32) {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return;
+        }
+        let index = y as usize * self.width + x as usize;
+        self.buffer[index] = if alpha >= 1.0 {
+            color.to_u32()
+        } else {
+            Color::from_u32(self.buffer[index]).lerp(&color, alpha.max(0.0)).to_u32()
+        };
+    }
+
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: usize, height: usize, color: Color, alpha: f32) {
+        let x0 = x.max(0);
+        let y0 = y.max(0);
+        let x1 = (x + width as i32).min(self.width as i32);
+        let y1 = (y + height as i32).min(self.height as i32);
+        for py in y0..y1 {
+            for px in x0..x1 {
+                self.blend_pixel(px, py, color, alpha);
+            }
+        }
+    }
+
+    // Tamaño en píxeles de un texto (con saltos de línea) dibujado a `scale` píxeles por punto
+    pub fn text_size(text: &str, scale: usize) -> (usize, usize) {
+        let columns = text.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+        let lines = text.lines().count().max(1);
+        (columns * CELL_WIDTH * scale, lines * CELL_HEIGHT * scale)
+    }
+
+    // Texto con la fuente incrustada; (x, y) es la esquina superior izquierda de la primera celda
+    pub fn draw_text(&mut self, x: i32, y: i32, text: &str, color: Color, scale: usize) {
+        let scale = scale.max(1) as i32;
+        for (line_index, line) in text.lines().enumerate() {
+            let cell_y = y + line_index as i32 * CELL_HEIGHT as i32 * scale;
+            for (char_index, c) in line.chars().enumerate() {
+                let cell_x = x + char_index as i32 * CELL_WIDTH as i32 * scale;
+                for (row, bits) in font::glyph(c).iter().enumerate() {
+                    for column in 0..GLYPH_WIDTH {
+                        if bits & (1 << (GLYPH_WIDTH - 1 - column)) == 0 {
+                            continue;
+                        }
+                        for dy in 0..scale {
+                            for dx in 0..scale {
+                                self.blend_pixel(
+                                    cell_x + column as i32 * scale + dx,
+                                    cell_y + row as i32 * scale + dy,
+                                    color,
+                                    1.0,
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Línea antialiasing de Xiaolin Wu: cada paso reparte la cobertura entre los dos píxeles vecinos
+    pub fn draw_line(&mut self, from: (f32, f32), to: (f32, f32), color: Color, alpha: f32) {
+        let Some((from, to)) = clip_segment(
+            Vec3::new(from.0, from.1, 0.0),
+            Vec3::new(to.0, to.1, 0.0),
+            self.width as f32,
+            self.height as f32,
+        ) else {
+            return;
+        };
+        let (mut x0, mut y0, mut x1, mut y1) = (from.x, from.y, to.x, to.y);
+
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            std::mem::swap(&mut x0, &mut y0);
+            std::mem::swap(&mut x1, &mut y1);
+        }
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+
+        let dx = x1 - x0;
+        let gradient = if dx.abs() < f32::EPSILON { 1.0 } else { (y1 - y0) / dx };
+        let mut plot = |x: f32, y: f32, coverage: f32| {
+            let (px, py) = if steep { (y, x) } else { (x, y) };
+            self.blend_pixel(px as i32, py as i32, color, alpha * coverage);
+        };
+
+        // Extremos: cobertura según cuánto del píxel tapa el segmento
+        let x_start = x0.round();
+        let y_start = y0 + gradient * (x_start - x0);
+        let gap_start = 1.0 - (x0 + 0.5).fract();
+        plot(x_start, y_start.floor(), (1.0 - y_start.fract()) * gap_start);
+        plot(x_start, y_start.floor() + 1.0, y_start.fract() * gap_start);
+
+        let x_end = x1.round();
+        let y_end = y1 + gradient * (x_end - x1);
+        let gap_end = (x1 + 0.5).fract();
+        plot(x_end, y_end.floor(), (1.0 - y_end.fract()) * gap_end);
+        plot(x_end, y_end.floor() + 1.0, y_end.fract() * gap_end);
+
+        let mut y = y_start + gradient;
+        let mut x = x_start + 1.0;
+        while x < x_end {
+            plot(x, y.floor(), 1.0 - y.fract());
+            plot(x, y.floor() + 1.0, y.fract());
+            y += gradient;
+            x += 1.0;
+        }
+    }
+
+    // Segmentos consecutivos; con `closed` también une el último punto con el primero
+    pub fn draw_polyline(&mut self, points: &[(f32, f32)], closed: bool, color: Color, alpha: f32) {
+        for pair in points.windows(2) {
+            self.draw_line(pair[0], pair[1], color, alpha);
+        }
+        if closed && points.len() > 2 {
+            self.draw_line(points[points.len() - 1], points[0], color, alpha);
+        }
+    }
+
+    // Trazos de `dash` píxeles separados por `gap` píxeles
+    pub fn draw_dashed_line(&mut self, from: (f32, f32), to: (f32, f32), dash: f32, gap: f32, color: Color, alpha: f32) {
+        let ((x0, y0), (x1, y1)) = (from, to);
+        let length = ((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt();
+        if length < f32::EPSILON || dash <= 0.0 {
+            return;
+        }
+        let (ux, uy) = ((x1 - x0) / length, (y1 - y0) / length);
+
+        let mut start = 0.0;
+        while start < length {
+            let end = (start + dash).min(length);
+            self.draw_line((x0 + ux * start, y0 + uy * start), (x0 + ux * end, y0 + uy * end), color, alpha);
+            start += dash + gap.max(0.0);
+        }
+    }
+
+    // Línea de `thickness` píxeles con extremos redondeados y borde suavizado
+    pub fn draw_thick_line(&mut self, from: (f32, f32), to: (f32, f32), thickness: f32, color: Color, alpha: f32) {
+        let ((x0, y0), (x1, y1)) = (from, to);
+        // Con NaN o infinitos el rectángulo a recorrer sería toda la pantalla
+        if ![x0, y0, x1, y1, thickness].iter().all(|v| v.is_finite()) {
+            return;
+        }
+        let half = thickness.max(1.0) / 2.0;
+        let min_x = (x0.min(x1) - half - 1.0).floor().max(0.0) as i32;
+        let max_x = (x0.max(x1) + half + 1.0).ceil().min(self.width as f32 - 1.0) as i32;
+        let min_y = (y0.min(y1) - half - 1.0).floor().max(0.0) as i32;
+        let max_y = (y0.max(y1) + half + 1.0).ceil().min(self.height as f32 - 1.0) as i32;
+
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let length_squared = dx * dx + dy * dy;
+        for py in min_y..=max_y {
+            for px in min_x..=max_x {
+                // Distancia del centro del píxel al segmento
+                let (cx, cy) = (px as f32 + 0.5, py as f32 + 0.5);
+                let t = if length_squared > 0.0 {
+                    (((cx - x0) * dx + (cy - y0) * dy) / length_squared).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let distance = ((cx - x0 - dx * t).powi(2) + (cy - y0 - dy * t).powi(2)).sqrt();
+                let coverage = (half + 0.5 - distance).clamp(0.0, 1.0);
+                if coverage > 0.0 {
+                    self.blend_pixel(px, py, color, alpha * coverage);
+                }
+            }
+        }
+    }
+
+    // Circunferencia de un píxel de ancho, suavizada
+    pub fn draw_circle(&mut self, cx: f32, cy: f32, radius: f32, color: Color, alpha: f32) {
+        self.circle_coverage(cx, cy, radius, color, alpha, |distance| 1.0 - (distance - radius).abs());
+    }
+
+    pub fn fill_circle(&mut self, cx: f32, cy: f32, radius: f32, color: Color, alpha: f32) {
+        self.circle_coverage(cx, cy, radius, color, alpha, |distance| radius + 0.5 - distance);
+    }
+
+    fn circle_coverage(&mut self, cx: f32, cy: f32, radius: f32, color: Color, alpha: f32, coverage: impl Fn(f32) -> f32) {
+        let min_x = (cx - radius - 1.0).floor().max(0.0) as i32;
+        let max_x = (cx + radius + 1.0).ceil().min(self.width as f32 - 1.0) as i32;
+        let min_y = (cy - radius - 1.0).floor().max(0.0) as i32;
+        let max_y = (cy + radius + 1.0).ceil().min(self.height as f32 - 1.0) as i32;
+
+        for py in min_y..=max_y {
+            for px in min_x..=max_x {
+                let distance = ((px as f32 + 0.5 - cx).powi(2) + (py as f32 + 0.5 - cy).powi(2)).sqrt();
+                let amount = coverage(distance).clamp(0.0, 1.0);
+                if amount > 0.0 {
+                    self.blend_pixel(px, py, color, alpha * amount);
+                }
+            }
+        }
+    }
+
+    // Contorno de un píxel de ancho
+    pub fn draw_rect(&mut self, x: i32, y: i32, width: usize, height: usize, color: Color, alpha: f32) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        let (w, h) = (width as i32, height as i32);
+        self.fill_rect(x, y, width, 1, color, alpha);
+        self.fill_rect(x, y + h - 1, width, 1, color, alpha);
+        if h > 2 {
+            self.fill_rect(x, y + 1, 1, height - 2, color, alpha);
+            self.fill_rect(x + w - 1, y + 1, 1, height - 2, color, alpha);
+        }
+    }
+}
+
+// Recorta el segmento al rectángulo [0, max_x] x [0, max_y] (Liang-Barsky); z se interpola igual.
+// None si queda completamente afuera o los extremos no son números finitos.
+fn clip_segment(from: Vec3, to: Vec3, max_x: f32, max_y: f32) -> Option<(Vec3, Vec3)> {
+    if ![from.x, from.y, from.z, to.x, to.y, to.z].iter().all(|v| v.is_finite()) {
+        return None;
+    }
+
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let mut t0: f32 = 0.0;
+    let mut t1: f32 = 1.0;
+    for (p, q) in [(-dx, from.x), (dx, max_x - from.x), (-dy, from.y), (dy, max_y - from.y)] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+            continue;
+        }
+        let r = q / p;
+        if p < 0.0 {
+            t0 = t0.max(r);
+        } else {
+            t1 = t1.min(r);
+        }
+        if t0 > t1 {
+            return None;
+        }
+    }
+
+    let at = |t: f32| from + (to - from) * t;
+    Some((at(t0), at(t1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    const WHITE: Color = Color::new(255, 255, 255);
+
+    // Lienzo negro; la cobertura de cada píxel se lee en el canal azul
+    fn canvas(width: usize, height: usize) -> Framebuffer {
+        Framebuffer::new_overlay(width, height)
+    }
+
+    fn coverage(framebuffer: &Framebuffer, x: usize, y: usize) -> f32 {
+        (framebuffer.buffer[y * framebuffer.width + x] & 0xff) as f32 / 255.0
+    }
+
+    fn total_coverage(framebuffer: &Framebuffer) -> f32 {
+        (0..framebuffer.height)
+            .flat_map(|y| (0..framebuffer.width).map(move |x| (x, y)))
+            .map(|(x, y)| coverage(framebuffer, x, y))
+            .sum()
+    }
+
+    #[test]
+    fn line_covers_its_length() {
+        let mut framebuffer = canvas(32, 16);
+        framebuffer.draw_line((4.0, 8.0), (20.0, 8.0), WHITE, 1.0);
+        // Horizontal sobre una fila: el interior queda entero, nada fuera de esa fila
+        for x in 5..20 {
+            assert_eq!(coverage(&framebuffer, x, 8), 1.0, "x = {}", x);
+        }
+        assert_eq!(coverage(&framebuffer, 2, 8), 0.0);
+        assert_eq!(coverage(&framebuffer, 22, 8), 0.0);
+        assert_eq!(coverage(&framebuffer, 10, 7) + coverage(&framebuffer, 10, 9), 0.0);
+        // En total cubre aproximadamente su largo
+        assert!((total_coverage(&framebuffer) - 16.0).abs() < 1.5);
+
+        // Diagonal entre dos filas: cada columna reparte la cobertura entre los dos vecinos
+        let mut framebuffer = canvas(32, 32);
+        framebuffer.draw_line((2.0, 2.5), (26.0, 14.5), WHITE, 1.0);
+        for x in 5..24 {
+            let column: f32 = (0..32).map(|y| coverage(&framebuffer, x, y)).sum();
+            assert!((column - 1.0).abs() < 0.02, "x = {}: {}", x, column);
+        }
+    }
+
+    #[test]
+    fn lines_are_clipped_to_the_framebuffer() {
+        let mut framebuffer = canvas(20, 10);
+        framebuffer.draw_line((-100.0, -100.0), (100.0, 100.0), WHITE, 1.0);
+        assert!(coverage(&framebuffer, 5, 5) > 0.5);
+        assert!(total_coverage(&framebuffer) > 9.0);
+
+        let mut framebuffer = canvas(20, 10);
+        framebuffer.draw_line((-10.0, 5.0), (-1.0, 5.0), WHITE, 1.0);
+        framebuffer.draw_line((5.0, 30.0), (15.0, 12.0), WHITE, 1.0);
+        framebuffer.draw_line((f32::NAN, 0.0), (5.0, 5.0), WHITE, 1.0);
+        framebuffer.draw_thick_line((-20.0, -20.0), (-5.0, -5.0), 3.0, WHITE, 1.0);
+        framebuffer.draw_thick_line((f32::NAN, 5.0), (10.0, 5.0), 3.0, WHITE, 1.0);
+        framebuffer.draw_thick_line((2.0, 5.0), (f32::INFINITY, 5.0), 3.0, WHITE, 1.0);
+        framebuffer.draw_thick_line((2.0, 5.0), (10.0, 5.0), f32::INFINITY, WHITE, 1.0);
+        framebuffer.fill_circle(50.0, 5.0, 4.0, WHITE, 1.0);
+        framebuffer.draw_rect(-5, -5, 3, 3, WHITE, 1.0);
+        assert_eq!(total_coverage(&framebuffer), 0.0);
+
+        // Lo que sí entra se dibuja hasta el borde
+        framebuffer.fill_circle(0.0, 0.0, 3.0, WHITE, 1.0);
+        assert_eq!(coverage(&framebuffer, 0, 0), 1.0);
+    }
+
+    #[test]
+    fn alpha_scales_coverage() {
+        let mut framebuffer = canvas(32, 16);
+        framebuffer.draw_line((4.0, 8.0), (20.0, 8.0), WHITE, 0.5);
+        assert!((coverage(&framebuffer, 10, 8) - 0.5).abs() < 0.01);
+
+        let mut framebuffer = canvas(32, 32);
+        framebuffer.fill_circle(16.0, 16.0, 8.0, WHITE, 1.0);
+        assert_eq!(coverage(&framebuffer, 16, 16), 1.0);
+        assert_eq!(coverage(&framebuffer, 16, 27), 0.0);
+        assert!((total_coverage(&framebuffer) - PI * 64.0).abs() < 3.0);
+
+        let mut framebuffer = canvas(32, 32);
+        framebuffer.draw_circle(16.0, 16.0, 8.0, WHITE, 0.5);
+        assert_eq!(coverage(&framebuffer, 16, 16), 0.0);
+        // Circunferencia de un píxel de ancho, a media opacidad
+        assert!((total_coverage(&framebuffer) - PI * 16.0 * 0.5).abs() < 3.0);
+    }
+
+    #[test]
+    fn dashes_keep_their_spacing() {
+        let mut framebuffer = canvas(48, 8);
+        framebuffer.draw_dashed_line((0.0, 4.0), (40.0, 4.0), 4.0, 4.0, WHITE, 1.0);
+        // Trazos de 4 píxeles cada 8 (0-4, 8-12, ... 32-36): los extremos tapan medio píxel
+        for start in (0..40).step_by(8) {
+            let dash: f32 = (start..start + 5).map(|x| coverage(&framebuffer, x, 4)).sum();
+            assert!((dash - 4.0).abs() < 0.05, "trazo en {}: {}", start, dash);
+            for x in start + 5..start + 8 {
+                assert_eq!(coverage(&framebuffer, x, 4), 0.0, "x = {}", x);
+            }
+        }
+        // Nada fuera de la fila ni después del final
+        assert!((total_coverage(&framebuffer) - 20.0).abs() < 0.25);
+    }
+
+    #[test]
+    fn thick_lines_polylines_and_rects() {
+        let mut framebuffer = canvas(32, 32);
+        framebuffer.draw_thick_line((4.0, 16.0), (28.0, 16.0), 5.0, WHITE, 1.0);
+        let column: f32 = (0..32).map(|y| coverage(&framebuffer, 16, y)).sum();
+        assert!((column - 5.0).abs() < 1.0, "{}", column);
+
+        let mut framebuffer = canvas(32, 32);
+        let square = [(4.0, 4.0), (20.0, 4.0), (20.0, 20.0), (4.0, 20.0)];
+        framebuffer.draw_polyline(&square, false, WHITE, 1.0);
+        let open = total_coverage(&framebuffer);
+        framebuffer.draw_polyline(&square, true, WHITE, 1.0);
+        assert!((total_coverage(&framebuffer) - open - 16.0).abs() < 1.5);
+
+        let mut framebuffer = canvas(16, 16);
+        framebuffer.draw_rect(2, 3, 8, 6, WHITE, 1.0);
+        assert_eq!(coverage(&framebuffer, 2, 3), 1.0);
+        assert_eq!(coverage(&framebuffer, 9, 8), 1.0);
+        assert_eq!(coverage(&framebuffer, 5, 5), 0.0);
+        assert_eq!(total_coverage(&framebuffer), (2 * 8 + 2 * 4) as f32);
     }
 }
@@ -0,0 +1,314 @@
+// Pruebas de imagen de referencia: cada una renderiza una escena fija (cámara, tiempo y resolución)
+// con el pipeline completo y la compara con tests/golden/NOMBRE.ppm.
+//
+//   UPDATE_GOLDEN=1 cargo test golden   reescribe las referencias
+//
+// Si una imagen no coincide, la obtenida, la esperada y la diferencia quedan en target/golden-diff/.
+
+use crate::antialiasing::AntiAliasing;
+use crate::camera::Camera;
+use crate::camera_preset;
+use crate::debug_view::DebugOptions;
+use crate::image::{load_ppm, save_image};
+use crate::math::Vec3;
+use crate::postprocess::PostChain;
+use crate::renderer::Renderer;
+use crate::scene::Scene;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+const WIDTH: usize = 160;
+const HEIGHT: usize = 120;
+const FRAME_TIME: f32 = 1.0 / 60.0;
+
+// Alcanza para el redondeo distinto entre compiladores y CPUs; cualquier cambio visible lo supera
+const MIN_PSNR: f64 = 40.0;
+const MIN_SSIM: f64 = 0.98;
+// Un píxel es distinto si algún canal cambia más que esto...
+const PIXEL_THRESHOLD: u8 = 16;
+// ...y puede haber a lo sumo esta fracción de píxeles distintos
+const MAX_DIFFERENT_PIXELS: f64 = 0.005;
+
+// Lado de las ventanas de SSIM y separación entre ellas
+const SSIM_WINDOW: usize = 8;
+const SSIM_STEP: usize = 4;
+
+struct Shot {
+    camera: fn(&Scene) -> Camera,
+    time: f32,
+    anti_aliasing: AntiAliasing,
+    debug: DebugOptions,
+}
+
+impl Shot {
+    fn new(camera: fn(&Scene) -> Camera, time: f32) -> Self {
+        Shot {
+            camera,
+            time,
+            anti_aliasing: AntiAliasing::None,
+            debug: DebugOptions::new(),
+        }
+    }
+}
+
+fn preset(name: &str, scene: &Scene) -> Camera {
+    camera_preset(name, scene).unwrap_or_else(|| panic!("cámara desconocida: {}", name))
+}
+
+fn look_at(eye: Vec3, center: Vec3) -> Camera {
+    Camera::new(eye, center, Vec3::new(0.0, 1.0, 0.0))
+}
+
+fn manifest_path(relative: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(relative)
+}
+
+// Un solo cuadro de la escena por defecto, sin escalado (render al tamaño de salida)
+fn render(shot: &Shot) -> Vec<u32> {
+    let assets = manifest_path("assets");
+    let mut scene = Scene::load(&assets.join("solar_system.scene"), &assets).expect("no se pudo cargar la escena");
+    scene.update(shot.time);
+
+    let mut renderer = Renderer::new((WIDTH, HEIGHT), 1.0, shot.anti_aliasing, PostChain::default_chain());
+    renderer.debug = shot.debug;
+    renderer.render(&scene, &(shot.camera)(&scene), shot.time, FRAME_TIME);
+    renderer.output.buffer.clone()
+}
+
+struct Comparison {
+    max_delta: u8,
+    different_pixels: f64,
+    psnr: f64,
+    ssim: f64,
+}
+
+impl Comparison {
+    fn passes(&self) -> bool {
+        self.psnr >= MIN_PSNR && self.ssim >= MIN_SSIM && self.different_pixels <= MAX_DIFFERENT_PIXELS
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "PSNR {:.2} dB (mínimo {}), SSIM {:.4} (mínimo {}), {:.2}% de píxeles distintos (máximo {}%), diferencia máxima {}",
+            self.psnr,
+            MIN_PSNR,
+            self.ssim,
+            MIN_SSIM,
+            self.different_pixels * 100.0,
+            MAX_DIFFERENT_PIXELS * 100.0,
+            self.max_delta
+        )
+    }
+}
+
+fn channels(pixel: u32) -> [u8; 3] {
+    [(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8]
+}
+
+fn luminance(pixel: u32) -> f64 {
+    let [r, g, b] = channels(pixel);
+    0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64
+}
+
+fn compare(expected: &[u32], actual: &[u32], width: usize, height: usize) -> Comparison {
+    let mut max_delta = 0;
+    let mut different = 0;
+    let mut squared_error = 0.0;
+    for (&e, &a) in expected.iter().zip(actual) {
+        let mut pixel_delta = 0;
+        for (ce, ca) in channels(e).into_iter().zip(channels(a)) {
+            let delta = ce.abs_diff(ca);
+            pixel_delta = pixel_delta.max(delta);
+            squared_error += (delta as f64).powi(2);
+        }
+        max_delta = max_delta.max(pixel_delta);
+        if pixel_delta > PIXEL_THRESHOLD {
+            different += 1;
+        }
+    }
+
+    let mse = squared_error / (expected.len() * 3) as f64;
+    let psnr = if mse == 0.0 { f64::INFINITY } else { 10.0 * (255.0 * 255.0 / mse).log10() };
+
+    Comparison {
+        max_delta,
+        different_pixels: different as f64 / expected.len() as f64,
+        psnr,
+        ssim: ssim(expected, actual, width, height),
+    }
+}
+
+// SSIM sobre la luminancia, promediado en ventanas cuadradas que se solapan
+fn ssim(expected: &[u32], actual: &[u32], width: usize, height: usize) -> f64 {
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+    let mut total = 0.0;
+    let mut windows = 0;
+    for y0 in (0..=height.saturating_sub(SSIM_WINDOW)).step_by(SSIM_STEP) {
+        for x0 in (0..=width.saturating_sub(SSIM_WINDOW)).step_by(SSIM_STEP) {
+            let (mut sum_e, mut sum_a, mut sum_ee, mut sum_aa, mut sum_ea) = (0.0, 0.0, 0.0, 0.0, 0.0);
+            let mut n = 0.0;
+            for y in y0..(y0 + SSIM_WINDOW).min(height) {
+                for x in x0..(x0 + SSIM_WINDOW).min(width) {
+                    let e = luminance(expected[y * width + x]);
+                    let a = luminance(actual[y * width + x]);
+                    sum_e += e;
+                    sum_a += a;
+                    sum_ee += e * e;
+                    sum_aa += a * a;
+                    sum_ea += e * a;
+                    n += 1.0;
+                }
+            }
+            let (mean_e, mean_a) = (sum_e / n, sum_a / n);
+            let variance_e = sum_ee / n - mean_e * mean_e;
+            let variance_a = sum_aa / n - mean_a * mean_a;
+            let covariance = sum_ea / n - mean_e * mean_a;
+
+            total += ((2.0 * mean_e * mean_a + C1) * (2.0 * covariance + C2))
+                / ((mean_e * mean_e + mean_a * mean_a + C1) * (variance_e + variance_a + C2));
+            windows += 1;
+        }
+    }
+    total / windows as f64
+}
+
+// Diferencia por canal multiplicada por 4 para que se vea; los píxeles distintos van en rojo puro
+fn diff_image(expected: &[u32], actual: &[u32]) -> Vec<u32> {
+    expected
+        .iter()
+        .zip(actual)
+        .map(|(&e, &a)| {
+            let deltas: Vec<u8> = channels(e).into_iter().zip(channels(a)).map(|(ce, ca)| ce.abs_diff(ca)).collect();
+            if deltas.iter().any(|&d| d > PIXEL_THRESHOLD) {
+                return 0xFF0000;
+            }
+            deltas.iter().fold(0, |pixel, &d| pixel << 8 | (d as u32 * 4).min(255))
+        })
+        .collect()
+}
+
+fn check(name: &str, shot: Shot) {
+    let actual = render(&shot);
+    let reference = manifest_path("tests/golden").join(format!("{}.ppm", name));
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(reference.parent().unwrap()).unwrap();
+        save_image(&reference, &actual, WIDTH, HEIGHT).unwrap();
+        return;
+    }
+
+    let (expected, width, height) = load_ppm(&reference).unwrap_or_else(|e| {
+        panic!("{}: no se pudo leer la referencia ({}); UPDATE_GOLDEN=1 cargo test la genera", name, e)
+    });
+    assert_eq!((width, height), (WIDTH, HEIGHT), "{}: la referencia tiene otro tamaño", name);
+
+    let comparison = compare(&expected, &actual, WIDTH, HEIGHT);
+    if !comparison.passes() {
+        let dir = manifest_path("target/golden-diff");
+        std::fs::create_dir_all(&dir).unwrap();
+        save_image(&dir.join(format!("{}_obtenida.ppm", name)), &actual, WIDTH, HEIGHT).unwrap();
+        save_image(&dir.join(format!("{}_esperada.ppm", name)), &expected, WIDTH, HEIGHT).unwrap();
+        save_image(&dir.join(format!("{}_diferencia.ppm", name)), &diff_image(&expected, &actual), WIDTH, HEIGHT).unwrap();
+        panic!("{}: la imagen no coincide con la referencia: {}. Imágenes en {}", name, comparison, dir.display());
+    }
+}
+
+#[test]
+fn compare_identical_images() {
+    let image: Vec<u32> = (0..(WIDTH * HEIGHT) as u32).map(|i| i.wrapping_mul(2_654_435_761) & 0xFFFFFF).collect();
+    let comparison = compare(&image, &image, WIDTH, HEIGHT);
+    assert!(comparison.passes());
+    assert_eq!(comparison.psnr, f64::INFINITY);
+    assert!((comparison.ssim - 1.0).abs() < 1e-9);
+    assert_eq!(comparison.max_delta, 0);
+}
+
+#[test]
+fn compare_detects_changes() {
+    let expected = vec![0x202020; WIDTH * HEIGHT];
+
+    // Un poco de ruido de redondeo pasa
+    let rounding: Vec<u32> = (0..WIDTH * HEIGHT).map(|i| if i % 7 == 0 { 0x212120 } else { 0x202020 }).collect();
+    assert!(compare(&expected, &rounding, WIDTH, HEIGHT).passes());
+
+    // Un cuadrado que falta no
+    let mut missing = expected.clone();
+    for y in 40..60 {
+        for x in 60..80 {
+            missing[y * WIDTH + x] = 0xFFFFFF;
+        }
+    }
+    let comparison = compare(&expected, &missing, WIDTH, HEIGHT);
+    assert!(!comparison.passes());
+    assert_eq!(comparison.max_delta, 0xFF - 0x20);
+    assert_eq!(diff_image(&expected, &missing)[50 * WIDTH + 70], 0xFF0000);
+}
+
+// Todos los shaders de planetas, las órbitas y el fondo de estrellas
+#[test]
+fn golden_overview() {
+    check("overview", Shot::new(|scene| preset("overview", scene), 0.0));
+}
+
+#[test]
+fn golden_overview_msaa() {
+    let mut shot = Shot::new(|scene| preset("overview", scene), 0.0);
+    shot.anti_aliasing = AntiAliasing::Msaa(4);
+    check("overview_msaa4", shot);
+}
+
+// Tierra, Luna y la nave con su shadow map
+#[test]
+fn golden_earth_close_up() {
+    check("earth", Shot::new(|scene| preset("Tierra", scene), 12.0));
+}
+
+#[test]
+fn golden_jupiter_close_up() {
+    check("jupiter", Shot::new(|scene| preset("Júpiter", scene), 30.0));
+}
+
+// Shader del Sol, bloom y destellos de lente
+#[test]
+fn golden_sun_close_up() {
+    check("sun", Shot::new(|scene| preset("Sol", scene), 0.0));
+}
+
+// Mirando hacia afuera del sistema: solo estrellas
+#[test]
+fn golden_skybox() {
+    check("skybox", Shot::new(|_| look_at(Vec3::new(0.0, 0.0, 40.0), Vec3::new(0.0, 0.0, 80.0)), 0.0));
+}
+
+// La cámara casi toca la Tierra: triángulos que cruzan el plano cercano y los bordes de la pantalla
+#[test]
+fn golden_near_plane_clipping() {
+    let camera = |scene: &Scene| {
+        let earth = scene.find("Tierra").unwrap();
+        look_at(earth.position + Vec3::new(0.0, 0.0, earth.radius() * 1.2), earth.position)
+    };
+    check("near_clipping", Shot::new(camera, 5.0));
+}
+
+// Órbitas que pasan por detrás de la cámara y salen de la pantalla
+#[test]
+fn golden_orbit_clipping() {
+    check("orbit_clipping", Shot::new(|_| look_at(Vec3::new(0.0, 0.5, 7.0), Vec3::new(0.0, 0.0, 0.0)), 0.0));
+}
+
+// Normales como color con las aristas encima
+#[test]
+fn golden_debug_views() {
+    let camera = |scene: &Scene| {
+        let earth = scene.find("Tierra").unwrap();
+        look_at(earth.position + Vec3::new(0.0, 0.5, 1.0).normalize() * earth.radius() * 4.0, earth.position)
+    };
+    let mut shot = Shot::new(camera, 12.0);
+    shot.debug = DebugOptions::from_spec("normals,wireframe").unwrap();
+    check("debug_normals_wireframe", shot);
+}
@@ -0,0 +1,373 @@
+use crate::framebuffer::{Color, Framebuffer};
+use crate::capture::civil_from_days;
+use crate::scene::CelestialBody;
+use crate::profiler::{ProfileSummary, Stage};
+use std::f32::consts::PI;
+
+// Teclas y lo que hacen; se imprimen al arrancar y se muestran en el HUD completo
+pub const CONTROLS: &[(&str, &str)] = &[
+    ("Flechas", "Orbitar (izq/der) y zoom (arriba/abajo)"),
+    ("W S", "Subir / bajar la órbita"),
+    ("Mouse izq", "Orbitar / mirar (vuelo)"),
+    ("Mouse der", "Desplazar el centro"),
+    ("Rueda", "Zoom / avanzar (vuelo)"),
+    ("Tab", "Cámara: órbita / vuelo / seguimiento"),
+    ("G", "Seguimiento: fijo / persecución / padre"),
+    ("WASD", "Vuelo: avanzar y desplazarse"),
+    ("Esp Ctrl", "Vuelo: subir / bajar"),
+    ("Flechas Z X", "Vuelo: girar y rolar"),
+    ("Shift", "Vuelo: turbo"),
+    ("1-9", "Warp (1 = Sol, después planetas y lunas)"),
+    ("N", "Warp al siguiente cuerpo (Shift: anterior)"),
+    ("0", "Vista general del sistema"),
+    ("L", "Iluminación Lambert / Blinn-Phong / PBR"),
+    ("T", "Tone mapping (Clamp / Reinhard / ACES)"),
+    ("E  + -", "Exposición automática / manual"),
+    ("B", "Bloom (off / Gaussiano / Dual Kawase)"),
+    ("F", "Destellos de lente"),
+    ("P O [ ]", "Post-proceso: elegir, activar, mover"),
+    ("U", "Filtro de escalado"),
+    (", .", "Escala de render"),
+    ("Y", "Resolución dinámica"),
+    ("F11", "Pantalla completa"),
+    ("C", "Captura de pantalla"),
+    ("V", "Grabar on/off"),
+    ("H", "HUD: off / datos / datos y controles"),
+    ("F1", "Vista de depuración"),
+    ("F2 F3", "Wireframe / normales y tangentes"),
+    ("F6", "Ejes de rotación y ecuadores"),
+    ("F4", "Perfil de tiempos por etapa"),
+    ("F5", "Exportar perfil (CSV y traza JSON)"),
+    ("ESC", "Salir"),
+];
+
+// Cuadros que se promedian en el panel del perfil
+pub const PROFILE_AVERAGE_FRAMES: usize = 60;
+
+// Día 0 de la simulación: 1 de enero de 2000 (días desde 1970-01-01)
+const EPOCH_DAYS: i64 = 10_957;
+const DAYS_PER_YEAR: f32 = 365.25;
+
+const TEXT_COLOR: Color = Color::new(230, 230, 240);
+const TITLE_COLOR: Color = Color::new(255, 210, 120);
+const KEY_COLOR: Color = Color::new(140, 190, 255);
+const BAR_COLOR: Color = Color::new(120, 220, 140);
+const BUDGET_COLOR: Color = Color::new(255, 120, 100);
+const MARKER_COLOR: Color = Color::new(255, 210, 120);
+// Tamaño del gráfico de tiempos por cuadro, en columnas y líneas de texto
+const GRAPH_COLUMNS: usize = 36;
+const GRAPH_LINES: usize = 5;
+// Columnas de texto que se dejan libres para las barras del perfil
+const BAR_COLUMNS: usize = 14;
+// Nombre de la etapa (13) y milisegundos (7 + " ms")
+const STAGE_COLUMNS: usize = 23;
+const PANEL_COLOR: Color = Color::new(0, 0, 0);
+const PANEL_ALPHA: f32 = 0.55;
+const MARGIN: i32 = 8;
+const PADDING: i32 = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HudMode {
+    Off,
+    Stats,
+    Full,
+}
+
+impl HudMode {
+    pub fn next(self) -> Self {
+        match self {
+            HudMode::Off => HudMode::Stats,
+            HudMode::Stats => HudMode::Full,
+            HudMode::Full => HudMode::Off,
+        }
+    }
+}
+
+// Lo que el HUD necesita saber del cuadro actual
+pub struct HudInfo<'a> {
+    pub time: f32,
+    pub time_scale: f32,
+    // Segundos de simulación que dura un año (la órbita de la Tierra), si la escena la tiene
+    pub year_length: Option<f32>,
+    pub focus: Option<&'a CelestialBody>,
+    pub focus_parent: Option<&'a str>,
+    pub camera_distance: f32,
+    pub camera_mode: String,
+    pub render_size: (usize, usize),
+    pub render_scale: f32,
+    // Vistas de depuración activas, si hay alguna
+    pub debug: Option<String>,
+    // Promedio de los últimos cuadros, si el panel del perfil está abierto
+    pub profile: Option<ProfileSummary>,
+    // Tiempo por cuadro buscado, marcado en el gráfico del perfil
+    pub frame_budget_ms: f32,
+    // Centro y radio en pantalla del cuerpo enfocado, si está delante de la cámara
+    pub focus_marker: Option<(f32, f32, f32)>,
+}
+
+// Texto superpuesto: FPS, fecha y velocidad de la simulación, cuerpo enfocado y controles
+pub struct Hud {
+    pub mode: HudMode,
+    // Panel con los tiempos por etapa (F4), independiente del modo
+    pub show_profile: bool,
+    // Promedio exponencial para que los números no salten cada cuadro
+    frame_time: f32,
+}
+
+impl Hud {
+    pub fn new() -> Self {
+        Hud {
+            mode: HudMode::Stats,
+            show_profile: false,
+            frame_time: 1.0 / 60.0,
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        if delta_time > 0.0 {
+            self.frame_time += (delta_time - self.frame_time) * 0.1;
+        }
+    }
+
+    pub fn draw(&self, framebuffer: &mut Framebuffer, info: &HudInfo) {
+        // Letras más grandes en pantallas grandes
+        let scale = (framebuffer.height / 600).max(1);
+
+        // El perfil se muestra aunque el resto del HUD esté apagado
+        if let Some(summary) = &info.profile {
+            let profile_y = draw_profile(framebuffer, summary, scale);
+            draw_frame_graph(framebuffer, summary, info.frame_budget_ms, profile_y, scale);
+        }
+        if self.mode == HudMode::Off {
+            return;
+        }
+
+        let mut stats = vec![
+            format!("{:.0} FPS  {:.1} ms", 1.0 / self.frame_time, self.frame_time * 1000.0),
+            format!(
+                "Render {}x{} ({:.0}%)",
+                info.render_size.0, info.render_size.1, info.render_scale * 100.0
+            ),
+        ];
+        match info.year_length {
+            Some(year_length) => {
+                let days_per_second = DAYS_PER_YEAR / year_length;
+                stats.push(format!("Fecha {}", sim_date(info.time / year_length * DAYS_PER_YEAR)));
+                stats.push(format!(
+                    "Velocidad x{:.1} ({:.1} días/s)",
+                    info.time_scale,
+                    days_per_second * info.time_scale
+                ));
+            }
+            None => {
+                stats.push(format!("Tiempo {:.1} s", info.time));
+                stats.push(format!("Velocidad x{:.1}", info.time_scale));
+            }
+        }
+        stats.push(format!("Cámara: {}", info.camera_mode));
+        if let Some(debug) = &info.debug {
+            stats.push(format!("Depuración: {}", debug));
+        }
+        draw_panel(framebuffer, MARGIN, MARGIN, None, &stats, scale);
+
+        if let Some(body) = info.focus {
+            let lines = body_stats(body, info);
+            let (width, height) = panel_size(Some(&body.name), &lines, scale);
+            let y = framebuffer.height as i32 - MARGIN - height as i32;
+            draw_panel(framebuffer, MARGIN, y, Some(&body.name), &lines, scale);
+            if let Some(marker) = info.focus_marker {
+                draw_focus_marker(framebuffer, marker, ((MARGIN + width as i32) as f32, y as f32), scale);
+            }
+        }
+
+        if self.mode == HudMode::Full {
+            draw_controls(framebuffer, scale);
+        }
+    }
+}
+
+// Fecha del calendario a partir de los días desde el inicio de la simulación
+fn sim_date(days: f32) -> String {
+    let (year, month, day) = civil_from_days(EPOCH_DAYS + days.floor() as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Períodos en días si la escena tiene calendario, si no en segundos de simulación
+fn period(speed: f32, year_length: Option<f32>) -> String {
+    if speed == 0.0 {
+        return "-".to_string();
+    }
+    let seconds = 2.0 * PI / speed.abs();
+    match year_length {
+        Some(year_length) => format!("{:.1} días", seconds / year_length * DAYS_PER_YEAR),
+        None => format!("{:.1} s", seconds),
+    }
+}
+
+fn body_stats(body: &CelestialBody, info: &HudInfo) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(parent) = info.focus_parent {
+        lines.push(format!("Orbita a {}", parent));
+    }
+    lines.push(format!("Radio {:.2}", body.radius()));
+    if body.orbit_radius > 0.0 {
+        lines.push(format!("Órbita {:.1}, año {}", body.orbit_radius, period(body.orbit_speed, info.year_length)));
+    }
+    lines.push(format!("Día {}", period(body.rotation_speed, info.year_length)));
+    lines.push(format!("Distancia a la cámara {:.1}", info.camera_distance));
+    lines
+}
+
+fn panel_size(title: Option<&str>, lines: &[String], scale: usize) -> (usize, usize) {
+    let mut text = lines.join("\n");
+    if let Some(title) = title {
+        text = format!("{}\n{}", title, text);
+    }
+    let (width, height) = Framebuffer::text_size(&text, scale);
+    (width + 2 * PADDING as usize, height + 2 * PADDING as usize)
+}
+
+// Cuadro semitransparente con un título opcional y una línea de texto por elemento
+fn draw_panel(framebuffer: &mut Framebuffer, x: i32, y: i32, title: Option<&str>, lines: &[String], scale: usize) {
+    let (width, height) = panel_size(title, lines, scale);
+    framebuffer.fill_rect(x, y, width, height, PANEL_COLOR, PANEL_ALPHA);
+    framebuffer.draw_rect(x, y, width, height, TEXT_COLOR, 0.25);
+
+    let mut text_y = y + PADDING;
+    let line_height = Framebuffer::text_size("", scale).1 as i32;
+    if let Some(title) = title {
+        framebuffer.draw_text(x + PADDING, text_y, title, TITLE_COLOR, scale);
+        text_y += line_height;
+    }
+    for line in lines {
+        framebuffer.draw_text(x + PADDING, text_y, line, TEXT_COLOR, scale);
+        text_y += line_height;
+    }
+}
+
+// Lista de teclas en la esquina superior derecha
+fn draw_controls(framebuffer: &mut Framebuffer, scale: usize) {
+    let key_columns = CONTROLS.iter().map(|(key, _)| key.chars().count()).max().unwrap_or(0) + 2;
+    let lines: Vec<String> = CONTROLS
+        .iter()
+        .map(|(_, action)| format!("{}{}", " ".repeat(key_columns), action))
+        .collect();
+
+    let (width, _) = panel_size(Some("Controles"), &lines, scale);
+    let x = framebuffer.width as i32 - MARGIN - width as i32;
+    draw_panel(framebuffer, x, MARGIN, Some("Controles"), &lines, scale);
+
+    // Las teclas van encima de la sangría que dejó draw_panel, en otro color
+    let line_height = Framebuffer::text_size("", scale).1 as i32;
+    for (i, (key, _)) in CONTROLS.iter().enumerate() {
+        let y = MARGIN + PADDING + line_height * (i as i32 + 1);
+        framebuffer.draw_text(x + PADDING, y, key, KEY_COLOR, scale);
+    }
+}
+
+// Tiempo promedio de cada etapa con una barra relativa al cuadro, y los contadores, abajo a la derecha.
+// Devuelve dónde empieza el panel, para apilar otro encima.
+fn draw_profile(framebuffer: &mut Framebuffer, summary: &ProfileSummary, scale: usize) -> i32 {
+    let title = format!("Perfil ({} cuadros)", summary.frames);
+    let mut lines = vec![format!("{:<13}{:>7.2} ms", "total", summary.total_ms)];
+    for stage in Stage::ALL {
+        lines.push(format!(
+            "{:<13}{:>7.2} ms{}",
+            stage.name(),
+            summary.stage_ms[stage as usize],
+            " ".repeat(BAR_COLUMNS)
+        ));
+    }
+    let counters = &summary.counters;
+    lines.push(format!(
+        "Triángulos {} ({} descartados, {} dibujados)",
+        counters.triangles_submitted, counters.triangles_culled, counters.triangles_drawn
+    ));
+    lines.push(format!(
+        "Fragmentos {} ({} rechazados por z)",
+        counters.fragments_shaded, counters.depth_rejects
+    ));
+
+    let (width, height) = panel_size(Some(&title), &lines, scale);
+    let x = framebuffer.width as i32 - MARGIN - width as i32;
+    let y = framebuffer.height as i32 - MARGIN - height as i32;
+    draw_panel(framebuffer, x, y, Some(&title), &lines, scale);
+
+    // Las barras van en el espacio que dejó cada línea de etapa, después de "nombre  0.00 ms "
+    let (column_width, line_height) = Framebuffer::text_size(" ", scale);
+    let bar_x = column_width * (STAGE_COLUMNS + 1);
+    let max_width = column_width * (BAR_COLUMNS - 1);
+    for (i, stage) in Stage::ALL.iter().enumerate() {
+        let fraction = (summary.stage_ms[*stage as usize] / summary.total_ms.max(1e-3)).clamp(0.0, 1.0);
+        let bar_width = (fraction * max_width as f32).round() as usize;
+        if bar_width == 0 {
+            continue;
+        }
+        // Línea 0 = título, línea 1 = total
+        let bar_y = y + PADDING + (line_height * (i + 2)) as i32 + scale as i32;
+        framebuffer.fill_rect(
+            x + PADDING + bar_x as i32,
+            bar_y,
+            bar_width,
+            line_height - 3 * scale,
+            BAR_COLOR,
+            0.8,
+        );
+    }
+    y
+}
+
+// Duración de los últimos cuadros sobre el panel del perfil, con el tiempo buscado punteado
+fn draw_frame_graph(framebuffer: &mut Framebuffer, summary: &ProfileSummary, budget_ms: f32, bottom: i32, scale: usize) {
+    let title = "Tiempo por cuadro";
+    let lines = vec![" ".repeat(GRAPH_COLUMNS); GRAPH_LINES];
+    let (width, height) = panel_size(Some(title), &lines, scale);
+    let x = framebuffer.width as i32 - MARGIN - width as i32;
+    let y = bottom - MARGIN - height as i32;
+    draw_panel(framebuffer, x, y, Some(title), &lines, scale);
+
+    // Área del gráfico: lo que queda debajo del título
+    let line_height = Framebuffer::text_size("", scale).1 as i32;
+    let (left, top) = (x + PADDING, y + PADDING + line_height);
+    let (graph_width, graph_height) = (width as i32 - 2 * PADDING, line_height * GRAPH_LINES as i32);
+    framebuffer.draw_rect(left, top, graph_width as usize, graph_height as usize, TEXT_COLOR, 0.4);
+
+    // El cuadro más lento o el doble del tiempo buscado llegan arriba
+    let max_ms = summary.frame_ms.iter().copied().fold(budget_ms * 2.0, f32::max);
+    let to_y = |ms: f32| (top + graph_height - 1) as f32 - ms / max_ms * (graph_height - 2) as f32;
+    let budget_y = to_y(budget_ms);
+    let dash = 4.0 * scale as f32;
+    framebuffer.draw_dashed_line(
+        (left as f32, budget_y),
+        ((left + graph_width - 1) as f32, budget_y),
+        dash,
+        dash * 0.75,
+        BUDGET_COLOR,
+        0.8,
+    );
+
+    let step = (graph_width - 2) as f32 / (summary.frame_ms.len().max(2) - 1) as f32;
+    let points: Vec<(f32, f32)> = summary
+        .frame_ms
+        .iter()
+        .enumerate()
+        .map(|(i, &ms)| ((left + 1) as f32 + i as f32 * step, to_y(ms)))
+        .collect();
+    framebuffer.draw_polyline(&points, false, BAR_COLOR, 1.0);
+    if let Some(&(last_x, last_y)) = points.last() {
+        framebuffer.fill_circle(last_x, last_y, 1.5 * scale as f32, BAR_COLOR, 1.0);
+    }
+}
+
+// Anillo alrededor del cuerpo enfocado y una línea hasta el panel con sus datos
+fn draw_focus_marker(framebuffer: &mut Framebuffer, (x, y, radius): (f32, f32, f32), panel_corner: (f32, f32), scale: usize) {
+    let ring = radius + 4.0 * scale as f32;
+    let (dx, dy) = (panel_corner.0 - x, panel_corner.1 - y);
+    let distance = (dx * dx + dy * dy).sqrt();
+    framebuffer.draw_circle(x, y, ring, MARKER_COLOR, 0.8);
+    // Sin línea si el anillo ya toca el panel
+    if distance > ring + 8.0 * scale as f32 {
+        let start = (x + dx / distance * ring, y + dy / distance * ring);
+        framebuffer.draw_thick_line(start, panel_corner, scale as f32, MARKER_COLOR, 0.5);
+    }
+}
@@ -0,0 +1,199 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+// Guarda un buffer 0xRRGGBB; el formato sale de la extensión (.ppm o .bmp)
+pub fn save_image(path: &Path, pixels: &[u32], width: usize, height: usize) -> io::Result<()> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    match extension.as_str() {
+        "ppm" => write_ppm(&mut writer, pixels, width, height)?,
+        "bmp" => write_bmp(&mut writer, pixels, width, height)?,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("formato de imagen no soportado: {} (usa .ppm o .bmp)", path.display()),
+            ))
+        }
+    }
+    writer.flush()
+}
+
+pub fn is_supported_format(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref(),
+        Some("ppm") | Some("bmp")
+    )
+}
+
+// Video sin comprimir: todos los cuadros van a un solo archivo .y4m
+pub fn is_video_format(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() == Some("y4m")
+}
+
+// PPM binario (P6)
+pub fn write_ppm(writer: &mut impl Write, pixels: &[u32], width: usize, height: usize) -> io::Result<()> {
+    write!(writer, "P6\n{} {}\n255\n", width, height)?;
+    let mut data = Vec::with_capacity(width * height * 3);
+    for &pixel in &pixels[..width * height] {
+        data.extend_from_slice(&[(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8]);
+    }
+    writer.write_all(&data)
+}
+
+// Lee un PPM binario (P6) de 8 bits como buffer 0xRRGGBB; devuelve (píxeles, ancho, alto)
+// Solo lo usan las pruebas de imágenes de referencia
+#[cfg(test)]
+pub fn load_ppm(path: &Path) -> io::Result<(Vec<u32>, usize, usize)> {
+    let bytes = std::fs::read(path)?;
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), message));
+
+    // Cabecera: cuatro campos separados por espacios, con comentarios `#` hasta el fin de línea
+    let mut fields = Vec::new();
+    let mut position = 0;
+    while fields.len() < 4 {
+        while position < bytes.len() && (bytes[position].is_ascii_whitespace() || bytes[position] == b'#') {
+            if bytes[position] == b'#' {
+                while position < bytes.len() && bytes[position] != b'\n' {
+                    position += 1;
+                }
+            } else {
+                position += 1;
+            }
+        }
+        let start = position;
+        while position < bytes.len() && !bytes[position].is_ascii_whitespace() {
+            position += 1;
+        }
+        if start == position {
+            return Err(invalid("cabecera PPM incompleta"));
+        }
+        fields.push(String::from_utf8_lossy(&bytes[start..position]).into_owned());
+    }
+    // Un solo espacio separa la cabecera de los datos
+    position += 1;
+
+    if fields[0] != "P6" || fields[3] != "255" {
+        return Err(invalid("solo se leen PPM binarios (P6) de 8 bits"));
+    }
+    let (width, height) = match (fields[1].parse::<usize>(), fields[2].parse::<usize>()) {
+        (Ok(w), Ok(h)) => (w, h),
+        _ => return Err(invalid("tamaño inválido")),
+    };
+    let data = bytes.get(position..position + width * height * 3).ok_or_else(|| invalid("faltan píxeles"))?;
+
+    let pixels = data
+        .chunks_exact(3)
+        .map(|rgb| (rgb[0] as u32) << 16 | (rgb[1] as u32) << 8 | rgb[2] as u32)
+        .collect();
+    Ok((pixels, width, height))
+}
+
+// BMP de 24 bits sin compresión; las filas van de abajo hacia arriba y se rellenan a 4 bytes
+pub fn write_bmp(writer: &mut impl Write, pixels: &[u32], width: usize, height: usize) -> io::Result<()> {
+    let row_size = (width * 3).div_ceil(4) * 4;
+    let data_size = row_size * height;
+    let header_size = 14 + 40;
+
+    writer.write_all(b"BM")?;
+    writer.write_all(&((header_size + data_size) as u32).to_le_bytes())?;
+    writer.write_all(&0u32.to_le_bytes())?;
+    writer.write_all(&(header_size as u32).to_le_bytes())?;
+
+    writer.write_all(&40u32.to_le_bytes())?;
+    writer.write_all(&(width as i32).to_le_bytes())?;
+    writer.write_all(&(height as i32).to_le_bytes())?;
+    writer.write_all(&1u16.to_le_bytes())?;
+    writer.write_all(&24u16.to_le_bytes())?;
+    writer.write_all(&0u32.to_le_bytes())?;
+    writer.write_all(&(data_size as u32).to_le_bytes())?;
+    writer.write_all(&2835i32.to_le_bytes())?;
+    writer.write_all(&2835i32.to_le_bytes())?;
+    writer.write_all(&0u32.to_le_bytes())?;
+    writer.write_all(&0u32.to_le_bytes())?;
+
+    let mut row = vec![0u8; row_size];
+    for y in (0..height).rev() {
+        for x in 0..width {
+            let pixel = pixels[y * width + x];
+            row[x * 3] = pixel as u8;
+            row[x * 3 + 1] = (pixel >> 8) as u8;
+            row[x * 3 + 2] = (pixel >> 16) as u8;
+        }
+        writer.write_all(&row)?;
+    }
+    Ok(())
+}
+
+// Flujo YUV4MPEG2 en 4:2:0 (rango completo, BT.601) que entienden ffmpeg y mpv
+pub struct Y4mWriter {
+    writer: BufWriter<File>,
+    width: usize,
+    height: usize,
+}
+
+impl Y4mWriter {
+    pub fn create(path: &Path, width: usize, height: usize, fps: u32) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C420jpeg XCOLORRANGE=FULL", width, height, fps)?;
+        Ok(Y4mWriter { writer, width, height })
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    pub fn write_frame(&mut self, pixels: &[u32]) -> io::Result<()> {
+        let (width, height) = (self.width, self.height);
+        let chroma_width = width.div_ceil(2);
+        let chroma_height = height.div_ceil(2);
+
+        let rgb = |x: usize, y: usize| {
+            let pixel = pixels[y.min(height - 1) * width + x.min(width - 1)];
+            (
+                ((pixel >> 16) & 0xFF) as f32,
+                ((pixel >> 8) & 0xFF) as f32,
+                (pixel & 0xFF) as f32,
+            )
+        };
+
+        let mut luma = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let (r, g, b) = rgb(x, y);
+                luma.push((0.299 * r + 0.587 * g + 0.114 * b).round() as u8);
+            }
+        }
+
+        // Cada muestra de croma promedia un bloque de 2x2 píxeles
+        let mut cb = Vec::with_capacity(chroma_width * chroma_height);
+        let mut cr = Vec::with_capacity(chroma_width * chroma_height);
+        for cy in 0..chroma_height {
+            for cx in 0..chroma_width {
+                let (mut r, mut g, mut b) = (0.0, 0.0, 0.0);
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let (pr, pg, pb) = rgb(cx * 2 + dx, cy * 2 + dy);
+                    r += pr / 4.0;
+                    g += pg / 4.0;
+                    b += pb / 4.0;
+                }
+                cb.push((128.0 - 0.168736 * r - 0.331264 * g + 0.5 * b).round().clamp(0.0, 255.0) as u8);
+                cr.push((128.0 + 0.5 * r - 0.418688 * g - 0.081312 * b).round().clamp(0.0, 255.0) as u8);
+            }
+        }
+
+        self.writer.write_all(b"FRAME\n")?;
+        self.writer.write_all(&luma)?;
+        self.writer.write_all(&cb)?;
+        self.writer.write_all(&cr)
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,188 @@
+use crate::math::Vec3;
+use crate::shaders::{Fragment, Uniforms};
+use std::f32::consts::PI;
+
+// Modelo usado para el término especular
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightingModel {
+    // Solo difuso, sin término especular
+    Lambert,
+    BlinnPhong,
+    Pbr,
+}
+
+impl LightingModel {
+    pub fn next(self) -> Self {
+        match self {
+            LightingModel::Lambert => LightingModel::BlinnPhong,
+            LightingModel::BlinnPhong => LightingModel::Pbr,
+            LightingModel::Pbr => LightingModel::Lambert,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LightingModel::Lambert => "Lambert",
+            LightingModel::BlinnPhong => "Blinn-Phong",
+            LightingModel::Pbr => "PBR (GGX)",
+        }
+    }
+}
+
+// Propiedades de superficie compartidas por ambos modelos
+#[derive(Debug, Clone, Copy)]
+pub struct Material {
+    pub albedo: Vec3,
+    pub ambient: f32,
+    // Blinn-Phong
+    pub specular: f32,
+    pub shininess: f32,
+    // PBR
+    pub roughness: f32,
+    pub metallic: f32,
+}
+
+impl Material {
+    // Sin reflejo especular en ningún modelo
+    pub fn is_matte(&self) -> bool {
+        self.specular <= 0.0 && self.metallic <= 0.0
+    }
+
+    // Superficie sin brillo (rocas, cráteres)
+    pub fn matte(albedo: Vec3) -> Self {
+        Material {
+            albedo,
+            ambient: 0.15,
+            specular: 0.0,
+            shininess: 1.0,
+            roughness: 1.0,
+            metallic: 0.0,
+        }
+    }
+
+    // Superficie con reflejo especular (agua, hielo)
+    pub fn glossy(albedo: Vec3, specular: f32, shininess: f32, roughness: f32) -> Self {
+        Material {
+            albedo,
+            ambient: 0.15,
+            specular,
+            shininess,
+            roughness,
+            metallic: 0.0,
+        }
+    }
+}
+
+pub fn lambert(normal: &Vec3, light_dir: &Vec3) -> f32 {
+    normal.dot(light_dir).max(0.0)
+}
+
+pub fn blinn_phong(normal: &Vec3, light_dir: &Vec3, view_dir: &Vec3, shininess: f32) -> f32 {
+    if normal.dot(light_dir) <= 0.0 {
+        return 0.0;
+    }
+    let half = (*light_dir + *view_dir).normalize();
+    normal.dot(&half).max(0.0).powf(shininess)
+}
+
+// Cook-Torrance con distribución GGX, Smith-Schlick y Fresnel de Schlick.
+// Devuelve el término especular ya multiplicado por N·L.
+pub fn ggx_specular(normal: &Vec3, light_dir: &Vec3, view_dir: &Vec3, roughness: f32, f0: f32) -> f32 {
+    let n_dot_l = normal.dot(light_dir);
+    let n_dot_v = normal.dot(view_dir);
+    if n_dot_l <= 0.0 || n_dot_v <= 0.0 {
+        return 0.0;
+    }
+
+    let half = (*light_dir + *view_dir).normalize();
+    let n_dot_h = normal.dot(&half).max(0.0);
+    let v_dot_h = view_dir.dot(&half).max(0.0);
+
+    let alpha = (roughness * roughness).max(0.001);
+    let alpha2 = alpha * alpha;
+    let denom = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+    let distribution = alpha2 / (PI * denom * denom);
+
+    let k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    let g_v = n_dot_v / (n_dot_v * (1.0 - k) + k);
+    let g_l = n_dot_l / (n_dot_l * (1.0 - k) + k);
+    let geometry = g_v * g_l;
+
+    let fresnel = f0 + (1.0 - f0) * (1.0 - v_dot_h).powi(5);
+
+    distribution * geometry * fresnel / (4.0 * n_dot_v * n_dot_l) * n_dot_l
+}
+
+// Ilumina un fragmento con la luz del Sol usando el modelo seleccionado en los uniforms
+pub fn shade(material: &Material, fragment: &Fragment, uniforms: &Uniforms) -> Vec3 {
+    let normal = fragment.normal;
+    let light_dir = (uniforms.light_position - fragment.world_position).normalize();
+    let view_dir = (uniforms.camera_position - fragment.world_position).normalize();
+
+    let diffuse = lambert(&normal, &light_dir);
+    if diffuse <= 0.0 {
+        return material.albedo * material.ambient;
+    }
+    let visibility = uniforms.sun_visibility(fragment.world_position);
+
+    let specular = match uniforms.lighting_model {
+        _ if material.is_matte() => 0.0,
+        LightingModel::Lambert => 0.0,
+        LightingModel::BlinnPhong => {
+            material.specular * blinn_phong(&normal, &light_dir, &view_dir, material.shininess)
+        }
+        LightingModel::Pbr => {
+            // Agua y hielo tienen F0 ≈ 0.02-0.04; los metales usan el albedo
+            let f0 = 0.04 * (1.0 - material.metallic) + material.metallic;
+            ggx_specular(&normal, &light_dir, &view_dir, material.roughness, f0) * PI
+        }
+    };
+
+    let diffuse_weight = (1.0 - material.metallic) * diffuse * visibility;
+    let lit = material.albedo * (material.ambient + diffuse_weight);
+    let specular = specular * visibility;
+    lit + Vec3::new(specular, specular, specular)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit_fragment(model: LightingModel) -> (Fragment, Uniforms) {
+        let mut uniforms = Uniforms::new();
+        uniforms.lighting_model = model;
+        uniforms.light_position = Vec3::new(0.0, 10.0, 0.0);
+        // Cámara en la dirección del reflejo especular
+        uniforms.camera_position = Vec3::new(0.0, 10.0, 0.0);
+        let fragment = Fragment::new(
+            Vec3::zeros(),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::zeros(),
+            Vec3::zeros(),
+        );
+        (fragment, uniforms)
+    }
+
+    #[test]
+    fn matte_has_no_specular_in_any_model() {
+        let material = Material::matte(Vec3::new(0.5, 0.5, 0.5));
+        let diffuse_only = material.albedo * (material.ambient + 1.0);
+        for model in [LightingModel::Lambert, LightingModel::BlinnPhong, LightingModel::Pbr] {
+            let (fragment, uniforms) = lit_fragment(model);
+            let color = shade(&material, &fragment, &uniforms);
+            assert!((color - diffuse_only).magnitude() < 1e-5, "{}: {:?}", model.name(), color);
+        }
+    }
+
+    #[test]
+    fn glossy_glints_except_with_lambert() {
+        let material = Material::glossy(Vec3::new(0.1, 0.2, 0.5), 0.8, 64.0, 0.2);
+        let diffuse_only = material.albedo * (material.ambient + 1.0);
+        let (fragment, uniforms) = lit_fragment(LightingModel::Lambert);
+        assert!((shade(&material, &fragment, &uniforms) - diffuse_only).magnitude() < 1e-5);
+        for model in [LightingModel::BlinnPhong, LightingModel::Pbr] {
+            let (fragment, uniforms) = lit_fragment(model);
+            assert!(shade(&material, &fragment, &uniforms).x > diffuse_only.x + 0.1, "{}", model.name());
+        }
+    }
+}
@@ -6,518 +6,629 @@ mod obj;
 mod camera;
 mod planet_shaders;
 mod math;
+mod lighting;
+mod shadows;
+mod shadow_map;
+mod tonemap;
+mod bloom;
+mod postprocess;
+mod antialiasing;
+mod upscale;
+mod scene;
+mod renderer;
+mod image;
+mod cli;
+mod capture;
+mod font;
+mod hud;
+mod debug_view;
+mod profiler;
+mod benchmark;
+mod mouse;
+mod warp;
+mod display;
+
+#[cfg(test)]
+mod golden_tests;
 
-use framebuffer::{Framebuffer, Color};
 use minifb::{Key, Window, WindowOptions};
 use math::Vec3;
-use std::time::Duration;
-use std::f32::consts::PI;
-
-use shaders::{
-    Uniforms, 
-    vertex_shader,
-    Fragment,
-    create_model_matrix,
-    create_view_matrix,
-    create_perspective_matrix,
-    create_viewport_matrix,
-};
-
-use vertex::Vertex;
-use triangle::triangle;
-use obj::Obj;
-use camera::Camera;
-use planet_shaders::*;
-
-const WINDOW_WIDTH: usize = 800;
-const WINDOW_HEIGHT: usize = 600;
-const RENDER_WIDTH: usize = 700;
-const RENDER_HEIGHT: usize = 525;
-
-struct CelestialBody {
-    position: Vec3,
-    scale: f32,
-    rotation_speed: f32,
-    orbit_speed: f32,
-    orbit_radius: f32,
-    rotation: f32,
-    orbit_angle: f32,
-    shader: fn(&Fragment) -> Color,
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use camera::{Camera, CameraMode, FlyInput, FollowTarget};
+use mouse::MouseControl;
+use warp::{Warp, warp_targets, arrival_eye, OVERVIEW_EYE};
+use shadows::{EclipseTracker, EclipseKind};
+use scene::Scene;
+use renderer::{Renderer, render_size};
+use postprocess::PostChain;
+use upscale::{DynamicResolution, MIN_RENDER_SCALE, MAX_RENDER_SCALE};
+use hud::{Hud, HudInfo, CONTROLS, PROFILE_AVERAGE_FRAMES};
+use profiler::{Profiler, Stage};
+use benchmark::{BenchmarkStats, flight_camera, WARMUP_FRAMES};
+use capture::{Recorder, save_screenshot};
+use cli::{Config, usage, DEFAULT_TARGET_FRAME_MS};
+
+// Paso fijo de simulación sin ventana
+const HEADLESS_FPS: u32 = 60;
+const HEADLESS_FRAME_TIME: f32 = 1.0 / HEADLESS_FPS as f32;
+
+// Teclas de warp directo; el resto de los destinos se alcanzan con N
+const WARP_KEYS: [Key; 10] = [
+    Key::Key0, Key::Key1, Key::Key2, Key::Key3, Key::Key4,
+    Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9,
+];
+
+// Ventana redimensionable. En pantalla completa es sin bordes, encima de todo y del tamaño del
+// monitor; si no se puede consultar el monitor se usa `size`.
+fn create_window(size: (usize, usize), fullscreen: bool) -> Window {
+    let size = if fullscreen {
+        display::primary_size().unwrap_or_else(|| {
+            eprintln!("No se pudo consultar el tamaño del monitor; se usa {}x{}", size.0, size.1);
+            size
+        })
+    } else {
+        size
+    };
+    let options = WindowOptions {
+        resize: true,
+        borderless: fullscreen,
+        title: !fullscreen,
+        topmost: fullscreen,
+        ..WindowOptions::default()
+    };
+
+    let mut window = Window::new("Space System - Rust Graphics", size.0, size.1, options)
+        .unwrap_or_else(|e| {
+            eprintln!("No se pudo crear la ventana: {}", e);
+            std::process::exit(1);
+        });
+    if fullscreen {
+        window.set_position(0, 0);
+    }
+    window.set_target_fps(60);
+    window
 }
 
-impl CelestialBody {
-    fn new(orbit_radius: f32, scale: f32, rotation_speed: f32, orbit_speed: f32, shader: fn(&Fragment) -> Color) -> Self {
-        CelestialBody {
-            position: Vec3::new(orbit_radius, 0.0, 0.0),
-            scale,
-            rotation_speed,
-            orbit_speed,
-            orbit_radius,
-            rotation: 0.0,
-            orbit_angle: 0.0,
-            shader,
+// Cámara inicial: `default`, `overview`, `top` o el nombre del Sol o de un planeta
+fn camera_preset(name: &str, scene: &Scene) -> Option<Camera> {
+    let origin = Vec3::new(0.0, 0.0, 0.0);
+    let (eye, center) = match name {
+        "default" => (Vec3::new(0.0, 10.0, 20.0), origin),
+        "overview" => (OVERVIEW_EYE, origin),
+        "top" => (Vec3::new(0.0, 40.0, 0.01), origin),
+        _ => {
+            // Donde termina el warp a ese cuerpo
+            let body = scene.find(name)?;
+            (arrival_eye(body), body.position)
         }
-    }
-
-    fn update(&mut self, delta_time: f32) {
-        self.rotation += self.rotation_speed * delta_time;
-        self.orbit_angle += self.orbit_speed * delta_time;
-        
-        self.position.x = self.orbit_radius * self.orbit_angle.cos();
-        self.position.z = self.orbit_radius * self.orbit_angle.sin();
-    }
+    };
+    Some(Camera::new(eye, center, Vec3::new(0.0, 1.0, 0.0)))
 }
 
-fn render_skybox(framebuffer: &mut Framebuffer) {
-    for y in 0..RENDER_HEIGHT {
-        for x in 0..RENDER_WIDTH {
-            let seed = (x * 73856093) ^ (y * 19349663);
-            let rand_val = ((seed * 1103515245 + 12345) / 65536) % 10000;
-            
-            if rand_val < 100 {
-                let brightness = ((rand_val % 3) as f32 / 2.0) * 0.6 + 0.6;
-                let color = Color::from_float(brightness, brightness, brightness);
-                framebuffer.point_with_color(x, y, 1000.0, color);
-            }
+// Registrar en consola los eclipses y tránsitos que empiezan o terminan
+fn report_eclipses(eclipses: &mut EclipseTracker, scene: &Scene, time: f32) {
+    for event in eclipses.update(time, scene.sun.position, scene.sun.radius(), &scene.occluders()) {
+        let kind = match event.kind {
+            EclipseKind::Eclipse => "Eclipse",
+            EclipseKind::Transit => "Tránsito",
+        };
+        if event.started {
+            println!(
+                "[t={:.1}s] {} en {}: {} tapa el Sol",
+                event.time, kind, scene.planets[event.target].name, scene.planets[event.occluder].name
+            );
+        } else {
+            println!(
+                "[t={:.1}s] Fin de {} en {} (máximo {:.0}% del Sol cubierto)",
+                event.time, kind.to_lowercase(), scene.planets[event.target].name, event.max_coverage * 100.0
+            );
         }
     }
 }
 
-fn render_orbit(planet: &CelestialBody, framebuffer: &mut Framebuffer, uniforms: &Uniforms) {
-    let segments = 150;
-    let orbit_color = Color::new(60, 60, 80);
-    
-    for i in 0..segments {
-        let angle1 = (i as f32 / segments as f32) * 2.0 * PI;
-        let angle2 = ((i + 1) as f32 / segments as f32) * 2.0 * PI;
-        
-        let p1 = Vec3::new(
-            planet.orbit_radius * angle1.cos(),
-            0.0,
-            planet.orbit_radius * angle1.sin()
-        );
-        let p2 = Vec3::new(
-            planet.orbit_radius * angle2.cos(),
-            0.0,
-            planet.orbit_radius * angle2.sin()
-        );
-        
-        use crate::math::Vec4;
-        let pos1_4d = Vec4::new(p1.x, p1.y, p1.z, 1.0);
-        let pos2_4d = Vec4::new(p2.x, p2.y, p2.z, 1.0);
-        
-        let transformed1 = uniforms.projection_matrix * uniforms.view_matrix * pos1_4d;
-        let transformed2 = uniforms.projection_matrix * uniforms.view_matrix * pos2_4d;
-        
-        if transformed1.w.abs() < 0.001 || transformed2.w.abs() < 0.001 {
-            continue;
-        }
-        
-        let ndc1 = Vec4::new(
-            transformed1.x / transformed1.w,
-            transformed1.y / transformed1.w,
-            transformed1.z / transformed1.w,
-            1.0
-        );
-        let ndc2 = Vec4::new(
-            transformed2.x / transformed2.w,
-            transformed2.y / transformed2.w,
-            transformed2.z / transformed2.w,
-            1.0
-        );
-        
-        if ndc1.x.abs() > 2.0 || ndc1.y.abs() > 2.0 || ndc2.x.abs() > 2.0 || ndc2.y.abs() > 2.0 {
-            continue;
-        }
-        
-        if ndc1.z < -1.0 || ndc1.z > 1.0 || ndc2.z < -1.0 || ndc2.z > 1.0 {
-            continue;
-        }
-        
-        let screen1 = uniforms.viewport_matrix * ndc1;
-        let screen2 = uniforms.viewport_matrix * ndc2;
-        
-        if !screen1.x.is_finite() || !screen1.y.is_finite() || 
-           !screen2.x.is_finite() || !screen2.y.is_finite() ||
-           !screen1.z.is_finite() || !screen2.z.is_finite() {
-            continue;
-        }
-        let sx1 = screen1.x as i32;
-        let sy1 = screen1.y as i32;
-        let sx2 = screen2.x as i32;
-        let sy2 = screen2.y as i32;
-        
-        if sx1.abs() > 10000 || sy1.abs() > 10000 || sx2.abs() > 10000 || sy2.abs() > 10000 {
-            continue;
-        }
-        
-        draw_line(
-            sx1, sy1,
-            sx2, sy2,
-            framebuffer,
-            orbit_color,
-            screen1.z
-        );
+// "seguimiento de Tierra (persecución)" o el nombre del modo
+fn describe_camera(camera: &Camera, focus: Option<&str>) -> String {
+    match (camera.mode, focus) {
+        (CameraMode::Follow, Some(name)) => format!("{} de {} ({})", camera.mode.name(), name, camera.follow_style.name()),
+        _ => camera.mode.name().to_string(),
     }
 }
 
-fn draw_line(x0: i32, y0: i32, x1: i32, y1: i32, framebuffer: &mut Framebuffer, color: Color, depth: f32) {
-    if !depth.is_finite() {
-        return;
+// Teclas del vuelo libre: cada eje vale -1, 0 o 1
+fn fly_input(window: &Window) -> FlyInput {
+    let axis = |positive: Key, negative: Key| {
+        window.is_key_down(positive) as i32 as f32 - window.is_key_down(negative) as i32 as f32
+    };
+    FlyInput {
+        thrust: Vec3::new(axis(Key::D, Key::A), axis(Key::Space, Key::LeftCtrl), axis(Key::W, Key::S)),
+        turn: Vec3::new(axis(Key::Up, Key::Down), axis(Key::Left, Key::Right), axis(Key::Z, Key::X)),
+        boost: window.is_key_down(Key::LeftShift),
     }
-    
-    let distance = ((x1 - x0).pow(2) + (y1 - y0).pow(2)) as f32;
-    if distance > 1000000.0 {
-        return;
+}
+
+fn save_profile(profiler: &Profiler, path: &Path) {
+    match profiler.save(path) {
+        Ok(()) => println!("Perfil guardado: {} ({} cuadros)", path.display(), profiler.frames().count()),
+        Err(e) => eprintln!("No se pudo guardar el perfil {}: {}", path.display(), e),
     }
-    
-    let dx = (x1 - x0).abs();
-    let dy = (y1 - y0).abs();
-    let sx = if x0 < x1 { 1 } else { -1 };
-    let sy = if y0 < y1 { 1 } else { -1 };
-    let mut err = dx - dy;
-    let mut x = x0;
-    let mut y = y0;
-    
-    let mut steps = 0;
-    let max_steps = 2000;
-    
-    loop {
-        if x >= 0 && x < RENDER_WIDTH as i32 && y >= 0 && y < RENDER_HEIGHT as i32 {
-            framebuffer.point_with_color(x as usize, y as usize, depth, color);
-        }
-        
-        if x == x1 && y == y1 {
-            break;
+}
+
+// Renderiza `config.frames` cuadros con paso fijo y los guarda sin abrir ventana
+fn run_headless(config: &Config, mut scene: Scene, camera: Camera, mut renderer: Renderer) {
+    let mut eclipses = EclipseTracker::new();
+    let mut time = config.start_time;
+
+    let output = config.output.as_ref().expect("sin ruta de salida");
+    let mut video = if image::is_video_format(output) {
+        let (width, height) = renderer.output_size;
+        let writer = image::Y4mWriter::create(output, width, height, HEADLESS_FPS).unwrap_or_else(|e| {
+            eprintln!("No se pudo crear {}: {}", output.display(), e);
+            std::process::exit(1);
+        });
+        Some(writer)
+    } else {
+        None
+    };
+
+    for frame in 0..config.frames {
+        if frame > 0 {
+            let delta_time = HEADLESS_FRAME_TIME * config.time_scale;
+            scene.update(delta_time);
+            time += delta_time;
         }
-        
-        steps += 1;
-        if steps > max_steps {
-            break;
+        report_eclipses(&mut eclipses, &scene, time);
+
+        renderer.render(&scene, &camera, time, HEADLESS_FRAME_TIME);
+
+        let path = config.output_path(frame).expect("sin ruta de salida");
+        let (width, height) = renderer.output_size;
+        let stage_start = Instant::now();
+        let result = match video.as_mut() {
+            Some(writer) => writer.write_frame(&renderer.output.buffer),
+            None => image::save_image(&path, &renderer.output.buffer, width, height),
+        };
+        renderer.profiler.record(Stage::Present, stage_start);
+        renderer.profiler.end_frame();
+        if let Err(e) = result {
+            eprintln!("No se pudo guardar {}: {}", path.display(), e);
+            std::process::exit(1);
         }
-        
-        let e2 = 2 * err;
-        if e2 > -dy {
-            err -= dy;
-            x += sx;
+        if video.is_none() {
+            println!("Guardado {} ({}x{})", path.display(), width, height);
         }
-        if e2 < dx {
-            err += dx;
-            y += sy;
+    }
+
+    if let Some(writer) = video {
+        if let Err(e) = writer.finish() {
+            eprintln!("No se pudo guardar {}: {}", output.display(), e);
+            std::process::exit(1);
         }
+        println!("Guardado {} ({} cuadros)", output.display(), config.frames);
     }
-}
 
-fn render_obj_with_shader(
-    obj: &Obj, 
-    framebuffer: &mut Framebuffer, 
-    uniforms: &Uniforms,
-    shader: fn(&Fragment) -> Color
-) {
-    let transformed_vertices: Vec<Vertex> = obj.vertices
-        .iter()
-        .map(|v| vertex_shader(v, uniforms))
-        .collect();
-
-    for i in (0..obj.indices.len()).step_by(3) {
-        if i + 2 >= obj.indices.len() {
-            break;
-        }
-        
-        let idx1 = obj.indices[i];
-        let idx2 = obj.indices[i + 1];
-        let idx3 = obj.indices[i + 2];
-        
-        if idx1 >= transformed_vertices.len() || 
-           idx2 >= transformed_vertices.len() || 
-           idx3 >= transformed_vertices.len() {
-            continue;
-        }
-
-        let v1 = &transformed_vertices[idx1];
-        let v2 = &transformed_vertices[idx2];
-        let v3 = &transformed_vertices[idx3];
-
-        if v1.position.z < 0.0 || v2.position.z < 0.0 || v3.position.z < 0.0 {
-            continue;
-        }
-
-        triangle(v1, v2, v3, framebuffer, shader);
+    if let Some(path) = &config.profile {
+        save_profile(&renderer.profiler, path);
     }
 }
 
-fn scale_buffer(src: &[u32], dst: &mut [u32], src_w: usize, src_h: usize, dst_w: usize, dst_h: usize) {
-    let x_ratio = (src_w - 1) as f32 / dst_w as f32;
-    let y_ratio = (src_h - 1) as f32 / dst_h as f32;
-    
-    for y in 0..dst_h {
-        for x in 0..dst_w {
-            let src_x = x as f32 * x_ratio;
-            let src_y = y as f32 * y_ratio;
-            
-            let x0 = src_x.floor() as usize;
-            let y0 = src_y.floor() as usize;
-            let x1 = (x0 + 1).min(src_w - 1);
-            let y1 = (y0 + 1).min(src_h - 1);
-            
-            let fx = src_x - x0 as f32;
-            let fy = src_y - y0 as f32;
-            
-            let c00 = src[y0 * src_w + x0];
-            let c10 = src[y0 * src_w + x1];
-            let c01 = src[y1 * src_w + x0];
-            let c11 = src[y1 * src_w + x1];
-            
-            let r0 = ((c00 >> 16) & 0xFF) as f32;
-            let g0 = ((c00 >> 8) & 0xFF) as f32;
-            let b0 = (c00 & 0xFF) as f32;
-            
-            let r1 = ((c10 >> 16) & 0xFF) as f32;
-            let g1 = ((c10 >> 8) & 0xFF) as f32;
-            let b1 = (c10 & 0xFF) as f32;
-            
-            let r2 = ((c01 >> 16) & 0xFF) as f32;
-            let g2 = ((c01 >> 8) & 0xFF) as f32;
-            let b2 = (c01 & 0xFF) as f32;
-            
-            let r3 = ((c11 >> 16) & 0xFF) as f32;
-            let g3 = ((c11 >> 8) & 0xFF) as f32;
-            let b3 = (c11 & 0xFF) as f32;
-            
-            let r_top = r0 * (1.0 - fx) + r1 * fx;
-            let r_bottom = r2 * (1.0 - fx) + r3 * fx;
-            let r = (r_top * (1.0 - fy) + r_bottom * fy) as u32;
-            
-            let g_top = g0 * (1.0 - fx) + g1 * fx;
-            let g_bottom = g2 * (1.0 - fx) + g3 * fx;
-            let g = (g_top * (1.0 - fy) + g_bottom * fy) as u32;
-            
-            let b_top = b0 * (1.0 - fx) + b1 * fx;
-            let b_bottom = b2 * (1.0 - fx) + b3 * fx;
-            let b = (b_top * (1.0 - fy) + b_bottom * fy) as u32;
-            
-            dst[y * dst_w + x] = (r << 16) | (g << 8) | b;
+// Recorrido fijo de la cámara con paso de simulación fijo; solo se mide el render de cada cuadro
+fn run_benchmark(config: &Config, mut scene: Scene, mut renderer: Renderer) {
+    renderer.profiler.set_capacity(config.frames);
+    // Los tiempos sólo se comparan si todos los cuadros se dibujan a la misma resolución
+    renderer.dynamic_resolution.enabled = false;
+    let mut stats = BenchmarkStats::new();
+    let mut time = config.start_time;
+
+    // El calentamiento se dibuja con la cámara en el punto de partida
+    for frame in 0..WARMUP_FRAMES + config.frames {
+        if frame > 0 {
+            let delta_time = HEADLESS_FRAME_TIME * config.time_scale;
+            scene.update(delta_time);
+            time += delta_time;
+        }
+        let progress = frame.saturating_sub(WARMUP_FRAMES) as f32 / (config.frames - 1).max(1) as f32;
+        let camera = flight_camera(progress);
+
+        let start = Instant::now();
+        renderer.render(&scene, &camera, time, HEADLESS_FRAME_TIME);
+        let elapsed = start.elapsed();
+        renderer.profiler.end_frame();
+
+        if frame >= WARMUP_FRAMES {
+            let counters = renderer.profiler.last_frame().map(|f| f.counters).unwrap_or_default();
+            stats.add_frame(elapsed, &counters);
         }
     }
+
+    let (width, height) = renderer.output_size;
+    println!(
+        "Benchmark: {} cuadros (+{} de calentamiento) a {}x{}, render {}x{}, antialiasing {:?}",
+        stats.frames(), WARMUP_FRAMES, width, height,
+        renderer.framebuffer.width, renderer.framebuffer.height, config.anti_aliasing
+    );
+    println!(
+        "Tiempo por cuadro: mín {:.2} ms, promedio {:.2} ms, p99 {:.2} ms, máx {:.2} ms",
+        stats.min_ms(), stats.average_ms(), stats.percentile_ms(99.0), stats.max_ms()
+    );
+    let (frames_per_second, triangles_per_second, fragments_per_second) = stats.throughput();
+    println!(
+        "Rendimiento: {:.1} cuadros/s, {:.2} M triángulos/s, {:.2} M fragmentos/s",
+        frames_per_second, triangles_per_second / 1e6, fragments_per_second / 1e6
+    );
+    if let Some(summary) = renderer.profiler.summary(config.frames) {
+        println!("Promedio por etapa:");
+        for stage in Stage::ALL {
+            println!("  {:<13}{:>8.2} ms", stage.name(), summary.stage_ms[stage as usize]);
+        }
+    }
+
+    if let Some(path) = &config.profile {
+        save_profile(&renderer.profiler, path);
+    }
 }
 
 fn main() {
-    // Framebuffer a menor resolución para mejor performance
-    let mut framebuffer = Framebuffer::new(RENDER_WIDTH, RENDER_HEIGHT);
-    
-    // Buffer escalado para mostrar en ventana
-    let mut scaled_buffer = vec![0u32; WINDOW_WIDTH * WINDOW_HEIGHT];
-    
-    let mut window = Window::new(
-        "Space System - Rust Graphics",
-        WINDOW_WIDTH,
-        WINDOW_HEIGHT,
-        WindowOptions::default(),
-    )
-    .unwrap();
+    let mut config = Config::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}\nUsa --help para ver las opciones", e);
+        std::process::exit(2);
+    });
+    if config.help {
+        println!("{}", usage());
+        return;
+    }
 
-    window.set_target_fps(60);
+    let scene_path = config.scene_path();
+    let mut scene = Scene::load(&scene_path, &config.assets).unwrap_or_else(|e| {
+        eprintln!("No se pudo cargar la escena: {}", e);
+        std::process::exit(1);
+    });
+    println!(
+        "Escena {}: {} planetas, {} objetos, {} mallas",
+        scene_path.display(), scene.planets.len(), scene.props.len(), scene.meshes.len()
+    );
 
-    framebuffer.set_background_color(Color::new(0, 0, 10));
+    // Adelantar la simulación hasta el tiempo inicial (las órbitas son lineales en el tiempo)
+    scene.update(config.start_time);
 
-    // Cargar el modelo de esfera
-    let sphere = Obj::load("assets/sphere.obj").expect("No se pudo cargar sphere.obj");
-    println!("Sphere loaded: {} vertices, {} indices", sphere.vertices.len(), sphere.indices.len());
-    println!("\n=== CONTROLES ===");
-    println!("Flechas: Mover cámara (izq/der orbitar, arriba/abajo zoom)");
-    println!("Teclas 1-8: Warp a planetas (1=Sol, 2-8=Planetas)");
-    println!("Tecla 0: Vista general del sistema");
-    println!("ESC: Salir\n");
-
-    // Inicializar cámara
-    let mut camera = Camera::new(
-        Vec3::new(0.0, 10.0, 20.0),
-        Vec3::new(0.0, 0.0, 0.0),
-        Vec3::new(0.0, 1.0, 0.0)
+    let mut camera = camera_preset(&config.camera, &scene).unwrap_or_else(|| {
+        eprintln!("--camera: cámara desconocida: {} (default, overview, top o un cuerpo de la escena)", config.camera);
+        std::process::exit(2);
+    });
+
+    let mut renderer = Renderer::new(
+        config.resolution,
+        config.render_scale,
+        config.anti_aliasing,
+        std::mem::replace(&mut config.post_chain, PostChain::new()),
     );
+    renderer.upscale_filter = config.upscale;
+    renderer.dynamic_resolution = DynamicResolution::new(config.target_frame_ms.unwrap_or(DEFAULT_TARGET_FRAME_MS) / 1000.0);
+    renderer.dynamic_resolution.enabled = config.target_frame_ms.is_some();
+    renderer.debug = config.debug;
 
-    // Crear el Sol
-    let mut sun = CelestialBody::new(0.0, 2.0, 0.2, 0.0, sun_shader);
+    if config.benchmark {
+        run_benchmark(&config, scene, renderer);
+        return;
+    }
+    if config.output.is_some() {
+        // Sin ventana se guardan todos los cuadros para el perfil
+        renderer.profiler.set_capacity(config.frames);
+        run_headless(&config, scene, camera, renderer);
+        return;
+    }
 
-    // Crear planetas (orbit_radius, scale, rotation_speed, orbit_speed, shader)
-    let mut planets = vec![
-        CelestialBody::new(5.0, 0.4, 1.0, 0.8, rocky_planet_shader),      // Mercurio
-        CelestialBody::new(7.0, 0.6, 0.8, 0.6, earth_shader),             // Venus
-        CelestialBody::new(10.0, 0.7, 1.2, 0.5, earth_shader),            // Tierra
-        CelestialBody::new(13.0, 0.5, 1.1, 0.4, red_planet_shader),       // Marte
-        CelestialBody::new(18.0, 1.5, 0.5, 0.2, gas_planet_shader),       // Júpiter
-    ];
+    let mut fullscreen = config.fullscreen;
+    // Tamaño al que vuelve la ventana al salir de pantalla completa
+    let mut windowed_size = config.resolution;
+    let mut window = create_window(config.resolution, fullscreen);
 
-    let mut time = 0.0f32;
+    println!("\n=== CONTROLES ===");
+    for (key, action) in CONTROLS {
+        println!("{}: {}", key, action);
+    }
+    println!("Capturas y grabaciones en {}\n", config.capture_dir.display());
+
+    let mut eclipses = EclipseTracker::new();
+    let mut recorder = Recorder::new(&config.capture_dir, config.record_format);
+    let mut hud = Hud::new();
+    // Cuerpo cuyos datos muestra el HUD: el último destino de warp o el de --camera
+    let mut focus = scene.find(&config.camera).map(|body| body.name.clone());
+
+    let mut time = config.start_time;
     let mut last_time = std::time::Instant::now();
     
-    let mut warping = false;
-    let mut warp_progress = 0.0f32;
-    let mut warp_start_pos = camera.eye;
-    let mut warp_target_pos = camera.eye;
-    let warp_duration = 1.5;
+    let mut mouse = MouseControl::new(config.mouse_sensitivity, config.invert_y);
+    let warp_targets = warp_targets(&scene);
+    let mut warp: Option<Warp> = None;
 
     while window.is_open() && !window.is_key_down(Key::Escape) {
         let current_time = std::time::Instant::now();
         let delta_time = current_time.duration_since(last_time).as_secs_f32();
         last_time = current_time;
-        
-        time += delta_time;
-
-        // Instant warping con teclas numéricas
-        if window.is_key_pressed(Key::Key1, minifb::KeyRepeat::No) {
-            // Warp al sol
-            warp_start_pos = camera.eye;
-            warp_target_pos = Vec3::new(0.0, 5.0, 8.0);
-            camera.center = Vec3::new(0.0, 0.0, 0.0);
-            warping = true;
-            warp_progress = 0.0;
-        }
-        if window.is_key_pressed(Key::Key2, minifb::KeyRepeat::No) && planets.len() > 0 {
-            warp_start_pos = camera.eye;
-            warp_target_pos = planets[0].position + Vec3::new(0.0, 2.0, 3.0);
-            camera.center = planets[0].position;
-            warping = true;
-            warp_progress = 0.0;
-        }
-        if window.is_key_pressed(Key::Key3, minifb::KeyRepeat::No) && planets.len() > 1 {
-            warp_start_pos = camera.eye;
-            warp_target_pos = planets[1].position + Vec3::new(0.0, 2.0, 4.0);
-            camera.center = planets[1].position;
-            warping = true;
-            warp_progress = 0.0;
-        }
-        if window.is_key_pressed(Key::Key4, minifb::KeyRepeat::No) && planets.len() > 2 {
-            warp_start_pos = camera.eye;
-            warp_target_pos = planets[2].position + Vec3::new(0.0, 2.0, 4.0);
-            camera.center = planets[2].position;
-            warping = true;
-            warp_progress = 0.0;
-        }
-        if window.is_key_pressed(Key::Key5, minifb::KeyRepeat::No) && planets.len() > 3 {
-            warp_start_pos = camera.eye;
-            warp_target_pos = planets[3].position + Vec3::new(0.0, 2.0, 3.0);
-            camera.center = planets[3].position;
-            warping = true;
-            warp_progress = 0.0;
-        }
-        if window.is_key_pressed(Key::Key6, minifb::KeyRepeat::No) && planets.len() > 4 {
-            warp_start_pos = camera.eye;
-            warp_target_pos = planets[4].position + Vec3::new(0.0, 3.0, 6.0);
-            camera.center = planets[4].position;
-            warping = true;
-            warp_progress = 0.0;
-        }
-        if window.is_key_pressed(Key::Key0, minifb::KeyRepeat::No) {
-            // Warp a vista general
-            warp_start_pos = camera.eye;
-            warp_target_pos = Vec3::new(0.0, 15.0, 35.0);
-            camera.center = Vec3::new(0.0, 0.0, 0.0);
-            warping = true;
-            warp_progress = 0.0;
+        // Desde acá se mide el movimiento de la cámara en el cuadro, para no atravesar cuerpos
+        let previous_eye = camera.eye;
+
+        // La simulación avanza según --time-scale; la cámara sigue en tiempo real
+        let sim_delta_time = delta_time * config.time_scale;
+        time += sim_delta_time;
+
+        // Warp: 0 = vista general, 1-9 = Sol y cuerpos en el orden de la escena; N recorre todos
+        let mut warp_choice = WARP_KEYS.iter().position(|key| window.is_key_pressed(*key, minifb::KeyRepeat::No));
+        if window.is_key_pressed(Key::N, minifb::KeyRepeat::No) {
+            let current = warp_targets.iter().position(|target| target.body == focus).unwrap_or(0);
+            let step = if window.is_key_down(Key::LeftShift) { warp_targets.len() - 1 } else { 1 };
+            warp_choice = Some((current + step) % warp_targets.len());
+        }
+        if let Some(target) = warp_choice.and_then(|index| warp_targets.get(index)) {
+            println!("Warp: {}", target.name);
+            focus = target.body.clone();
+            warp = Some(Warp::start(target, &scene, &mut camera));
         }
 
-        // Animación de warping
-        if warping {
-            warp_progress += delta_time / warp_duration;
-            if warp_progress >= 1.0 {
-                warping = false;
-                warp_progress = 1.0;
+        if window.is_key_pressed(Key::L, minifb::KeyRepeat::No) {
+            renderer.lighting_model = renderer.lighting_model.next();
+            println!("Iluminación: {}", renderer.lighting_model.name());
+        }
+
+        if window.is_key_pressed(Key::T, minifb::KeyRepeat::No) {
+            renderer.tone_mapper.operator = renderer.tone_mapper.operator.next();
+            println!("Tone mapping: {}", renderer.tone_mapper.operator.name());
+        }
+        if window.is_key_pressed(Key::E, minifb::KeyRepeat::No) {
+            renderer.tone_mapper.auto_exposure = !renderer.tone_mapper.auto_exposure;
+            println!("Exposición automática: {}", if renderer.tone_mapper.auto_exposure { "on" } else { "off" });
+        }
+        if window.is_key_pressed(Key::Equal, minifb::KeyRepeat::Yes) {
+            renderer.tone_mapper.adjust_exposure(0.25);
+            println!("Exposición: {:+.2} EV", renderer.tone_mapper.exposure_ev);
+        }
+        if window.is_key_pressed(Key::Minus, minifb::KeyRepeat::Yes) {
+            renderer.tone_mapper.adjust_exposure(-0.25);
+            println!("Exposición: {:+.2} EV", renderer.tone_mapper.exposure_ev);
+        }
+
+        if window.is_key_pressed(Key::B, minifb::KeyRepeat::No) {
+            renderer.bloom.cycle_mode();
+            println!("Bloom: {}", renderer.bloom.mode_name());
+        }
+        if window.is_key_pressed(Key::F, minifb::KeyRepeat::No) {
+            renderer.lens_flare.enabled = !renderer.lens_flare.enabled;
+            println!("Destellos de lente: {}", if renderer.lens_flare.enabled { "on" } else { "off" });
+        }
+
+        if window.is_key_pressed(Key::P, minifb::KeyRepeat::No) {
+            renderer.post_chain.select_next();
+            println!("Post-proceso:\n{}", renderer.post_chain.describe());
+        }
+        if window.is_key_pressed(Key::O, minifb::KeyRepeat::No) {
+            renderer.post_chain.toggle_selected();
+            println!("Post-proceso:\n{}", renderer.post_chain.describe());
+        }
+        if window.is_key_pressed(Key::LeftBracket, minifb::KeyRepeat::No) {
+            renderer.post_chain.move_selected(-1);
+            println!("Post-proceso:\n{}", renderer.post_chain.describe());
+        }
+        if window.is_key_pressed(Key::RightBracket, minifb::KeyRepeat::No) {
+            renderer.post_chain.move_selected(1);
+            println!("Post-proceso:\n{}", renderer.post_chain.describe());
+        }
+
+        if window.is_key_pressed(Key::U, minifb::KeyRepeat::No) {
+            renderer.upscale_filter = renderer.upscale_filter.next();
+            println!("Filtro de escalado: {}", renderer.upscale_filter.name());
+        }
+        if window.is_key_pressed(Key::Y, minifb::KeyRepeat::No) {
+            renderer.dynamic_resolution.enabled = !renderer.dynamic_resolution.enabled;
+            println!("Resolución dinámica: {}", if renderer.dynamic_resolution.enabled { "on" } else { "off" });
+        }
+        let scale_step = if window.is_key_pressed(Key::Period, minifb::KeyRepeat::No) {
+            0.125
+        } else if window.is_key_pressed(Key::Comma, minifb::KeyRepeat::No) {
+            -0.125
+        } else {
+            0.0
+        };
+        if scale_step != 0.0 {
+            renderer.set_render_scale((renderer.render_scale + scale_step).clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE));
+            let (width, height) = render_size(renderer.output_size, renderer.render_scale);
+            println!("Escala de render: {:.0}% ({}x{})", renderer.render_scale * 100.0, width, height);
+        }
+
+        if window.is_key_pressed(Key::H, minifb::KeyRepeat::No) {
+            hud.mode = hud.mode.next();
+        }
+
+        // Vistas de depuración
+        let mut debug_changed = false;
+        if window.is_key_pressed(Key::F1, minifb::KeyRepeat::No) {
+            renderer.debug.view = renderer.debug.view.next();
+            debug_changed = true;
+        }
+        if window.is_key_pressed(Key::F2, minifb::KeyRepeat::No) {
+            renderer.debug.wireframe = !renderer.debug.wireframe;
+            debug_changed = true;
+        }
+        if window.is_key_pressed(Key::F3, minifb::KeyRepeat::No) {
+            renderer.debug.gizmos = !renderer.debug.gizmos;
+            debug_changed = true;
+        }
+        if window.is_key_pressed(Key::F6, minifb::KeyRepeat::No) {
+            renderer.debug.axes = !renderer.debug.axes;
+            debug_changed = true;
+        }
+        if debug_changed {
+            println!("Depuración: {}", renderer.debug.describe());
+        }
+
+        if window.is_key_pressed(Key::F4, minifb::KeyRepeat::No) {
+            hud.show_profile = !hud.show_profile;
+        }
+        if window.is_key_pressed(Key::F5, minifb::KeyRepeat::No) {
+            match std::fs::create_dir_all(&config.capture_dir) {
+                Ok(()) => {
+                    let stamp = capture::timestamp();
+                    for extension in ["csv", "json"] {
+                        let path = config.capture_dir.join(format!("perfil_{}.{}", stamp, extension));
+                        save_profile(&renderer.profiler, &path);
+                    }
+                }
+                Err(e) => eprintln!("No se pudo crear {}: {}", config.capture_dir.display(), e),
             }
-            
-            // Interpolación suave (easing)
-            let t = warp_progress;
-            let smooth_t = t * t * (3.0 - 2.0 * t); // smoothstep
-            
-            camera.eye.x = warp_start_pos.x + (warp_target_pos.x - warp_start_pos.x) * smooth_t;
-            camera.eye.y = warp_start_pos.y + (warp_target_pos.y - warp_start_pos.y) * smooth_t;
-            camera.eye.z = warp_start_pos.z + (warp_target_pos.z - warp_start_pos.z) * smooth_t;
-            
-            camera.distance = (camera.eye - camera.center).magnitude();
         }
 
-        // Control de cámara (solo cuando no está en warping)
-        if !warping {
-            if window.is_key_down(Key::Left) {
-                camera.orbit_around_center(delta_time * 2.0);
+        if window.is_key_pressed(Key::F11, minifb::KeyRepeat::No) {
+            if !fullscreen {
+                windowed_size = renderer.output_size;
             }
-            if window.is_key_down(Key::Right) {
-                camera.orbit_around_center(-delta_time * 2.0);
+            fullscreen = !fullscreen;
+            window = create_window(windowed_size, fullscreen);
+        }
+
+        // La ventana se puede redimensionar
+        let (new_width, new_height) = window.get_size();
+        if new_width > 0 && new_height > 0 {
+            renderer.resize((new_width, new_height));
+        }
+
+        // Animación de warping
+        if let Some(active) = warp.as_mut() {
+            if active.update(&scene, &mut camera, delta_time) {
+                warp = None;
             }
-            if window.is_key_down(Key::Up) {
-                camera.zoom(-delta_time * 5.0);
+        }
+        let warping = warp.is_some();
+
+        if window.is_key_pressed(Key::Tab, minifb::KeyRepeat::No) && !warping {
+            let mut mode = camera.mode.next();
+            // Solo se puede seguir al cuerpo enfocado (destino del último warp o --camera)
+            if mode == CameraMode::Follow && focus.is_none() {
+                mode = mode.next();
             }
-            if window.is_key_down(Key::Down) {
-                camera.zoom(delta_time * 5.0);
+            camera.set_mode(mode);
+            println!("Cámara: {}", describe_camera(&camera, focus.as_deref()));
+        }
+        if window.is_key_pressed(Key::G, minifb::KeyRepeat::No) {
+            camera.follow_style = camera.follow_style.next();
+            println!("Seguimiento: {}", camera.follow_style.name());
+        }
+
+        // Control de cámara (solo cuando no está en warping)
+        if !warping {
+            match camera.mode {
+                CameraMode::Orbit | CameraMode::Follow => {
+                    if window.is_key_down(Key::Left) {
+                        camera.orbit_around_center(delta_time * 2.0);
+                    }
+                    if window.is_key_down(Key::Right) {
+                        camera.orbit_around_center(-delta_time * 2.0);
+                    }
+                    if window.is_key_down(Key::W) {
+                        camera.orbit(0.0, delta_time);
+                    }
+                    if window.is_key_down(Key::S) {
+                        camera.orbit(0.0, -delta_time);
+                    }
+                    if window.is_key_down(Key::Up) {
+                        camera.zoom(-delta_time * 5.0);
+                    }
+                    if window.is_key_down(Key::Down) {
+                        camera.zoom(delta_time * 5.0);
+                    }
+                }
+                CameraMode::FreeFly => camera.fly(&fly_input(&window), delta_time),
             }
         }
+        // Durante el warp solo se sigue la posición del cursor
+        mouse.update(&window, &mut camera, !warping);
+        camera.update(delta_time);
 
-        framebuffer.clear();
-        
-        // Renderizar skybox (estrellas de fondo)
-        render_skybox(&mut framebuffer);
-
-        // Setup uniforms
-        let mut uniforms = Uniforms::new();
-        
-        uniforms.projection_matrix = create_perspective_matrix(
-            PI / 3.0,
-            RENDER_WIDTH as f32 / RENDER_HEIGHT as f32,
-            0.1,
-            100.0
-        );
-
-        uniforms.view_matrix = create_view_matrix(
-            camera.eye,
-            camera.center,
-            camera.up
-        );
-
-        uniforms.viewport_matrix = create_viewport_matrix(RENDER_WIDTH as f32, RENDER_HEIGHT as f32);
-        uniforms.time = time;
-
-        // Renderizar órbitas (todas siempre visibles)
-        for planet in &planets {
-            render_orbit(planet, &mut framebuffer, &uniforms);
-        }
-
-        // Renderizar el Sol
-        uniforms.model_matrix = create_model_matrix(
-            sun.position,
-            sun.scale,
-            Vec3::new(0.0, sun.rotation, 0.0)
-        );
-        render_obj_with_shader(&sphere, &mut framebuffer, &uniforms, sun.shader);
-        sun.update(delta_time);
-
-        // Renderizar planetas
-        for planet in &mut planets {
-            planet.update(delta_time);
-            
-            uniforms.model_matrix = create_model_matrix(
-                planet.position,
-                planet.scale,
-                Vec3::new(0.0, planet.rotation, 0.0)
-            );
-            
-            render_obj_with_shader(&sphere, &mut framebuffer, &uniforms, planet.shader);
+
+        scene.update(sim_delta_time);
+        report_eclipses(&mut eclipses, &scene, time);
+        if let Some(body) = focus.as_deref().and_then(|name| scene.find(name)) {
+            let target = FollowTarget { position: body.position, parent_position: scene.orbit_center(body) };
+            camera.follow(&target, delta_time);
+        }
+        camera.avoid_bodies(previous_eye, &scene.obstacles());
+
+        renderer.render(&scene, &camera, time, delta_time);
+
+        let (width, height) = renderer.output_size;
+
+        // El HUD se dibuja sobre la imagen ya escalada para que el texto quede nítido
+        let stage_start = Instant::now();
+        hud.update(delta_time);
+        let focus_body = focus.as_deref().and_then(|name| scene.find(name));
+        let info = HudInfo {
+            time,
+            time_scale: config.time_scale,
+            year_length: scene.year_length(),
+            focus: focus_body,
+            focus_parent: focus_body.and_then(|body| body.parent).map(|p| scene.planets[p].name.as_str()),
+            camera_distance: focus_body.map_or(0.0, |body| (camera.eye - body.position).magnitude()),
+            camera_mode: describe_camera(&camera, focus.as_deref()),
+            render_size: (renderer.framebuffer.width, renderer.framebuffer.height),
+            render_scale: renderer.render_scale,
+            debug: renderer.debug.is_active().then(|| renderer.debug.describe()),
+            profile: if hud.show_profile { renderer.profiler.summary(PROFILE_AVERAGE_FRAMES) } else { None },
+            frame_budget_ms: config.target_frame_ms.unwrap_or(DEFAULT_TARGET_FRAME_MS),
+            focus_marker: focus_body.and_then(|body| renderer.screen_circle(&camera, body.position, body.radius())),
+        };
+        hud.draw(&mut renderer.output, &info);
+        renderer.profiler.record(Stage::Hud, stage_start);
+
+        if window.is_key_pressed(Key::C, minifb::KeyRepeat::No) {
+            let native = &renderer.framebuffer;
+            match save_screenshot(
+                &config.capture_dir,
+                (&native.buffer, native.width, native.height),
+                (&renderer.output.buffer, width, height),
+            ) {
+                Ok((native_path, scaled_path)) => {
+                    println!("Captura: {} y {}", scaled_path.display(), native_path.display())
+                }
+                Err(e) => eprintln!("No se pudo guardar la captura: {}", e),
+            }
         }
 
-        // Escalar el framebuffer de menor a mayor resolución
-        scale_buffer(
-            &framebuffer.buffer, 
-            &mut scaled_buffer, 
-            RENDER_WIDTH, 
-            RENDER_HEIGHT, 
-            WINDOW_WIDTH, 
-            WINDOW_HEIGHT
-        );
+        if window.is_key_pressed(Key::V, minifb::KeyRepeat::No) {
+            if recorder.is_recording() {
+                match recorder.stop() {
+                    Ok(Some((path, frames))) => println!("Grabación guardada: {} ({} cuadros)", path.display(), frames),
+                    Ok(None) => {}
+                    Err(e) => eprintln!("No se pudo terminar la grabación: {}", e),
+                }
+            } else {
+                match recorder.start(width, height) {
+                    Ok(path) => println!("Grabando en {}", path.display()),
+                    Err(e) => eprintln!("No se pudo empezar a grabar: {}", e),
+                }
+            }
+        }
+        if recorder.is_recording() {
+            if let Err(e) = recorder.add_frame(&renderer.output.buffer, width, height, delta_time) {
+                eprintln!("Grabación detenida: {}", e);
+                let _ = recorder.stop();
+            }
+        }
 
+        let stage_start = Instant::now();
         window
-            .update_with_buffer(&scaled_buffer, WINDOW_WIDTH, WINDOW_HEIGHT)
+            .update_with_buffer(&renderer.output.buffer, width, height)
             .unwrap();
+        renderer.profiler.record(Stage::Present, stage_start);
+        renderer.profiler.end_frame();
 
         std::thread::sleep(Duration::from_millis(16));
     }
-}
 
+    // Cerrar la grabación si se sale mientras está activa
+    if let Ok(Some((path, frames))) = recorder.stop() {
+        println!("Grabación guardada: {} ({} cuadros)", path.display(), frames);
+    }
+    if let Some(path) = &config.profile {
+        save_profile(&renderer.profiler, path);
+    }
+}
@@ -1,4 +1,154 @@
-use std::ops::{Add, Sub, Mul, Neg, AddAssign};
+use std::fmt;
+use std::ops::{Add, Sub, Mul, Div, Neg, AddAssign, SubAssign, MulAssign};
+
+// Tolerancia por defecto para comparar resultados de punto flotante en las pruebas
+#[cfg(test)]
+pub const EPSILON: f32 = 1e-5;
+
+// Un pivote menor que esto (relativo al elemento más grande) hace la matriz singular
+const SINGULAR_EPSILON: f32 = 1e-6;
+
+pub fn approx_eq(a: f32, b: f32, epsilon: f32) -> bool {
+    (a - b).abs() <= epsilon * 1.0f32.max(a.abs()).max(b.abs())
+}
+
+pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+// ============ Vec2 ============
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vec2 {
+    pub const fn new(x: f32, y: f32) -> Self {
+        Vec2 { x, y }
+    }
+
+    pub fn zeros() -> Self {
+        Vec2::new(0.0, 0.0)
+    }
+
+    pub fn dot(&self, other: &Vec2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    // Componente z del producto vectorial: positivo si `other` está en sentido antihorario
+    pub fn cross(&self, other: &Vec2) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub fn magnitude(&self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(&self) -> Vec2 {
+        let mag = self.magnitude();
+        if mag > 0.0 {
+            *self / mag
+        } else {
+            *self
+        }
+    }
+
+    // Rotado 90° en sentido antihorario
+    pub fn perpendicular(&self) -> Vec2 {
+        Vec2::new(-self.y, self.x)
+    }
+
+    pub fn lerp(&self, other: &Vec2, t: f32) -> Vec2 {
+        *self + (*other - *self) * t
+    }
+
+    pub fn approx_eq(&self, other: &Vec2, epsilon: f32) -> bool {
+        approx_eq(self.x, other.x, epsilon) && approx_eq(self.y, other.y, epsilon)
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+    fn add(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+    fn sub(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Vec2;
+    fn mul(self, scalar: f32) -> Vec2 {
+        Vec2::new(self.x * scalar, self.y * scalar)
+    }
+}
+
+impl Mul<Vec2> for f32 {
+    type Output = Vec2;
+    fn mul(self, vec: Vec2) -> Vec2 {
+        vec * self
+    }
+}
+
+// Producto componente a componente
+impl Mul for Vec2 {
+    type Output = Vec2;
+    fn mul(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x * other.x, self.y * other.y)
+    }
+}
+
+impl Div<f32> for Vec2 {
+    type Output = Vec2;
+    fn div(self, scalar: f32) -> Vec2 {
+        Vec2::new(self.x / scalar, self.y / scalar)
+    }
+}
+
+impl Div for Vec2 {
+    type Output = Vec2;
+    fn div(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x / other.x, self.y / other.y)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, other: Vec2) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Vec2 {
+    fn sub_assign(&mut self, other: Vec2) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign<f32> for Vec2 {
+    fn mul_assign(&mut self, scalar: f32) {
+        *self = *self * scalar;
+    }
+}
+
+// Los componentes respetan el formato pedido: `{:.2}` da `(1.00, 2.00)`
+impl fmt::Display for Vec2 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_components(f, &[self.x, self.y])
+    }
+}
 
 // ============ Vec3 ============
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -41,6 +191,22 @@ impl Vec3 {
             *self
         }
     }
+
+    pub fn magnitude_squared(&self) -> f32 {
+        self.dot(self)
+    }
+
+    pub fn distance(&self, other: &Vec3) -> f32 {
+        (*self - *other).magnitude()
+    }
+
+    pub fn lerp(&self, other: &Vec3, t: f32) -> Vec3 {
+        *self + (*other - *self) * t
+    }
+
+    pub fn approx_eq(&self, other: &Vec3, epsilon: f32) -> bool {
+        approx_eq(self.x, other.x, epsilon) && approx_eq(self.y, other.y, epsilon) && approx_eq(self.z, other.z, epsilon)
+    }
 }
 
 impl Add for Vec3 {
@@ -65,7 +231,7 @@ impl Sub for Vec3 {
     }
 }
 
-impl<'a, 'b> Sub<&'b Vec3> for &'a Vec3 {
+impl<'b> Sub<&'b Vec3> for &Vec3 {
     type Output = Vec3;
     fn sub(self, other: &'b Vec3) -> Vec3 {
         Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
@@ -93,6 +259,46 @@ impl Neg for Vec3 {
     }
 }
 
+// Producto componente a componente
+impl Mul for Vec3 {
+    type Output = Vec3;
+    fn mul(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x * other.x, self.y * other.y, self.z * other.z)
+    }
+}
+
+impl Div<f32> for Vec3 {
+    type Output = Vec3;
+    fn div(self, scalar: f32) -> Vec3 {
+        Vec3::new(self.x / scalar, self.y / scalar, self.z / scalar)
+    }
+}
+
+impl Div for Vec3 {
+    type Output = Vec3;
+    fn div(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x / other.x, self.y / other.y, self.z / other.z)
+    }
+}
+
+impl SubAssign for Vec3 {
+    fn sub_assign(&mut self, other: Vec3) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign<f32> for Vec3 {
+    fn mul_assign(&mut self, scalar: f32) {
+        *self = *self * scalar;
+    }
+}
+
+impl fmt::Display for Vec3 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_components(f, &[self.x, self.y, self.z])
+    }
+}
+
 // ============ Vec4 ============
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Vec4 {
@@ -106,6 +312,194 @@ impl Vec4 {
     pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
         Vec4 { x, y, z, w }
     }
+
+    pub fn from_vec3(v: Vec3, w: f32) -> Self {
+        Vec4::new(v.x, v.y, v.z, w)
+    }
+
+    pub fn xyz(&self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+
+    pub fn dot(&self, other: &Vec4) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    pub fn magnitude(&self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn lerp(&self, other: &Vec4, t: f32) -> Vec4 {
+        *self + (*other - *self) * t
+    }
+
+    pub fn approx_eq(&self, other: &Vec4, epsilon: f32) -> bool {
+        approx_eq(self.x, other.x, epsilon)
+            && approx_eq(self.y, other.y, epsilon)
+            && approx_eq(self.z, other.z, epsilon)
+            && approx_eq(self.w, other.w, epsilon)
+    }
+}
+
+impl Add for Vec4 {
+    type Output = Vec4;
+    fn add(self, other: Vec4) -> Vec4 {
+        Vec4::new(self.x + other.x, self.y + other.y, self.z + other.z, self.w + other.w)
+    }
+}
+
+impl Sub for Vec4 {
+    type Output = Vec4;
+    fn sub(self, other: Vec4) -> Vec4 {
+        Vec4::new(self.x - other.x, self.y - other.y, self.z - other.z, self.w - other.w)
+    }
+}
+
+impl Mul<f32> for Vec4 {
+    type Output = Vec4;
+    fn mul(self, scalar: f32) -> Vec4 {
+        Vec4::new(self.x * scalar, self.y * scalar, self.z * scalar, self.w * scalar)
+    }
+}
+
+impl Mul<Vec4> for f32 {
+    type Output = Vec4;
+    fn mul(self, vec: Vec4) -> Vec4 {
+        vec * self
+    }
+}
+
+// Producto componente a componente
+impl Mul for Vec4 {
+    type Output = Vec4;
+    fn mul(self, other: Vec4) -> Vec4 {
+        Vec4::new(self.x * other.x, self.y * other.y, self.z * other.z, self.w * other.w)
+    }
+}
+
+impl Div<f32> for Vec4 {
+    type Output = Vec4;
+    fn div(self, scalar: f32) -> Vec4 {
+        Vec4::new(self.x / scalar, self.y / scalar, self.z / scalar, self.w / scalar)
+    }
+}
+
+impl Neg for Vec4 {
+    type Output = Vec4;
+    fn neg(self) -> Vec4 {
+        Vec4::new(-self.x, -self.y, -self.z, -self.w)
+    }
+}
+
+impl fmt::Display for Vec4 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_components(f, &[self.x, self.y, self.z, self.w])
+    }
+}
+
+// ============ Mat3 ============
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat3 {
+    pub data: [[f32; 3]; 3],
+}
+
+impl Mat3 {
+    #[allow(dead_code)] // API de álgebra que el render aún no usa; la cubren las pruebas
+    pub fn identity() -> Self {
+        Mat3 {
+            data: [
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        m00: f32, m01: f32, m02: f32,
+        m10: f32, m11: f32, m12: f32,
+        m20: f32, m21: f32, m22: f32,
+    ) -> Self {
+        Mat3 {
+            data: [
+                [m00, m01, m02],
+                [m10, m11, m12],
+                [m20, m21, m22],
+            ],
+        }
+    }
+
+    // Parte 3x3 de arriba a la izquierda (rotación y escala, sin traslación)
+    pub fn from_mat4(m: &Mat4) -> Self {
+        let mut data = [[0.0; 3]; 3];
+        for (i, row) in data.iter_mut().enumerate() {
+            row.copy_from_slice(&m.data[i][..3]);
+        }
+        Mat3 { data }
+    }
+
+    #[allow(dead_code)] // API de álgebra que el render aún no usa; la cubren las pruebas
+    pub fn transpose(&self) -> Mat3 {
+        Mat3 { data: transpose(&self.data) }
+    }
+
+    #[allow(dead_code)] // API de álgebra que el render aún no usa; la cubren las pruebas
+    pub fn determinant(&self) -> f32 {
+        let m = &self.data;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    // None si la matriz es singular
+    #[allow(dead_code)] // API de álgebra que el render aún no usa; la cubren las pruebas
+    pub fn inverse(&self) -> Option<Mat3> {
+        invert(&self.data).map(|data| Mat3 { data })
+    }
+
+    // Transforma normales con escala no uniforme: la inversa transpuesta
+    #[allow(dead_code)] // API de álgebra que el render aún no usa; la cubren las pruebas
+    pub fn normal_matrix(model: &Mat4) -> Option<Mat3> {
+        Mat3::from_mat4(model).inverse().map(|m| m.transpose())
+    }
+
+    #[allow(dead_code)] // API de álgebra que el render aún no usa; la cubren las pruebas
+    pub fn approx_eq(&self, other: &Mat3, epsilon: f32) -> bool {
+        self.data.iter().flatten().zip(other.data.iter().flatten()).all(|(&a, &b)| approx_eq(a, b, epsilon))
+    }
+}
+
+impl Mul<Mat3> for Mat3 {
+    type Output = Mat3;
+    fn mul(self, other: Mat3) -> Mat3 {
+        Mat3 { data: multiply(&self.data, &other.data) }
+    }
+}
+
+impl Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+    fn mul(self, vec: Vec3) -> Vec3 {
+        let m = &self.data;
+        Vec3::new(
+            m[0][0] * vec.x + m[0][1] * vec.y + m[0][2] * vec.z,
+            m[1][0] * vec.x + m[1][1] * vec.y + m[1][2] * vec.z,
+            m[2][0] * vec.x + m[2][1] * vec.y + m[2][2] * vec.z,
+        )
+    }
+}
+
+impl Mul<f32> for Mat3 {
+    type Output = Mat3;
+    fn mul(self, scalar: f32) -> Mat3 {
+        Mat3 { data: self.data.map(|row| row.map(|value| value * scalar)) }
+    }
+}
+
+impl fmt::Display for Mat3 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_rows(f, &self.data)
+    }
 }
 
 // ============ Mat4 ============
@@ -126,6 +520,7 @@ impl Mat4 {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         m00: f32, m01: f32, m02: f32, m03: f32,
         m10: f32, m11: f32, m12: f32, m13: f32,
@@ -143,19 +538,80 @@ impl Mat4 {
     }
 }
 
+impl Mat4 {
+    #[allow(dead_code)] // API de álgebra que el render aún no usa; la cubren las pruebas
+    pub fn transpose(&self) -> Mat4 {
+        Mat4 { data: transpose(&self.data) }
+    }
+
+    // Desarrollo por la primera fila con los menores 3x3
+    #[allow(dead_code)] // API de álgebra que el render aún no usa; la cubren las pruebas
+    pub fn determinant(&self) -> f32 {
+        (0..4)
+            .map(|column| {
+                let mut minor = [[0.0; 3]; 3];
+                for (i, row) in minor.iter_mut().enumerate() {
+                    let source = &self.data[i + 1];
+                    let mut k = 0;
+                    for (j, &value) in source.iter().enumerate() {
+                        if j != column {
+                            row[k] = value;
+                            k += 1;
+                        }
+                    }
+                }
+                let sign = if column % 2 == 0 { 1.0 } else { -1.0 };
+                sign * self.data[0][column] * Mat3 { data: minor }.determinant()
+            })
+            .sum()
+    }
+
+    // None si la matriz es singular
+    #[allow(dead_code)] // API de álgebra que el render aún no usa; la cubren las pruebas
+    pub fn inverse(&self) -> Option<Mat4> {
+        invert(&self.data).map(|data| Mat4 { data })
+    }
+
+    // Punto con w = 1, dividido por w si la matriz es proyectiva
+    #[allow(dead_code)] // API de álgebra que el render aún no usa; la cubren las pruebas
+    pub fn transform_point(&self, point: &Vec3) -> Vec3 {
+        let v = *self * Vec4::from_vec3(*point, 1.0);
+        if v.w != 0.0 && v.w != 1.0 {
+            v.xyz() / v.w
+        } else {
+            v.xyz()
+        }
+    }
+
+    // Dirección con w = 0: no le afecta la traslación
+    #[allow(dead_code)] // API de álgebra que el render aún no usa; la cubren las pruebas
+    pub fn transform_vector(&self, vector: &Vec3) -> Vec3 {
+        (*self * Vec4::from_vec3(*vector, 0.0)).xyz()
+    }
+
+    #[allow(dead_code)] // API de álgebra que el render aún no usa; la cubren las pruebas
+    pub fn approx_eq(&self, other: &Mat4, epsilon: f32) -> bool {
+        self.data.iter().flatten().zip(other.data.iter().flatten()).all(|(&a, &b)| approx_eq(a, b, epsilon))
+    }
+}
+
 impl Mul<Mat4> for Mat4 {
     type Output = Mat4;
     fn mul(self, other: Mat4) -> Mat4 {
-        let mut result = Mat4::identity();
-        for i in 0..4 {
-            for j in 0..4 {
-                result.data[i][j] = 0.0;
-                for k in 0..4 {
-                    result.data[i][j] += self.data[i][k] * other.data[k][j];
-                }
-            }
-        }
-        result
+        Mat4 { data: multiply(&self.data, &other.data) }
+    }
+}
+
+impl Mul<f32> for Mat4 {
+    type Output = Mat4;
+    fn mul(self, scalar: f32) -> Mat4 {
+        Mat4 { data: self.data.map(|row| row.map(|value| value * scalar)) }
+    }
+}
+
+impl fmt::Display for Mat4 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_rows(f, &self.data)
     }
 }
 
@@ -171,6 +627,351 @@ impl Mul<Vec4> for Mat4 {
     }
 }
 
+// ============ Quat ============
+// Cuaternión unitario: rotación sin gimbal lock que se puede interpolar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quat {
+    pub w: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Quat {
+    pub const fn new(w: f32, x: f32, y: f32, z: f32) -> Self {
+        Quat { w, x, y, z }
+    }
+
+    pub const fn identity() -> Self {
+        Quat::new(1.0, 0.0, 0.0, 0.0)
+    }
+
+    // Giro de `angle` radianes alrededor de `axis` (no hace falta que sea unitario)
+    pub fn from_axis_angle(axis: &Vec3, angle: f32) -> Self {
+        let axis = axis.normalize();
+        let (sin, cos) = (angle * 0.5).sin_cos();
+        Quat::new(cos, axis.x * sin, axis.y * sin, axis.z * sin)
+    }
+
+    // Eje unitario y ángulo en [0, 2π); sin giro da el eje X
+    #[allow(dead_code)] // API de Quat que el render aún no usa; la cubren las pruebas
+    pub fn to_axis_angle(self) -> (Vec3, f32) {
+        let q = self.normalize();
+        let angle = 2.0 * q.w.clamp(-1.0, 1.0).acos();
+        let sin = (1.0 - q.w * q.w).max(0.0).sqrt();
+        if sin < 1e-6 {
+            (Vec3::new(1.0, 0.0, 0.0), 0.0)
+        } else {
+            (Vec3::new(q.x / sin, q.y / sin, q.z / sin), angle)
+        }
+    }
+
+    // Mismo orden que create_model_matrix: primero X, después Y, después Z (Rz * Ry * Rx)
+    #[allow(dead_code)] // API de Quat que el render aún no usa; la cubren las pruebas
+    pub fn from_euler(angles: &Vec3) -> Self {
+        Quat::from_axis_angle(&Vec3::new(0.0, 0.0, 1.0), angles.z)
+            * Quat::from_axis_angle(&Vec3::new(0.0, 1.0, 0.0), angles.y)
+            * Quat::from_axis_angle(&Vec3::new(1.0, 0.0, 0.0), angles.x)
+    }
+
+    // Inversa de from_euler; con Y = ±90° (gimbal lock) X y Z se confunden y todo el giro va a Z
+    #[allow(dead_code)] // API de Quat que el render aún no usa; la cubren las pruebas
+    pub fn to_euler(self) -> Vec3 {
+        let m = self.to_mat3().data;
+        // atan2 en vez de asin: cerca de ±90° el seno casi no cambia y se pierde precisión
+        let cos_y = (m[0][0] * m[0][0] + m[1][0] * m[1][0]).sqrt();
+        let y = (-m[2][0]).atan2(cos_y);
+        if cos_y < 1e-4 {
+            Vec3::new(0.0, y, (-m[0][1]).atan2(m[1][1]))
+        } else {
+            Vec3::new(m[2][1].atan2(m[2][2]), y, m[1][0].atan2(m[0][0]))
+        }
+    }
+
+    // Orientación de un objeto que mira hacia `forward` (su -Z) con `up` como arriba (su +Y)
+    pub fn look_rotation(forward: &Vec3, up: &Vec3) -> Self {
+        let f = forward.normalize();
+        let mut right = f.cross(up);
+        if right.magnitude() < 1e-6 {
+            // `up` paralelo a la dirección: cualquier perpendicular sirve
+            let fallback = if f.x.abs() < 0.9 { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 0.0, 1.0) };
+            right = f.cross(&fallback);
+        }
+        let right = right.normalize();
+        let up = right.cross(&f);
+        Quat::from_mat3(&Mat3::new(
+            right.x, up.x, -f.x,
+            right.y, up.y, -f.y,
+            right.z, up.z, -f.z,
+        ))
+    }
+
+    // La matriz tiene que ser una rotación pura (ortonormal, determinante 1)
+    pub fn from_mat3(m: &Mat3) -> Self {
+        let m = &m.data;
+        let trace = m[0][0] + m[1][1] + m[2][2];
+        // Se despeja primero el componente más grande para no dividir por algo cercano a cero
+        let q = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Quat::new(0.25 * s, (m[2][1] - m[1][2]) / s, (m[0][2] - m[2][0]) / s, (m[1][0] - m[0][1]) / s)
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+            Quat::new((m[2][1] - m[1][2]) / s, 0.25 * s, (m[0][1] + m[1][0]) / s, (m[0][2] + m[2][0]) / s)
+        } else if m[1][1] > m[2][2] {
+            let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+            Quat::new((m[0][2] - m[2][0]) / s, (m[0][1] + m[1][0]) / s, 0.25 * s, (m[1][2] + m[2][1]) / s)
+        } else {
+            let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+            Quat::new((m[1][0] - m[0][1]) / s, (m[0][2] + m[2][0]) / s, (m[1][2] + m[2][1]) / s, 0.25 * s)
+        };
+        q.normalize()
+    }
+
+    // Usa solo la parte de rotación; la matriz no puede tener escala
+    #[allow(dead_code)] // API de Quat que el render aún no usa; la cubren las pruebas
+    pub fn from_mat4(m: &Mat4) -> Self {
+        Quat::from_mat3(&Mat3::from_mat4(m))
+    }
+
+    pub fn to_mat3(self) -> Mat3 {
+        let Quat { w, x, y, z } = self;
+        Mat3::new(
+            1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - w * z), 2.0 * (x * z + w * y),
+            2.0 * (x * y + w * z), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - w * x),
+            2.0 * (x * z - w * y), 2.0 * (y * z + w * x), 1.0 - 2.0 * (x * x + y * y),
+        )
+    }
+
+    pub fn to_mat4(self) -> Mat4 {
+        let m = self.to_mat3().data;
+        Mat4::new(
+            m[0][0], m[0][1], m[0][2], 0.0,
+            m[1][0], m[1][1], m[1][2], 0.0,
+            m[2][0], m[2][1], m[2][2], 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        )
+    }
+
+    pub fn dot(&self, other: &Quat) -> f32 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn magnitude(&self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(&self) -> Quat {
+        let mag = self.magnitude();
+        if mag > 0.0 {
+            Quat::new(self.w / mag, self.x / mag, self.y / mag, self.z / mag)
+        } else {
+            Quat::identity()
+        }
+    }
+
+    pub fn conjugate(&self) -> Quat {
+        Quat::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    // Para cuaterniones unitarios es igual al conjugado
+    #[allow(dead_code)] // API de Quat que el render aún no usa; la cubren las pruebas
+    pub fn inverse(&self) -> Quat {
+        let norm = self.dot(self);
+        if norm > 0.0 {
+            let c = self.conjugate();
+            Quat::new(c.w / norm, c.x / norm, c.y / norm, c.z / norm)
+        } else {
+            Quat::identity()
+        }
+    }
+
+    pub fn rotate(&self, v: &Vec3) -> Vec3 {
+        // v' = v + 2w (q × v) + 2 q × (q × v), con q la parte vectorial
+        let q = Vec3::new(self.x, self.y, self.z);
+        let t = q.cross(v) * 2.0;
+        *v + t * self.w + q.cross(&t)
+    }
+
+    // Interpolación lineal normalizada: barata, pero la velocidad angular no es constante
+    pub fn nlerp(&self, other: &Quat, t: f32) -> Quat {
+        let other = self.nearest(other);
+        Quat::new(
+            lerp(self.w, other.w, t),
+            lerp(self.x, other.x, t),
+            lerp(self.y, other.y, t),
+            lerp(self.z, other.z, t),
+        )
+        .normalize()
+    }
+
+    // Interpolación esférica: velocidad angular constante por el camino más corto
+    pub fn slerp(&self, other: &Quat, t: f32) -> Quat {
+        let other = self.nearest(other);
+        let cos = self.dot(&other).clamp(-1.0, 1.0);
+        // Casi iguales: el seno del ángulo es ~0 y nlerp da lo mismo
+        if cos > 0.9995 {
+            return self.nlerp(&other, t);
+        }
+        let angle = cos.acos();
+        let sin = angle.sin();
+        let a = ((1.0 - t) * angle).sin() / sin;
+        let b = (t * angle).sin() / sin;
+        Quat::new(
+            a * self.w + b * other.w,
+            a * self.x + b * other.x,
+            a * self.y + b * other.y,
+            a * self.z + b * other.z,
+        )
+        .normalize()
+    }
+
+    // q y -q son la misma rotación; se elige el que queda del mismo lado que `self`
+    fn nearest(&self, other: &Quat) -> Quat {
+        if self.dot(other) < 0.0 {
+            Quat::new(-other.w, -other.x, -other.y, -other.z)
+        } else {
+            *other
+        }
+    }
+
+    // Ángulo de la rotación que lleva de `self` a `other`
+    #[allow(dead_code)] // API de Quat que el render aún no usa; la cubren las pruebas
+    pub fn angle_to(&self, other: &Quat) -> f32 {
+        2.0 * self.dot(other).abs().clamp(0.0, 1.0).acos()
+    }
+
+    // Compara rotaciones, así que q y -q son iguales
+    #[allow(dead_code)] // API de Quat que el render aún no usa; la cubren las pruebas
+    pub fn approx_eq(&self, other: &Quat, epsilon: f32) -> bool {
+        let other = self.nearest(other);
+        approx_eq(self.w, other.w, epsilon)
+            && approx_eq(self.x, other.x, epsilon)
+            && approx_eq(self.y, other.y, epsilon)
+            && approx_eq(self.z, other.z, epsilon)
+    }
+}
+
+// Composición: `a * b` aplica primero `b` y después `a`, como las matrices
+impl Mul<Quat> for Quat {
+    type Output = Quat;
+    fn mul(self, o: Quat) -> Quat {
+        Quat::new(
+            self.w * o.w - self.x * o.x - self.y * o.y - self.z * o.z,
+            self.w * o.x + self.x * o.w + self.y * o.z - self.z * o.y,
+            self.w * o.y - self.x * o.z + self.y * o.w + self.z * o.x,
+            self.w * o.z + self.x * o.y - self.y * o.x + self.z * o.w,
+        )
+    }
+}
+
+impl Mul<Vec3> for Quat {
+    type Output = Vec3;
+    fn mul(self, v: Vec3) -> Vec3 {
+        self.rotate(&v)
+    }
+}
+
+impl fmt::Display for Quat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_components(f, &[self.w, self.x, self.y, self.z])
+    }
+}
+
+// ============ Operaciones sobre matrices NxN ============
+
+fn multiply<const N: usize>(a: &[[f32; N]; N], b: &[[f32; N]; N]) -> [[f32; N]; N] {
+    let mut result = [[0.0; N]; N];
+    for (i, row) in result.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..N).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    result
+}
+
+fn transpose<const N: usize>(m: &[[f32; N]; N]) -> [[f32; N]; N] {
+    let mut result = [[0.0; N]; N];
+    for (i, row) in m.iter().enumerate() {
+        for (j, &value) in row.iter().enumerate() {
+            result[j][i] = value;
+        }
+    }
+    result
+}
+
+// Gauss-Jordan con pivoteo parcial
+fn invert<const N: usize>(m: &[[f32; N]; N]) -> Option<[[f32; N]; N]> {
+    let largest = m.iter().flatten().fold(0.0f32, |max, value| max.max(value.abs()));
+    if largest == 0.0 || !largest.is_finite() {
+        return None;
+    }
+
+    let mut a = *m;
+    let mut inverse = [[0.0; N]; N];
+    for (i, row) in inverse.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+
+    for column in 0..N {
+        let pivot_row = (column..N).max_by(|&i, &j| a[i][column].abs().total_cmp(&a[j][column].abs()))?;
+        if a[pivot_row][column].abs() <= SINGULAR_EPSILON * largest {
+            return None;
+        }
+        a.swap(column, pivot_row);
+        inverse.swap(column, pivot_row);
+
+        let pivot = a[column][column];
+        for j in 0..N {
+            a[column][j] /= pivot;
+            inverse[column][j] /= pivot;
+        }
+
+        for row in 0..N {
+            if row == column {
+                continue;
+            }
+            let factor = a[row][column];
+            if factor == 0.0 {
+                continue;
+            }
+            for j in 0..N {
+                a[row][j] -= factor * a[column][j];
+                inverse[row][j] -= factor * inverse[column][j];
+            }
+        }
+    }
+    Some(inverse)
+}
+
+fn write_components(f: &mut fmt::Formatter, components: &[f32]) -> fmt::Result {
+    write!(f, "(")?;
+    for (i, value) in components.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        fmt::Display::fmt(value, f)?;
+    }
+    write!(f, ")")
+}
+
+// Una fila por línea
+fn write_rows<const N: usize>(f: &mut fmt::Formatter, rows: &[[f32; N]; N]) -> fmt::Result {
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            writeln!(f)?;
+        }
+        write!(f, "[")?;
+        for (j, value) in row.iter().enumerate() {
+            if j > 0 {
+                write!(f, ", ")?;
+            }
+            fmt::Display::fmt(value, f)?;
+        }
+        write!(f, "]")?;
+    }
+    Ok(())
+}
+
 // ============ Transformaciones ============
 
 pub fn translate(translation: &Vec3) -> Mat4 {
@@ -191,6 +992,7 @@ pub fn scale(scale: &Vec3) -> Mat4 {
     )
 }
 
+#[allow(dead_code)] // Los modelos se orientan con Quat; se conserva como referencia de las pruebas
 pub fn rotate_x(angle: f32) -> Mat4 {
     let c = angle.cos();
     let s = angle.sin();
@@ -202,6 +1004,7 @@ pub fn rotate_x(angle: f32) -> Mat4 {
     )
 }
 
+#[allow(dead_code)] // Los modelos se orientan con Quat; se conserva como referencia de las pruebas
 pub fn rotate_y(angle: f32) -> Mat4 {
     let c = angle.cos();
     let s = angle.sin();
@@ -213,6 +1016,7 @@ pub fn rotate_y(angle: f32) -> Mat4 {
     )
 }
 
+#[allow(dead_code)] // Los modelos se orientan con Quat; se conserva como referencia de las pruebas
 pub fn rotate_z(angle: f32) -> Mat4 {
     let c = angle.cos();
     let s = angle.sin();
@@ -256,3 +1060,343 @@ pub fn viewport(width: f32, height: f32) -> Mat4 {
         0.0, 0.0, 0.0, 1.0,
     )
 }
+
+// Distancia a lo largo del rayo (dirección normalizada) hasta donde entra en la esfera. None si no la
+// toca, si se aleja de ella o si el origen ya está adentro.
+pub fn ray_sphere(origin: &Vec3, direction: &Vec3, center: &Vec3, radius: f32) -> Option<f32> {
+    let to_origin = *origin - *center;
+    let b = to_origin.dot(direction);
+    let c = to_origin.magnitude_squared() - radius * radius;
+    if c < 0.0 || b >= 0.0 {
+        return None;
+    }
+    let discriminant = b * b - c;
+    (discriminant >= 0.0).then(|| -b - discriminant.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Generador xorshift: casos "aleatorios" pero iguales en cada corrida
+    struct Rng(u32);
+
+    impl Rng {
+        fn next(&mut self) -> f32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 17;
+            self.0 ^= self.0 << 5;
+            self.0 as f32 / u32::MAX as f32
+        }
+
+        fn range(&mut self, min: f32, max: f32) -> f32 {
+            min + (max - min) * self.next()
+        }
+
+        fn vec3(&mut self, extent: f32) -> Vec3 {
+            Vec3::new(self.range(-extent, extent), self.range(-extent, extent), self.range(-extent, extent))
+        }
+
+        fn matrix(&mut self) -> Mat4 {
+            let mut m = Mat4::identity();
+            for value in m.data.iter_mut().flatten() {
+                *value = self.range(-2.0, 2.0);
+            }
+            m
+        }
+
+        // Traslación, rotación y escala como las de los cuerpos de la escena
+        fn transform(&mut self) -> Mat4 {
+            let scale_factor = Vec3::new(self.range(0.1, 3.0), self.range(0.1, 3.0), self.range(0.1, 3.0));
+            translate(&self.vec3(50.0))
+                * rotate_y(self.range(-3.2, 3.2))
+                * rotate_x(self.range(-3.2, 3.2))
+                * rotate_z(self.range(-3.2, 3.2))
+                * scale(&scale_factor)
+        }
+    }
+
+    const CASES: usize = 200;
+
+    #[test]
+    fn transform_times_inverse_is_identity() {
+        let mut rng = Rng(0x1234_5678);
+        for _ in 0..CASES {
+            let m = rng.transform();
+            let inverse = m.inverse().expect("una transformación con escala no nula es invertible");
+            assert!((m * inverse).approx_eq(&Mat4::identity(), 1e-4), "M * M⁻¹ ≠ I para\n{}", m);
+            assert!((inverse * m).approx_eq(&Mat4::identity(), 1e-4), "M⁻¹ * M ≠ I para\n{}", m);
+        }
+    }
+
+    #[test]
+    fn general_matrix_times_inverse_is_identity() {
+        let mut rng = Rng(0x0BAD_F00D);
+        let mut checked = 0;
+        while checked < CASES {
+            let m = rng.matrix();
+            // Las casi singulares amplifican el error de redondeo; no prueban la inversa
+            if m.determinant().abs() < 0.1 {
+                continue;
+            }
+            let inverse = m.inverse().expect("determinante lejos de cero");
+            assert!((m * inverse).approx_eq(&Mat4::identity(), 1e-3), "M * M⁻¹ ≠ I para\n{}", m);
+            checked += 1;
+        }
+    }
+
+    #[test]
+    fn singular_matrices_have_no_inverse() {
+        let flat = scale(&Vec3::new(1.0, 0.0, 1.0));
+        assert!(flat.inverse().is_none());
+        assert!(Mat4 { data: [[0.0; 4]; 4] }.inverse().is_none());
+
+        let mut repeated = Mat4::identity();
+        repeated.data[3] = repeated.data[1];
+        assert!(repeated.inverse().is_none());
+        assert!(Mat3::new(1.0, 2.0, 3.0, 2.0, 4.0, 6.0, 0.0, 1.0, 0.0).inverse().is_none());
+    }
+
+    #[test]
+    fn determinant_is_multiplicative() {
+        let mut rng = Rng(0xC0FF_EE00);
+        for _ in 0..CASES {
+            let (a, b) = (rng.matrix(), rng.matrix());
+            let product = (a * b).determinant();
+            assert!(approx_eq(product, a.determinant() * b.determinant(), 1e-3));
+        }
+        assert!(approx_eq(scale(&Vec3::new(2.0, 3.0, 4.0)).determinant(), 24.0, EPSILON));
+        assert!(approx_eq(rotate_y(0.7).determinant(), 1.0, EPSILON));
+    }
+
+    #[test]
+    fn transpose_identities() {
+        let mut rng = Rng(0x5EED_0001);
+        for _ in 0..CASES {
+            let (a, b) = (rng.matrix(), rng.matrix());
+            assert_eq!(a.transpose().transpose(), a);
+            assert!((a * b).transpose().approx_eq(&(b.transpose() * a.transpose()), 1e-5));
+            assert!(approx_eq(a.transpose().determinant(), a.determinant(), 1e-4));
+        }
+    }
+
+    #[test]
+    fn rotation_inverse_is_transpose() {
+        let mut rng = Rng(0x0000_ABCD);
+        for _ in 0..CASES {
+            let r = rotate_x(rng.range(-3.2, 3.2)) * rotate_y(rng.range(-3.2, 3.2)) * rotate_z(rng.range(-3.2, 3.2));
+            assert!(r.inverse().unwrap().approx_eq(&r.transpose(), 1e-4));
+
+            let r3 = Mat3::from_mat4(&r);
+            assert!(approx_eq(r3.determinant(), 1.0, 1e-4));
+            assert!((r3 * r3.inverse().unwrap()).approx_eq(&Mat3::identity(), 1e-4));
+        }
+    }
+
+    #[test]
+    fn look_at_is_orthonormal() {
+        let mut rng = Rng(0x7777_1111);
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        for _ in 0..CASES {
+            let eye = rng.vec3(40.0);
+            let center = rng.vec3(40.0);
+            // Mirar justo hacia arriba o abajo deja la base indefinida
+            let forward = (center - eye).normalize();
+            if forward.cross(&up).magnitude() < 0.05 {
+                continue;
+            }
+            let view = look_at(&eye, &center, &up);
+
+            let rotation = Mat3::from_mat4(&view);
+            assert!((rotation * rotation.transpose()).approx_eq(&Mat3::identity(), 1e-4));
+            assert!(approx_eq(rotation.determinant(), 1.0, 1e-4));
+
+            // El ojo va al origen y el punto mirado queda sobre -z, a la misma distancia
+            assert!(view.transform_point(&eye).approx_eq(&Vec3::zeros(), 1e-3));
+            let target = view.transform_point(&center);
+            let expected = Vec3::new(0.0, 0.0, -eye.distance(&center));
+            assert!(target.approx_eq(&expected, 1e-3), "{} ≠ {}", target, expected);
+        }
+    }
+
+    #[test]
+    fn normal_matrix_keeps_normals_perpendicular() {
+        let mut rng = Rng(0x2468_ACE0);
+        for _ in 0..CASES {
+            let model = rng.transform();
+            let normal_matrix = Mat3::normal_matrix(&model).unwrap();
+            let (a, b) = (rng.vec3(1.0), rng.vec3(1.0));
+            let normal = a.cross(&b);
+
+            let tangent = model.transform_vector(&a).normalize();
+            let transformed = (normal_matrix * normal).normalize();
+            assert!(tangent.dot(&transformed).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn vector_operations() {
+        let mut rng = Rng(0x1357_9BDF);
+        for _ in 0..CASES {
+            let (a, b) = (rng.vec3(10.0), rng.vec3(10.0));
+            let cross = a.cross(&b);
+            assert!(approx_eq(cross.dot(&a), 0.0, 1e-3 * a.magnitude_squared()));
+            assert!(approx_eq(cross.dot(&b), 0.0, 1e-3 * b.magnitude_squared()));
+            assert!(approx_eq(a.normalize().magnitude(), 1.0, 1e-5));
+            assert!(a.lerp(&b, 0.0).approx_eq(&a, EPSILON));
+            assert!(a.lerp(&b, 1.0).approx_eq(&b, 1e-4));
+            assert!(((a * b) / b).approx_eq(&a, 1e-3));
+        }
+
+        let v = Vec4::new(1.0, 2.0, 3.0, 4.0);
+        let w = Vec4::new(0.5, -1.0, 2.0, 1.0);
+        assert_eq!((v - w) + w, v);
+        assert_eq!(v * w, Vec4::new(0.5, -2.0, 6.0, 4.0));
+        assert_eq!(v / 2.0, Vec4::new(0.5, 1.0, 1.5, 2.0));
+        assert_eq!(Vec4::from_vec3(v.xyz(), 4.0), v);
+        assert_eq!(v.lerp(&w, 0.5), Vec4::new(0.75, 0.5, 2.5, 2.5));
+
+        let p = Vec2::new(3.0, 4.0);
+        assert_eq!(p.magnitude(), 5.0);
+        assert_eq!(p.perpendicular().dot(&p), 0.0);
+        assert_eq!(Vec2::new(1.0, 0.0).cross(&Vec2::new(0.0, 1.0)), 1.0);
+        assert_eq!(p * Vec2::new(2.0, 0.5) - p, Vec2::new(3.0, -2.0));
+        assert!(approx_eq(lerp(2.0, 4.0, 0.25), 2.5, EPSILON));
+    }
+
+    #[test]
+    fn transform_point_and_vector() {
+        let m = translate(&Vec3::new(1.0, 2.0, 3.0)) * scale(&Vec3::new(2.0, 2.0, 2.0));
+        assert_eq!(m.transform_point(&Vec3::new(1.0, 0.0, 0.0)), Vec3::new(3.0, 2.0, 3.0));
+        assert_eq!(m.transform_vector(&Vec3::new(1.0, 0.0, 0.0)), Vec3::new(2.0, 0.0, 0.0));
+
+        // La proyección divide por w
+        let projection = perspective(std::f32::consts::FRAC_PI_2, 1.0, 0.1, 100.0);
+        let near = projection.transform_point(&Vec3::new(0.0, 0.0, -0.1));
+        let far = projection.transform_point(&Vec3::new(0.0, 0.0, -100.0));
+        assert!(approx_eq(near.z, -1.0, 1e-4));
+        assert!(approx_eq(far.z, 1.0, 1e-4));
+    }
+
+    #[test]
+    fn quaternion_matches_rotation_matrices() {
+        let mut rng = Rng(0x9999_0001);
+        for _ in 0..CASES {
+            let angles = rng.vec3(3.2);
+            let matrix = rotate_z(angles.z) * rotate_y(angles.y) * rotate_x(angles.x);
+            let q = Quat::from_euler(&angles);
+            assert!(q.to_mat4().approx_eq(&matrix, 1e-4));
+            assert!(approx_eq(q.magnitude(), 1.0, 1e-5));
+
+            // Ida y vuelta por la matriz y por los ángulos
+            assert!(Quat::from_mat4(&matrix).approx_eq(&q, 1e-3));
+            let back = Quat::from_euler(&q.to_euler());
+            assert!(back.approx_eq(&q, 1e-3), "{} → {} → {}", angles, q.to_euler(), back);
+
+            let v = rng.vec3(5.0);
+            assert!((q * v).approx_eq(&matrix.transform_vector(&v), 1e-3));
+            assert!((q.inverse() * (q * v)).approx_eq(&v, 1e-3));
+        }
+    }
+
+    #[test]
+    fn quaternion_composition_and_axis_angle() {
+        let mut rng = Rng(0x4242_4242);
+        for _ in 0..CASES {
+            let (a, b) = (Quat::from_euler(&rng.vec3(3.2)), Quat::from_euler(&rng.vec3(3.2)));
+            assert!((a * b).to_mat3().approx_eq(&(a.to_mat3() * b.to_mat3()), 1e-4));
+
+            let axis = rng.vec3(1.0).normalize();
+            let angle = rng.range(0.01, 6.2);
+            let (back_axis, back_angle) = Quat::from_axis_angle(&axis, angle).to_axis_angle();
+            assert!(back_axis.approx_eq(&axis, 1e-2), "{} ≠ {}", back_axis, axis);
+            assert!(approx_eq(back_angle, angle, 1e-3));
+        }
+    }
+
+    #[test]
+    fn euler_survives_gimbal_lock() {
+        // Con Y = 90° X y Z giran alrededor del mismo eje: los ángulos cambian, la rotación no
+        let q = Quat::from_euler(&Vec3::new(0.4, std::f32::consts::FRAC_PI_2, 0.3));
+        let back = Quat::from_euler(&q.to_euler());
+        assert!(back.approx_eq(&q, 1e-3));
+    }
+
+    #[test]
+    fn slerp_has_constant_angular_speed() {
+        let mut rng = Rng(0x1111_2222);
+        for _ in 0..CASES {
+            let (a, b) = (Quat::from_euler(&rng.vec3(3.2)), Quat::from_euler(&rng.vec3(3.2)));
+            let total = a.angle_to(&b);
+            assert!(a.slerp(&b, 0.0).approx_eq(&a, 1e-4));
+            assert!(a.slerp(&b, 1.0).approx_eq(&b, 1e-4));
+            for t in [0.25, 0.5, 0.75] {
+                let q = a.slerp(&b, t);
+                assert!(approx_eq(a.angle_to(&q), total * t, 2e-3));
+                assert!(approx_eq(a.nlerp(&b, t).magnitude(), 1.0, 1e-5));
+            }
+        }
+        // Toma el camino corto aunque el signo sea el opuesto
+        let a = Quat::from_axis_angle(&Vec3::new(0.0, 1.0, 0.0), 0.1);
+        let b = Quat::from_axis_angle(&Vec3::new(0.0, 1.0, 0.0), -0.1);
+        let flipped = Quat::new(-b.w, -b.x, -b.y, -b.z);
+        assert!(a.slerp(&flipped, 0.5).approx_eq(&Quat::identity(), 1e-5));
+    }
+
+    #[test]
+    fn look_rotation_matches_look_at() {
+        let mut rng = Rng(0x3333_4444);
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        for _ in 0..CASES {
+            let (eye, center) = (rng.vec3(40.0), rng.vec3(40.0));
+            if (center - eye).normalize().cross(&up).magnitude() < 0.05 {
+                continue;
+            }
+            // La vista rota al revés que la cámara
+            let orientation = Quat::look_rotation(&(center - eye), &up);
+            let view = Mat3::from_mat4(&look_at(&eye, &center, &up));
+            assert!(orientation.to_mat3().transpose().approx_eq(&view, 1e-4));
+            assert!((orientation * Vec3::new(0.0, 0.0, -1.0)).approx_eq(&(center - eye).normalize(), 1e-4));
+        }
+    }
+
+    #[test]
+    fn ray_sphere_finds_entry_point() {
+        let mut rng = Rng(0x5555_6666);
+        for _ in 0..CASES {
+            let (origin, center) = (rng.vec3(20.0), rng.vec3(20.0));
+            let radius = rng.range(0.1, 5.0);
+            let direction = rng.vec3(1.0).normalize();
+            match ray_sphere(&origin, &direction, &center, radius) {
+                Some(t) => {
+                    assert!(t >= 0.0);
+                    let hit = origin + direction * t;
+                    assert!(approx_eq(hit.distance(&center), radius, 1e-3));
+                    assert!(direction.dot(&(hit - center)) <= 1e-3);
+                }
+                // Sin choque, ningún punto del rayo hacia adelante cae adentro de la esfera
+                None if origin.distance(&center) > radius => {
+                    let closest = direction.dot(&(center - origin)).max(0.0);
+                    assert!((origin + direction * closest).distance(&center) >= radius * (1.0 - 1e-3));
+                }
+                None => {}
+            }
+        }
+        // Desde adentro o alejándose no hay choque
+        let center = Vec3::zeros();
+        let forward = Vec3::new(0.0, 0.0, 1.0);
+        assert_eq!(ray_sphere(&Vec3::new(0.0, 0.0, 0.5), &forward, &center, 1.0), None);
+        assert_eq!(ray_sphere(&Vec3::new(0.0, 0.0, 2.0), &forward, &center, 1.0), None);
+        assert_eq!(ray_sphere(&Vec3::new(0.0, 0.0, -3.0), &forward, &center, 1.0), Some(2.0));
+    }
+
+    #[test]
+    fn display_respects_precision() {
+        assert_eq!(format!("{}", Vec2::new(1.0, -2.5)), "(1, -2.5)");
+        assert_eq!(format!("{:.2}", Vec3::new(1.0, 2.0, 3.0)), "(1.00, 2.00, 3.00)");
+        assert_eq!(format!("{:.1}", Vec4::new(0.25, 0.0, 1.0, 1.0)), "(0.2, 0.0, 1.0, 1.0)");
+        assert_eq!(format!("{:.0}", Mat3::identity()), "[1, 0, 0]\n[0, 1, 0]\n[0, 0, 1]");
+        assert_eq!(format!("{}", Mat4::identity()).lines().count(), 4);
+        assert_eq!(format!("{:.1}", Quat::identity()), "(1.0, 0.0, 0.0, 0.0)");
+    }
+}
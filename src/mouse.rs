@@ -0,0 +1,71 @@
+use crate::camera::{Camera, CameraMode};
+use minifb::{MouseButton, MouseMode, Window};
+
+pub const DEFAULT_MOUSE_SENSITIVITY: f32 = 1.0;
+
+// Con sensibilidad 1: radianes por píxel arrastrado
+const ROTATE_PER_PIXEL: f32 = 0.005;
+// Desplazamiento por píxel, relativo a la distancia al centro (así la escena sigue al cursor)
+const PAN_PER_PIXEL: f32 = 0.0015;
+// Unidades por paso de la rueda: zoom en órbita, avance en vuelo libre
+const ZOOM_PER_STEP: f32 = 1.0;
+const DOLLY_PER_STEP: f32 = 0.5;
+
+// Arrastrar con el botón izquierdo gira (órbita o mirada), con el derecho o el del medio desplaza
+// el centro (salvo siguiendo un cuerpo), y la rueda acerca o avanza
+pub struct MouseControl {
+    pub sensitivity: f32,
+    // Arrastrar hacia arriba baja la cámara en vez de subirla
+    pub invert_y: bool,
+    last_position: Option<(f32, f32)>,
+}
+
+impl MouseControl {
+    pub fn new(sensitivity: f32, invert_y: bool) -> Self {
+        MouseControl {
+            sensitivity,
+            invert_y,
+            last_position: None,
+        }
+    }
+
+    // Con `active` en false solo se registra la posición del cursor
+    pub fn update(&mut self, window: &Window, camera: &mut Camera, active: bool) {
+        // La posición se sigue siempre para que apretar un botón no produzca un salto
+        let position = window.get_unscaled_mouse_pos(MouseMode::Discard);
+        let (dx, dy) = match (self.last_position, position) {
+            (Some((x0, y0)), Some((x1, y1))) => (x1 - x0, y1 - y0),
+            _ => (0.0, 0.0),
+        };
+        self.last_position = position;
+        let scroll = window.get_scroll_wheel();
+        if !active {
+            return;
+        }
+
+        // Hacia arriba es positivo
+        let dy = if self.invert_y { dy } else { -dy };
+        let rotate = ROTATE_PER_PIXEL * self.sensitivity;
+        let pan = PAN_PER_PIXEL * self.sensitivity * camera.distance;
+
+        if window.get_mouse_down(MouseButton::Left) {
+            match camera.mode {
+                CameraMode::Orbit | CameraMode::Follow => camera.orbit(dx * rotate, dy * rotate),
+                CameraMode::FreeFly => camera.look(-dx * rotate, dy * rotate),
+            }
+        }
+        // Siguiendo un cuerpo el centro es el cuerpo: no se desplaza
+        let can_pan = camera.mode != CameraMode::Follow;
+        if can_pan && (window.get_mouse_down(MouseButton::Right) || window.get_mouse_down(MouseButton::Middle)) {
+            // Se arrastra la escena: el centro va al revés que el cursor
+            camera.pan(-dx * pan, -dy * pan);
+        }
+
+        if let Some((_, steps)) = scroll {
+            match camera.mode {
+                CameraMode::Orbit | CameraMode::Follow => camera.zoom(-steps * ZOOM_PER_STEP * self.sensitivity),
+                CameraMode::FreeFly => camera.dolly(steps * DOLLY_PER_STEP * self.sensitivity),
+            }
+        }
+    }
+}
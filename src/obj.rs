@@ -28,67 +28,65 @@ impl Obj {
             }
 
             match parts[0] {
-                "v" => {
-                    if parts.len() >= 4 {
-                        let x: f32 = parts[1].parse().unwrap_or(0.0);
-                        let y: f32 = parts[2].parse().unwrap_or(0.0);
-                        let z: f32 = parts[3].parse().unwrap_or(0.0);
-                        positions.push(Vec3::new(x, y, z));
-                    }
+                "v" if parts.len() >= 4 => {
+                    let x: f32 = parts[1].parse().unwrap_or(0.0);
+                    let y: f32 = parts[2].parse().unwrap_or(0.0);
+                    let z: f32 = parts[3].parse().unwrap_or(0.0);
+                    positions.push(Vec3::new(x, y, z));
                 }
-                "vn" => {
-                    if parts.len() >= 4 {
-                        let x: f32 = parts[1].parse().unwrap_or(0.0);
-                        let y: f32 = parts[2].parse().unwrap_or(0.0);
-                        let z: f32 = parts[3].parse().unwrap_or(0.0);
-                        normals.push(Vec3::new(x, y, z));
-                    }
+                "vn" if parts.len() >= 4 => {
+                    let x: f32 = parts[1].parse().unwrap_or(0.0);
+                    let y: f32 = parts[2].parse().unwrap_or(0.0);
+                    let z: f32 = parts[3].parse().unwrap_or(0.0);
+                    normals.push(Vec3::new(x, y, z));
                 }
-                "vt" => {
-                    if parts.len() >= 3 {
-                        let u: f32 = parts[1].parse().unwrap_or(0.0);
-                        let v: f32 = parts[2].parse().unwrap_or(0.0);
-                        tex_coords.push(Vec3::new(u, v, 0.0));
-                    }
+                "vt" if parts.len() >= 3 => {
+                    let u: f32 = parts[1].parse().unwrap_or(0.0);
+                    let v: f32 = parts[2].parse().unwrap_or(0.0);
+                    tex_coords.push(Vec3::new(u, v, 0.0));
                 }
-                "f" => {
-                    if parts.len() >= 4 {
-                        for i in 1..parts.len() {
-                            let face_parts: Vec<&str> = parts[i].split('/').collect();
-                            
-                            let pos_idx: usize = face_parts[0].parse::<usize>().unwrap_or(1) - 1;
-                            let tex_idx: usize = if face_parts.len() > 1 && !face_parts[1].is_empty() {
-                                face_parts[1].parse::<usize>().unwrap_or(1) - 1
-                            } else {
-                                0
-                            };
-                            let norm_idx: usize = if face_parts.len() > 2 {
-                                face_parts[2].parse::<usize>().unwrap_or(1) - 1
-                            } else {
-                                0
-                            };
+                "f" if parts.len() >= 4 => {
+                    // Polígonos de más de 3 vértices se triangulan en abanico
+                    let first = vertices.len();
+                    for part in &parts[1..] {
+                        let face_parts: Vec<&str> = part.split('/').collect();
+                        
+                        let pos_idx: usize = face_parts[0].parse::<usize>().unwrap_or(1) - 1;
+                        let tex_idx: usize = if face_parts.len() > 1 && !face_parts[1].is_empty() {
+                            face_parts[1].parse::<usize>().unwrap_or(1) - 1
+                        } else {
+                            0
+                        };
+                        let norm_idx: usize = if face_parts.len() > 2 {
+                            face_parts[2].parse::<usize>().unwrap_or(1) - 1
+                        } else {
+                            0
+                        };
 
-                            let position = if pos_idx < positions.len() {
-                                positions[pos_idx]
-                            } else {
-                                Vec3::new(0.0, 0.0, 0.0)
-                            };
+                        let position = if pos_idx < positions.len() {
+                            positions[pos_idx]
+                        } else {
+                            Vec3::new(0.0, 0.0, 0.0)
+                        };
 
-                            let normal = if norm_idx < normals.len() {
-                                normals[norm_idx]
-                            } else {
-                                Vec3::new(0.0, 1.0, 0.0)
-                            };
+                        let normal = if norm_idx < normals.len() {
+                            normals[norm_idx]
+                        } else {
+                            Vec3::new(0.0, 1.0, 0.0)
+                        };
 
-                            let tex_coord = if tex_idx < tex_coords.len() {
-                                tex_coords[tex_idx]
-                            } else {
-                                Vec3::new(0.0, 0.0, 0.0)
-                            };
+                        let tex_coord = if tex_idx < tex_coords.len() {
+                            tex_coords[tex_idx]
+                        } else {
+                            Vec3::new(0.0, 0.0, 0.0)
+                        };
 
-                            vertices.push(Vertex::new(position, normal, tex_coord));
-                            indices.push(vertices.len() - 1);
-                        }
+                        vertices.push(Vertex::new(position, normal, tex_coord));
+                    }
+                    for k in 1..(vertices.len() - first - 1) {
+                        indices.push(first);
+                        indices.push(first + k);
+                        indices.push(first + k + 1);
                     }
                 }
                 _ => {}
@@ -97,4 +95,49 @@ impl Obj {
 
         Ok(Obj { vertices, indices })
     }
+
+    // Radio de la esfera centrada en el origen que contiene al modelo
+    pub fn bounding_radius(&self) -> f32 {
+        self.vertices
+            .iter()
+            .map(|v| v.position.magnitude())
+            .fold(0.0, f32::max)
+    }
+
+    // Tangente por vértice a partir de cómo cambian las UV en cada triángulo, ortogonal a la normal.
+    // Sin UV útiles se usa cualquier dirección perpendicular a la normal.
+    pub fn tangents(&self) -> Vec<Vec3> {
+        let mut tangents = vec![Vec3::zeros(); self.vertices.len()];
+        for triangle in self.indices.chunks_exact(3) {
+            let (a, b, c) = (&self.vertices[triangle[0]], &self.vertices[triangle[1]], &self.vertices[triangle[2]]);
+            let edge1 = b.position - a.position;
+            let edge2 = c.position - a.position;
+            let (du1, dv1) = (b.tex_coords.x - a.tex_coords.x, b.tex_coords.y - a.tex_coords.y);
+            let (du2, dv2) = (c.tex_coords.x - a.tex_coords.x, c.tex_coords.y - a.tex_coords.y);
+            let det = du1 * dv2 - du2 * dv1;
+            if det.abs() < 1e-8 {
+                continue;
+            }
+            let tangent = (edge1 * dv2 - edge2 * dv1) * (1.0 / det);
+            for &index in triangle {
+                tangents[index] += tangent;
+            }
+        }
+
+        self.vertices
+            .iter()
+            .zip(tangents)
+            .map(|(vertex, tangent)| {
+                let normal = vertex.normal;
+                // Gram-Schmidt: quitar la componente sobre la normal
+                let orthogonal = tangent - normal * normal.dot(&tangent);
+                if orthogonal.magnitude() > 1e-6 {
+                    orthogonal.normalize()
+                } else {
+                    let axis = if normal.x.abs() < 0.9 { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+                    normal.cross(&axis).normalize()
+                }
+            })
+            .collect()
+    }
 }
@@ -1,39 +1,37 @@
 use crate::math::Vec3;
-use crate::shaders::Fragment;
+use crate::shaders::{Fragment, Uniforms, FragmentShader};
 use crate::framebuffer::Color;
+use crate::lighting::{shade, Material};
+
+fn to_color(color: Vec3) -> Color {
+    Color::from_float(color.x, color.y, color.z)
+}
 
 // Shader para el Sol - Más brillante y pulsante
-pub fn sun_shader(fragment: &Fragment) -> Color {
+pub fn sun_shader(fragment: &Fragment, _uniforms: &Uniforms) -> Color {
     let bright = 1.5;  // Aumentado de 1.0
     let flicker = (fragment.position.x * 0.1 + fragment.position.y * 0.1).sin() * 0.15 + 1.0;  // Más pulsación
     
+    // Valores HDR: el tone mapping decide cuánto satura
     Color::from_float(
-        (bright * flicker).min(1.0),
-        (bright * 0.8 * flicker).min(1.0),
-        (bright * 0.4 * flicker).min(1.0),
+        bright * flicker,
+        bright * 0.8 * flicker,
+        bright * 0.4 * flicker,
     )
 }
 
-pub fn rocky_planet_shader(fragment: &Fragment) -> Color {
-    let light_dir = Vec3::new(0.0, 0.0, 1.0).normalize();
-    let intensity = fragment.normal.dot(&light_dir).max(0.3);
-    
+// Roca mate: solo difuso
+pub fn rocky_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let base_color = Vec3::new(0.8, 0.5, 0.4);
     
     let pattern = ((fragment.tex_coords.x * 20.0).sin() * (fragment.tex_coords.y * 20.0).cos()).abs();
     let variation = 0.8 + pattern * 0.2;
     
-    Color::from_float(
-        base_color.x * intensity * variation * 1.2,
-        base_color.y * intensity * variation * 1.2,
-        base_color.z * intensity * variation * 1.2
-    )
+    let material = Material::matte(base_color * (variation * 1.2));
+    to_color(shade(&material, fragment, uniforms))
 }
 
-pub fn gas_planet_shader(fragment: &Fragment) -> Color {
-    let light_dir = Vec3::new(1.0, 0.5, 0.5).normalize();
-    let intensity = fragment.normal.dot(&light_dir).max(0.3);  // Aumentado de 0.1 a 0.3
-    
+pub fn gas_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let bands = (fragment.tex_coords.y * 10.0).sin() * 0.5 + 0.5;
     let turbulence = ((fragment.tex_coords.x * 15.0).sin() * (fragment.tex_coords.y * 3.0).cos()).abs();
     
@@ -43,71 +41,88 @@ pub fn gas_planet_shader(fragment: &Fragment) -> Color {
     let mixed = color1 * bands + color2 * (1.0 - bands);
     let final_color = mixed * (0.9 + turbulence * 0.1);
     
-    Color::from_float(
-        final_color.x * intensity * 1.2,
-        final_color.y * intensity * 1.2,
-        final_color.z * intensity * 1.2
-    )
+    // Atmósfera con un brillo especular muy difuso
+    let material = Material::glossy(final_color * 1.2, 0.1, 8.0, 0.8);
+    to_color(shade(&material, fragment, uniforms))
 }
 
-pub fn earth_shader(fragment: &Fragment) -> Color {
-    let light_dir = Vec3::new(0.0, 0.0, 1.0).normalize();
-    let intensity = fragment.normal.dot(&light_dir).max(0.3);
-    
+// Océanos con reflejo del Sol, continentes mates
+pub fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let ocean = ((fragment.tex_coords.x * 8.0).sin() * (fragment.tex_coords.y * 8.0).cos()).abs();
     
     let blue = Vec3::new(0.3, 0.5, 1.0);   // Más brillante
     let green = Vec3::new(0.4, 0.8, 0.4);  // Más brillante
     
-    let color = if ocean > 0.5 { blue } else { green };
-    
-    Color::from_float(
-        color.x * intensity * 1.3,  // +30% brillo
-        color.y * intensity * 1.3,
-        color.z * intensity * 1.3,
-    )
+    let material = if ocean > 0.5 {
+        Material::glossy(blue * 1.3, 0.9, 64.0, 0.15)
+    } else {
+        Material::matte(green * 1.3)
+    };
+    to_color(shade(&material, fragment, uniforms))
 }
 
-pub fn red_planet_shader(fragment: &Fragment) -> Color {
-    let light_dir = Vec3::new(0.0, 0.0, 1.0).normalize();
-    let intensity = fragment.normal.dot(&light_dir).max(0.3);
-    
+pub fn red_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let craters = ((fragment.tex_coords.x * 25.0).sin() * (fragment.tex_coords.y * 25.0).cos()).abs();
     let variation = 0.7 + craters * 0.3;
     
-    Color::from_float(
-        1.0 * intensity * variation,      // Más brillante
-        0.4 * intensity * variation * 1.2,
-        0.25 * intensity * variation * 1.2,
-    )
+    let albedo = Vec3::new(1.0, 0.4 * 1.2, 0.25 * 1.2) * variation;
+    to_color(shade(&Material::matte(albedo), fragment, uniforms))
 }
 
 // Shader para planeta helado - Más brillante y reflectivo
-pub fn ice_planet_shader(fragment: &Fragment) -> Color {
-    let light_dir = Vec3::new(1.0, 0.5, 0.5).normalize();
-    let intensity = fragment.normal.dot(&light_dir).max(0.3);  // Aumentado de 0.1 a 0.3
-    
+pub fn ice_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let ice_pattern = ((fragment.tex_coords.x * 15.0).cos() * (fragment.tex_coords.y * 15.0).sin()).abs();
     
     let brightness = 0.9 + ice_pattern * 0.2;  // Más claro
     
-    Color::from_float(
-        brightness * intensity * 1.4,  // +40% brillo (hielo refleja más)
-        brightness * intensity * 1.35,
-        brightness * intensity * 1.4,
-    )
+    let albedo = Vec3::new(1.4, 1.35, 1.4) * brightness;  // hielo refleja más
+    let material = Material::glossy(albedo, 0.6, 32.0, 0.3);
+    to_color(shade(&material, fragment, uniforms))
 }
 
-pub fn moon_shader(fragment: &Fragment) -> Color {
-    let light_dir = Vec3::new(1.0, 0.5, 0.5).normalize();
-    let intensity = fragment.normal.dot(&light_dir).max(0.3);  // Mejorado
-    
+pub fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let craters = ((fragment.tex_coords.x * 30.0).sin() * (fragment.tex_coords.y * 30.0).cos()).abs();
     let gray = 0.6 + craters * 0.3;  // Más claro
     
-    Color::from_float(
-        gray * intensity * 1.2,
-        gray * intensity * 1.2,
-        gray * intensity * 1.2,
-    )
+    let albedo = Vec3::new(gray, gray, gray) * 1.2;
+    to_color(shade(&Material::matte(albedo), fragment, uniforms))
+}
+
+// Roca gris oscura y rugosa para asteroides
+pub fn asteroid_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let grain = ((fragment.tex_coords.x * 40.0).sin() * (fragment.tex_coords.y * 40.0).cos()).abs();
+    let gray = 0.4 + grain * 0.15;
+
+    let albedo = Vec3::new(gray, gray * 0.95, gray * 0.9);
+    to_color(shade(&Material::matte(albedo), fragment, uniforms))
+}
+
+// Casco metálico pulido
+pub fn spacecraft_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let material = Material {
+        albedo: Vec3::new(0.75, 0.78, 0.82),
+        ambient: 0.2,
+        specular: 0.8,
+        shininess: 48.0,
+        roughness: 0.35,
+        metallic: 0.7,
+    };
+    to_color(shade(&material, fragment, uniforms))
+}
+
+// Nombres que se usan en los archivos de escena
+pub fn shader_by_name(name: &str) -> Option<FragmentShader> {
+    let shader: FragmentShader = match name {
+        "sun" => sun_shader,
+        "rocky" => rocky_planet_shader,
+        "gas" => gas_planet_shader,
+        "earth" => earth_shader,
+        "red" => red_planet_shader,
+        "ice" => ice_planet_shader,
+        "moon" => moon_shader,
+        "asteroid" => asteroid_shader,
+        "spacecraft" => spacecraft_shader,
+        _ => return None,
+    };
+    Some(shader)
 }
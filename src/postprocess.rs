@@ -0,0 +1,555 @@
+use crate::framebuffer::{Color, Framebuffer};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+// Datos por cuadro disponibles para las pasadas
+pub struct PostContext {
+    pub frame: u64,
+}
+
+// Pasada de post-proceso sobre el buffer de color ya en rango [0, 1] (después del tone mapping).
+// Tiene acceso también al z-buffer del framebuffer.
+pub trait PostPass {
+    fn name(&self) -> &'static str;
+    fn apply(&mut self, framebuffer: &mut Framebuffer, context: &PostContext);
+}
+
+struct PostEntry {
+    pass: Box<dyn PostPass>,
+    enabled: bool,
+}
+
+// Cadena ordenada de pasadas que se pueden activar y reordenar en tiempo de ejecución
+pub struct PostChain {
+    entries: Vec<PostEntry>,
+    pub selected: usize,
+}
+
+pub const PASS_NAMES: [&str; 6] = ["lut", "chromatic", "sharpen", "fxaa", "vignette", "grain"];
+
+impl PostChain {
+    pub fn new() -> Self {
+        PostChain {
+            entries: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    // Todas las pasadas en su orden por defecto; solo FXAA y viñeta activas
+    pub fn default_chain() -> Self {
+        let mut chain = PostChain::new();
+        for name in PASS_NAMES {
+            let pass = create_pass(name, None).expect("pasada por defecto inválida");
+            chain.push(pass, name == "fxaa" || name == "vignette");
+        }
+        chain
+    }
+
+    // Construye la cadena a partir de "nombre[=valor],..." en el orden dado.
+    // Las pasadas no mencionadas se agregan al final desactivadas.
+    pub fn from_spec(spec: &str) -> Result<Self, String> {
+        let mut chain = PostChain::new();
+        for item in spec.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            let (name, value) = match item.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim())),
+                None => (item, None),
+            };
+            if chain.entries.iter().any(|e| e.pass.name() == name) {
+                return Err(format!("pasada repetida: {}", name));
+            }
+            chain.push(create_pass(name, value)?, true);
+        }
+        for name in PASS_NAMES {
+            if !chain.entries.iter().any(|e| e.pass.name() == name) {
+                chain.push(create_pass(name, None)?, false);
+            }
+        }
+        Ok(chain)
+    }
+
+    pub fn push(&mut self, pass: Box<dyn PostPass>, enabled: bool) {
+        self.entries.push(PostEntry { pass, enabled });
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected = (self.selected + 1) % self.entries.len();
+        }
+    }
+
+    pub fn toggle_selected(&mut self) {
+        if let Some(entry) = self.entries.get_mut(self.selected) {
+            entry.enabled = !entry.enabled;
+        }
+    }
+
+    // Mueve la pasada seleccionada una posición antes (-1) o después (+1)
+    pub fn move_selected(&mut self, direction: i32) {
+        let target = self.selected as i32 + direction;
+        if target < 0 || target >= self.entries.len() as i32 {
+            return;
+        }
+        self.entries.swap(self.selected, target as usize);
+        self.selected = target as usize;
+    }
+
+    pub fn apply(&mut self, framebuffer: &mut Framebuffer, context: &PostContext) {
+        for entry in self.entries.iter_mut().filter(|e| e.enabled) {
+            entry.pass.apply(framebuffer, context);
+        }
+    }
+
+    // Una línea por pasada: "> 1. [x] fxaa"
+    pub fn describe(&self) -> String {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                format!(
+                    "{} {}. [{}] {}",
+                    if i == self.selected { ">" } else { " " },
+                    i + 1,
+                    if entry.enabled { "x" } else { " " },
+                    entry.pass.name()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn parse_strength(name: &str, value: Option<&str>, default: f32) -> Result<f32, String> {
+    match value {
+        None => Ok(default),
+        Some(v) => v
+            .parse::<f32>()
+            .map_err(|_| format!("valor inválido para {}: {}", name, v)),
+    }
+}
+
+// Crea una pasada por nombre. `value` es la intensidad, o para "lut" un preset/archivo .cube
+pub fn create_pass(name: &str, value: Option<&str>) -> Result<Box<dyn PostPass>, String> {
+    let pass: Box<dyn PostPass> = match name {
+        "vignette" => Box::new(Vignette { strength: parse_strength(name, value, 0.35)? }),
+        "grain" => Box::new(FilmGrain { strength: parse_strength(name, value, 0.06)? }),
+        "chromatic" => Box::new(ChromaticAberration { strength: parse_strength(name, value, 2.0)? }),
+        "sharpen" => Box::new(Sharpen { strength: parse_strength(name, value, 0.4)? }),
+        "fxaa" => Box::new(Fxaa),
+        "lut" => {
+            let lut = match value {
+                None | Some("warm") => Lut3D::warm(),
+                Some("cool") => Lut3D::cool(),
+                Some(path) => Lut3D::from_cube_file(path)?,
+            };
+            Box::new(ColorGrade { lut })
+        }
+        _ => return Err(format!("pasada desconocida: {} (opciones: {})", name, PASS_NAMES.join(", "))),
+    };
+    Ok(pass)
+}
+
+fn luma(c: &Color) -> f32 {
+    0.299 * c.r + 0.587 * c.g + 0.114 * c.b
+}
+
+fn fetch(colors: &[Color], width: usize, height: usize, x: i32, y: i32) -> Color {
+    let x = x.clamp(0, width as i32 - 1) as usize;
+    let y = y.clamp(0, height as i32 - 1) as usize;
+    colors[y * width + x]
+}
+
+// Muestreo bilineal en coordenadas de píxel (centro del píxel 0 en 0.5)
+fn sample(colors: &[Color], width: usize, height: usize, x: f32, y: f32) -> Color {
+    let fx = x - 0.5;
+    let fy = y - 0.5;
+    let x0 = fx.floor();
+    let y0 = fy.floor();
+    let (tx, ty) = (fx - x0, fy - y0);
+    let (x0, y0) = (x0 as i32, y0 as i32);
+
+    let top = fetch(colors, width, height, x0, y0).lerp(&fetch(colors, width, height, x0 + 1, y0), tx);
+    let bottom = fetch(colors, width, height, x0, y0 + 1).lerp(&fetch(colors, width, height, x0 + 1, y0 + 1), tx);
+    top.lerp(&bottom, ty)
+}
+
+pub struct Vignette {
+    pub strength: f32,
+}
+
+impl PostPass for Vignette {
+    fn name(&self) -> &'static str {
+        "vignette"
+    }
+
+    fn apply(&mut self, framebuffer: &mut Framebuffer, _context: &PostContext) {
+        let cx = framebuffer.width as f32 * 0.5;
+        let cy = framebuffer.height as f32 * 0.5;
+        let max_distance = (cx * cx + cy * cy).sqrt();
+
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                let dx = x as f32 + 0.5 - cx;
+                let dy = y as f32 + 0.5 - cy;
+                let d = (dx * dx + dy * dy).sqrt() / max_distance;
+                let factor = 1.0 - self.strength * d * d;
+                let c = &mut framebuffer.color_buffer[y * framebuffer.width + x];
+                *c = Color::from_float(c.r * factor, c.g * factor, c.b * factor);
+            }
+        }
+    }
+}
+
+pub struct FilmGrain {
+    pub strength: f32,
+}
+
+impl PostPass for FilmGrain {
+    fn name(&self) -> &'static str {
+        "grain"
+    }
+
+    fn apply(&mut self, framebuffer: &mut Framebuffer, context: &PostContext) {
+        let seed = context.frame.wrapping_mul(0x9E37_79B9) as u32;
+        for (i, c) in framebuffer.color_buffer.iter_mut().enumerate() {
+            // Hash entero barato: ruido distinto por píxel y por cuadro
+            let mut h = (i as u32) ^ seed;
+            h = h.wrapping_mul(0x85EB_CA6B);
+            h ^= h >> 13;
+            h = h.wrapping_mul(0xC2B2_AE35);
+            h ^= h >> 16;
+            let noise = (h & 0xFFFF) as f32 / 65535.0 - 0.5;
+
+            // El grano se nota más en los tonos medios
+            let l = luma(c);
+            let amount = noise * self.strength * (1.0 - (2.0 * l - 1.0).abs() * 0.5);
+            *c = Color::from_float(c.r + amount, c.g + amount, c.b + amount);
+        }
+    }
+}
+
+pub struct ChromaticAberration {
+    // Desplazamiento máximo en píxeles en las esquinas
+    pub strength: f32,
+}
+
+impl PostPass for ChromaticAberration {
+    fn name(&self) -> &'static str {
+        "chromatic"
+    }
+
+    fn apply(&mut self, framebuffer: &mut Framebuffer, _context: &PostContext) {
+        let (w, h) = (framebuffer.width, framebuffer.height);
+        let source = framebuffer.color_buffer.clone();
+        let cx = w as f32 * 0.5;
+        let cy = h as f32 * 0.5;
+        let max_distance = (cx * cx + cy * cy).sqrt();
+
+        for y in 0..h {
+            for x in 0..w {
+                let px = x as f32 + 0.5;
+                let py = y as f32 + 0.5;
+                let dx = (px - cx) / max_distance;
+                let dy = (py - cy) / max_distance;
+                let ox = dx * self.strength;
+                let oy = dy * self.strength;
+
+                let red = sample(&source, w, h, px + ox, py + oy).r;
+                let green = source[y * w + x].g;
+                let blue = sample(&source, w, h, px - ox, py - oy).b;
+                framebuffer.color_buffer[y * w + x] = Color::from_float(red, green, blue);
+            }
+        }
+    }
+}
+
+pub struct Sharpen {
+    pub strength: f32,
+}
+
+impl PostPass for Sharpen {
+    fn name(&self) -> &'static str {
+        "sharpen"
+    }
+
+    fn apply(&mut self, framebuffer: &mut Framebuffer, _context: &PostContext) {
+        let (w, h) = (framebuffer.width, framebuffer.height);
+        let source = framebuffer.color_buffer.clone();
+
+        for y in 0..h {
+            for x in 0..w {
+                let (xi, yi) = (x as i32, y as i32);
+                let center = source[y * w + x];
+                let mut blur = Color::from_float(0.0, 0.0, 0.0);
+                for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    let n = fetch(&source, w, h, xi + dx, yi + dy);
+                    blur = Color::from_float(blur.r + n.r * 0.25, blur.g + n.g * 0.25, blur.b + n.b * 0.25);
+                }
+                // Máscara de enfoque: centro + k * (centro - promedio de vecinos)
+                let k = self.strength;
+                framebuffer.color_buffer[y * w + x] = Color::from_float(
+                    (center.r + k * (center.r - blur.r)).min(1.0),
+                    (center.g + k * (center.g - blur.g)).min(1.0),
+                    (center.b + k * (center.b - blur.b)).min(1.0),
+                );
+            }
+        }
+    }
+}
+
+// FXAA "consola": busca la dirección del borde con la luma y promedia a lo largo de ella
+pub struct Fxaa;
+
+impl PostPass for Fxaa {
+    fn name(&self) -> &'static str {
+        "fxaa"
+    }
+
+    fn apply(&mut self, framebuffer: &mut Framebuffer, _context: &PostContext) {
+        const REDUCE_MIN: f32 = 1.0 / 128.0;
+        const REDUCE_MUL: f32 = 1.0 / 8.0;
+        const SPAN_MAX: f32 = 8.0;
+
+        let (w, h) = (framebuffer.width, framebuffer.height);
+        let source = framebuffer.color_buffer.clone();
+
+        for y in 0..h {
+            for x in 0..w {
+                let (xi, yi) = (x as i32, y as i32);
+                let luma_nw = luma(&fetch(&source, w, h, xi - 1, yi - 1));
+                let luma_ne = luma(&fetch(&source, w, h, xi + 1, yi - 1));
+                let luma_sw = luma(&fetch(&source, w, h, xi - 1, yi + 1));
+                let luma_se = luma(&fetch(&source, w, h, xi + 1, yi + 1));
+                let luma_m = luma(&source[y * w + x]);
+
+                let luma_min = luma_m.min(luma_nw.min(luma_ne).min(luma_sw.min(luma_se)));
+                let luma_max = luma_m.max(luma_nw.max(luma_ne).max(luma_sw.max(luma_se)));
+                if luma_max - luma_min < 0.05 {
+                    continue;
+                }
+
+                let mut dir_x = -((luma_nw + luma_ne) - (luma_sw + luma_se));
+                let mut dir_y = (luma_nw + luma_sw) - (luma_ne + luma_se);
+                let reduce = ((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * REDUCE_MUL).max(REDUCE_MIN);
+                let rcp_min = 1.0 / (dir_x.abs().min(dir_y.abs()) + reduce);
+                dir_x = (dir_x * rcp_min).clamp(-SPAN_MAX, SPAN_MAX);
+                dir_y = (dir_y * rcp_min).clamp(-SPAN_MAX, SPAN_MAX);
+
+                let px = x as f32 + 0.5;
+                let py = y as f32 + 0.5;
+                let tap = |t: f32| sample(&source, w, h, px + dir_x * t, py + dir_y * t);
+
+                let a1 = tap(1.0 / 3.0 - 0.5);
+                let a2 = tap(2.0 / 3.0 - 0.5);
+                let rgb_a = Color::from_float((a1.r + a2.r) * 0.5, (a1.g + a2.g) * 0.5, (a1.b + a2.b) * 0.5);
+                let b1 = tap(-0.5);
+                let b2 = tap(0.5);
+                let rgb_b = Color::from_float(
+                    rgb_a.r * 0.5 + (b1.r + b2.r) * 0.25,
+                    rgb_a.g * 0.5 + (b1.g + b2.g) * 0.25,
+                    rgb_a.b * 0.5 + (b1.b + b2.b) * 0.25,
+                );
+
+                let luma_b = luma(&rgb_b);
+                framebuffer.color_buffer[y * w + x] = if luma_b < luma_min || luma_b > luma_max {
+                    rgb_a
+                } else {
+                    rgb_b
+                };
+            }
+        }
+    }
+}
+
+// Tabla 3D de corrección de color, indexada [b][g][r] como en el formato .cube
+pub struct Lut3D {
+    size: usize,
+    data: Vec<Color>,
+}
+
+impl Lut3D {
+    fn from_fn(size: usize, f: impl Fn(f32, f32, f32) -> Color) -> Self {
+        let mut data = Vec::with_capacity(size * size * size);
+        let max = (size - 1) as f32;
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    data.push(f(r as f32 / max, g as f32 / max, b as f32 / max));
+                }
+            }
+        }
+        Lut3D { size, data }
+    }
+
+    // Tonos cálidos y un poco más de contraste
+    pub fn warm() -> Self {
+        Lut3D::from_fn(16, |r, g, b| {
+            let contrast = |v: f32| (v - 0.5) * 1.1 + 0.5;
+            Color::from_float(contrast(r * 1.06).min(1.0), contrast(g * 1.0), contrast(b * 0.9))
+        })
+    }
+
+    // Sombras azuladas y altas luces ligeramente desaturadas
+    pub fn cool() -> Self {
+        Lut3D::from_fn(16, |r, g, b| {
+            let l = 0.299 * r + 0.587 * g + 0.114 * b;
+            let mix = |v: f32| v * 0.85 + l * 0.15;
+            Color::from_float(mix(r) * 0.92, mix(g) * 0.98, (mix(b) * 1.05 + (1.0 - l) * 0.04).min(1.0))
+        })
+    }
+
+    // Lee un archivo .cube (Adobe/Resolve) con LUT_3D_SIZE
+    pub fn from_cube_file(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("no se pudo abrir {}: {}", path, e))?;
+        let mut size = 0;
+        let mut data = Vec::new();
+
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| e.to_string())?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(rest) = line.strip_prefix("LUT_3D_SIZE") {
+                size = rest.trim().parse().map_err(|_| format!("LUT_3D_SIZE inválido en {}", path))?;
+                continue;
+            }
+            let values: Vec<f32> = line.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+            if values.len() == 3 && line.starts_with(|c: char| c.is_ascii_digit() || c == '.' || c == '-') {
+                data.push(Color::from_float(values[0], values[1], values[2]));
+            }
+        }
+
+        if size < 2 || data.len() != size * size * size {
+            return Err(format!("{} no es una LUT 3D válida", path));
+        }
+        Ok(Lut3D { size, data })
+    }
+
+    fn at(&self, r: usize, g: usize, b: usize) -> Color {
+        self.data[(b * self.size + g) * self.size + r]
+    }
+
+    // Interpolación trilineal
+    pub fn lookup(&self, color: &Color) -> Color {
+        let max = (self.size - 1) as f32;
+        let coords = [color.r, color.g, color.b].map(|v| v.clamp(0.0, 1.0) * max);
+        let lo = coords.map(|v| (v.floor() as usize).min(self.size - 2));
+        let t = [coords[0] - lo[0] as f32, coords[1] - lo[1] as f32, coords[2] - lo[2] as f32];
+
+        let corner = |dr: usize, dg: usize, db: usize| self.at(lo[0] + dr, lo[1] + dg, lo[2] + db);
+        let c00 = corner(0, 0, 0).lerp(&corner(1, 0, 0), t[0]);
+        let c10 = corner(0, 1, 0).lerp(&corner(1, 1, 0), t[0]);
+        let c01 = corner(0, 0, 1).lerp(&corner(1, 0, 1), t[0]);
+        let c11 = corner(0, 1, 1).lerp(&corner(1, 1, 1), t[0]);
+        let c0 = c00.lerp(&c10, t[1]);
+        let c1 = c01.lerp(&c11, t[1]);
+        c0.lerp(&c1, t[2])
+    }
+}
+
+pub struct ColorGrade {
+    pub lut: Lut3D,
+}
+
+impl PostPass for ColorGrade {
+    fn name(&self) -> &'static str {
+        "lut"
+    }
+
+    fn apply(&mut self, framebuffer: &mut Framebuffer, _context: &PostContext) {
+        for c in framebuffer.color_buffer.iter_mut() {
+            *c = self.lut.lookup(c);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(chain: &PostChain) -> Vec<&'static str> {
+        chain.entries.iter().map(|e| e.pass.name()).collect()
+    }
+
+    fn enabled(chain: &PostChain) -> Vec<&'static str> {
+        chain.entries.iter().filter(|e| e.enabled).map(|e| e.pass.name()).collect()
+    }
+
+    fn spec_error(spec: &str) -> String {
+        PostChain::from_spec(spec).err().expect("la especificación debería fallar")
+    }
+
+    // Escribe `contents` en un archivo .cube temporal, lo carga y lo borra
+    fn load_cube(name: &str, contents: &str) -> Result<Lut3D, String> {
+        let path = std::env::temp_dir().join(format!("{}_{}.cube", name, std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        let lut = Lut3D::from_cube_file(path.to_str().unwrap());
+        let _ = std::fs::remove_file(path);
+        lut
+    }
+
+    #[test]
+    fn spec_sets_order_and_enabled_passes() {
+        let chain = PostChain::from_spec("grain=0.1, fxaa,vignette").unwrap();
+        assert_eq!(names(&chain), ["grain", "fxaa", "vignette", "lut", "chromatic", "sharpen"]);
+        assert_eq!(enabled(&chain), ["grain", "fxaa", "vignette"]);
+        assert_eq!(chain.entries.len(), PASS_NAMES.len());
+        // Vacía: todas desactivadas en el orden por defecto
+        let empty = PostChain::from_spec("").unwrap();
+        assert_eq!(names(&empty), PASS_NAMES);
+        assert!(enabled(&empty).is_empty());
+    }
+
+    #[test]
+    fn spec_rejects_unknown_repeated_and_invalid() {
+        assert!(spec_error("fxaa,bokeh").contains("desconocida: bokeh"));
+        assert!(spec_error("fxaa,fxaa").contains("repetida"));
+        assert!(spec_error("vignette=mucho").contains("inválido"));
+    }
+
+    #[test]
+    fn selected_pass_moves_within_bounds() {
+        let mut chain = PostChain::from_spec("lut,chromatic,sharpen").unwrap();
+        chain.move_selected(-1);
+        assert_eq!(names(&chain)[..3], ["lut", "chromatic", "sharpen"]);
+        assert_eq!(chain.selected, 0);
+
+        chain.move_selected(1);
+        assert_eq!(names(&chain)[..3], ["chromatic", "lut", "sharpen"]);
+        assert_eq!(chain.selected, 1);
+
+        chain.selected = chain.entries.len() - 1;
+        chain.move_selected(1);
+        assert_eq!(chain.selected, chain.entries.len() - 1);
+        chain.move_selected(-1);
+        assert_eq!(names(&chain)[chain.entries.len() - 2..], ["grain", "vignette"]);
+        assert!(chain.describe().lines().nth(chain.entries.len() - 2).unwrap().starts_with(">"));
+    }
+
+    #[test]
+    fn cube_file_loads_and_interpolates() {
+        // LUT 2x2x2 que invierte los colores; el rojo varía más rápido
+        let mut cube = String::from("# invertir\nTITLE \"invertir\"\nLUT_3D_SIZE 2\n\n");
+        for b in [1.0, 0.0] {
+            for g in [1.0, 0.0] {
+                for r in [1.0, 0.0] {
+                    cube.push_str(&format!("{} {} {}\n", r, g, b));
+                }
+            }
+        }
+        let lut = load_cube("lut_valida", &cube).unwrap();
+        let graded = lut.lookup(&Color::from_float(0.25, 0.5, 1.0));
+        assert!((graded.r - 0.75).abs() < 1e-5);
+        assert!((graded.g - 0.5).abs() < 1e-5);
+        assert!(graded.b.abs() < 1e-5);
+    }
+
+    #[test]
+    fn malformed_cube_file_is_rejected() {
+        assert!(load_cube("lut_sin_tamano", "0 0 0\n1 1 1\n").is_err());
+        assert!(load_cube("lut_tamano_invalido", "LUT_3D_SIZE dos\n").err().unwrap().contains("LUT_3D_SIZE"));
+        // Faltan entradas para una tabla de 2x2x2
+        assert!(load_cube("lut_incompleta", "LUT_3D_SIZE 2\n0 0 0\n1 0 0\n0 1 0\n").is_err());
+        assert!(Lut3D::from_cube_file("/no/existe.cube").err().unwrap().contains("no se pudo abrir"));
+    }
+}
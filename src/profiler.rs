@@ -0,0 +1,422 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+// Cuadros que guarda la ventana para exportar: un minuto a 60 FPS
+pub const PROFILE_HISTORY: usize = 3600;
+
+// Partes de un cuadro que se miden por separado
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    ShadowMap,
+    Clear,
+    Skybox,
+    Orbits,
+    Vertex,
+    Raster,
+    // MSAA, SSAA y vistas de depuración
+    Resolve,
+    PostProcess,
+    Upscale,
+    Hud,
+    // Mostrar en la ventana o guardar el archivo sin ventana
+    Present,
+}
+
+pub const STAGE_COUNT: usize = 11;
+
+impl Stage {
+    pub const ALL: [Stage; STAGE_COUNT] = [
+        Stage::ShadowMap,
+        Stage::Clear,
+        Stage::Skybox,
+        Stage::Orbits,
+        Stage::Vertex,
+        Stage::Raster,
+        Stage::Resolve,
+        Stage::PostProcess,
+        Stage::Upscale,
+        Stage::Hud,
+        Stage::Present,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::ShadowMap => "sombras",
+            Stage::Clear => "limpiar",
+            Stage::Skybox => "skybox",
+            Stage::Orbits => "órbitas",
+            Stage::Vertex => "vértices",
+            Stage::Raster => "rasterizado",
+            Stage::Resolve => "resolver",
+            Stage::PostProcess => "post-proceso",
+            Stage::Upscale => "escalado",
+            Stage::Hud => "hud",
+            Stage::Present => "presentar",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+// Trabajo del rasterizador en un cuadro; lo va sumando el framebuffer en el que se dibuja
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameCounters {
+    pub triangles_submitted: u64,
+    // Cruzan el plano de la cámara o quedan fuera de la pantalla
+    pub triangles_culled: u64,
+    pub triangles_drawn: u64,
+    pub fragments_shaded: u64,
+    // Fragmentos sombreados que después perdieron contra el z-buffer
+    pub depth_rejects: u64,
+}
+
+impl FrameCounters {
+    pub const NAMES: [&'static str; 5] = [
+        "triangulos_enviados",
+        "triangulos_descartados",
+        "triangulos_dibujados",
+        "fragmentos_sombreados",
+        "rechazos_profundidad",
+    ];
+
+    pub fn values(&self) -> [u64; 5] {
+        [
+            self.triangles_submitted,
+            self.triangles_culled,
+            self.triangles_drawn,
+            self.fragments_shaded,
+            self.depth_rejects,
+        ]
+    }
+}
+
+// Un tramo medido, relativo al arranque del profiler
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    stage: Stage,
+    start: Duration,
+    duration: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct FrameProfile {
+    pub index: u64,
+    pub start: Duration,
+    pub total: Duration,
+    pub stages: [Duration; STAGE_COUNT],
+    pub counters: FrameCounters,
+    spans: Vec<Span>,
+}
+
+impl FrameProfile {
+    fn new(index: u64, start: Duration) -> Self {
+        FrameProfile {
+            index,
+            start,
+            total: Duration::ZERO,
+            stages: [Duration::ZERO; STAGE_COUNT],
+            counters: FrameCounters::default(),
+            spans: Vec::new(),
+        }
+    }
+}
+
+// Promedio de los últimos cuadros, en milisegundos, para mostrar en pantalla
+pub struct ProfileSummary {
+    pub frames: usize,
+    pub total_ms: f32,
+    pub stage_ms: [f32; STAGE_COUNT],
+    pub counters: FrameCounters,
+    // Duración de cada uno de esos cuadros, del más viejo al más nuevo
+    pub frame_ms: Vec<f32>,
+}
+
+// Tiempos por etapa y contadores de cada cuadro, con un historial para exportar
+pub struct Profiler {
+    origin: Instant,
+    frame_start: Instant,
+    current: Option<FrameProfile>,
+    history: VecDeque<FrameProfile>,
+    capacity: usize,
+    next_index: u64,
+}
+
+impl Profiler {
+    pub fn new(capacity: usize) -> Self {
+        let now = Instant::now();
+        Profiler {
+            origin: now,
+            frame_start: now,
+            current: None,
+            history: VecDeque::new(),
+            capacity: capacity.max(1),
+            next_index: 0,
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.history.len() > self.capacity {
+            self.history.pop_front();
+        }
+    }
+
+    // Empieza un cuadro nuevo; si el anterior quedó abierto, lo cierra
+    pub fn begin_frame(&mut self) {
+        self.end_frame();
+        self.frame_start = Instant::now();
+        self.current = Some(FrameProfile::new(self.next_index, self.frame_start - self.origin));
+        self.next_index += 1;
+    }
+
+    pub fn end_frame(&mut self) {
+        if let Some(mut frame) = self.current.take() {
+            frame.total = self.frame_start.elapsed();
+            if self.history.len() == self.capacity {
+                self.history.pop_front();
+            }
+            self.history.push_back(frame);
+        }
+    }
+
+    // Suma al cuadro actual el tiempo desde `start` hasta ahora
+    pub fn record(&mut self, stage: Stage, start: Instant) {
+        let end = Instant::now();
+        if let Some(frame) = self.current.as_mut() {
+            let duration = end - start;
+            frame.stages[stage.index()] += duration;
+            frame.spans.push(Span { stage, start: start - self.origin, duration });
+        }
+    }
+
+    pub fn set_counters(&mut self, counters: FrameCounters) {
+        if let Some(frame) = self.current.as_mut() {
+            frame.counters = counters;
+        }
+    }
+
+    pub fn frames(&self) -> impl Iterator<Item = &FrameProfile> {
+        self.history.iter()
+    }
+
+    pub fn last_frame(&self) -> Option<&FrameProfile> {
+        self.history.back()
+    }
+
+    // Promedio de los últimos `count` cuadros terminados
+    pub fn summary(&self, count: usize) -> Option<ProfileSummary> {
+        let frames: Vec<&FrameProfile> = self.history.iter().rev().take(count).collect();
+        if frames.is_empty() {
+            return None;
+        }
+        let n = frames.len();
+        let average_ms = |total: Duration| total.as_secs_f32() * 1000.0 / n as f32;
+
+        let mut stage_ms = [0.0; STAGE_COUNT];
+        for (i, ms) in stage_ms.iter_mut().enumerate() {
+            *ms = average_ms(frames.iter().map(|f| f.stages[i]).sum());
+        }
+        let average = |value: fn(&FrameCounters) -> u64| frames.iter().map(|f| value(&f.counters)).sum::<u64>() / n as u64;
+
+        Some(ProfileSummary {
+            frames: n,
+            total_ms: average_ms(frames.iter().map(|f| f.total).sum()),
+            stage_ms,
+            counters: FrameCounters {
+                triangles_submitted: average(|c| c.triangles_submitted),
+                triangles_culled: average(|c| c.triangles_culled),
+                triangles_drawn: average(|c| c.triangles_drawn),
+                fragments_shaded: average(|c| c.fragments_shaded),
+                depth_rejects: average(|c| c.depth_rejects),
+            },
+            frame_ms: frames.iter().rev().map(|f| f.total.as_secs_f32() * 1000.0).collect(),
+        })
+    }
+
+    // `.csv` o `.json` (formato de trazas de Chrome, para chrome://tracing o Perfetto)
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+            Some("csv") => self.write_csv(path),
+            Some("json") => self.write_chrome_trace(path),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("formato de perfil no soportado: {} (usa .csv o .json)", path.display()),
+            )),
+        }
+    }
+
+    // Una fila por cuadro: tiempos en milisegundos y contadores
+    pub fn write_csv(&self, path: &Path) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+
+        let mut header = vec!["cuadro".to_string(), "total_ms".to_string()];
+        header.extend(Stage::ALL.iter().map(|stage| format!("{}_ms", stage.name())));
+        header.extend(FrameCounters::NAMES.iter().map(|name| name.to_string()));
+        writeln!(out, "{}", header.join(","))?;
+
+        for frame in &self.history {
+            let mut row = vec![frame.index.to_string(), format_ms(frame.total)];
+            row.extend(frame.stages.iter().map(|&d| format_ms(d)));
+            row.extend(frame.counters.values().iter().map(|v| v.to_string()));
+            writeln!(out, "{}", row.join(","))?;
+        }
+        out.flush()
+    }
+
+    // Un evento por cuadro con sus etapas anidadas y los contadores como gráficos
+    pub fn write_chrome_trace(&self, path: &Path) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        let mut events = Vec::new();
+
+        for frame in &self.history {
+            events.push(format!(
+                r#"{{"name":"cuadro {}","cat":"cuadro","ph":"X","ts":{},"dur":{},"pid":1,"tid":1}}"#,
+                frame.index,
+                format_us(frame.start),
+                format_us(frame.total)
+            ));
+            for span in &frame.spans {
+                events.push(format!(
+                    r#"{{"name":"{}","cat":"etapa","ph":"X","ts":{},"dur":{},"pid":1,"tid":1}}"#,
+                    span.stage.name(),
+                    format_us(span.start),
+                    format_us(span.duration)
+                ));
+            }
+            let args: Vec<String> = FrameCounters::NAMES
+                .iter()
+                .zip(frame.counters.values())
+                .map(|(name, value)| format!(r#""{}":{}"#, name, value))
+                .collect();
+            events.push(format!(
+                r#"{{"name":"contadores","ph":"C","ts":{},"pid":1,"args":{{{}}}}}"#,
+                format_us(frame.start),
+                args.join(",")
+            ));
+        }
+
+        writeln!(out, "{{\"traceEvents\":[")?;
+        for (i, event) in events.iter().enumerate() {
+            let separator = if i + 1 < events.len() { "," } else { "" };
+            writeln!(out, "{}{}", event, separator)?;
+        }
+        writeln!(out, "],\"displayTimeUnit\":\"ms\"}}")?;
+        out.flush()
+    }
+}
+
+fn format_ms(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64() * 1000.0)
+}
+
+// Las trazas de Chrome usan microsegundos
+fn format_us(duration: Duration) -> String {
+    format!("{:.1}", duration.as_secs_f64() * 1_000_000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cuadro terminado con tiempos fijos, para no depender del reloj
+    fn frame(index: u64, total_ms: u64, raster_ms: u64, fragments: u64) -> FrameProfile {
+        let mut frame = FrameProfile::new(index, Duration::from_millis(index * 20));
+        frame.total = Duration::from_millis(total_ms);
+        frame.stages[Stage::Raster.index()] = Duration::from_millis(raster_ms);
+        frame.counters.fragments_shaded = fragments;
+        frame
+    }
+
+    fn profiler(frames: &[FrameProfile]) -> Profiler {
+        let mut profiler = Profiler::new(PROFILE_HISTORY);
+        profiler.history.extend(frames.iter().cloned());
+        profiler
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn summary_averages_the_latest_frames() {
+        let profiler = profiler(&[frame(0, 100, 90, 1000), frame(1, 10, 4, 300), frame(2, 20, 8, 500)]);
+
+        // Solo los dos más nuevos
+        let summary = profiler.summary(2).unwrap();
+        assert_eq!(summary.frames, 2);
+        assert!((summary.total_ms - 15.0).abs() < 1e-4);
+        assert!((summary.stage_ms[Stage::Raster.index()] - 6.0).abs() < 1e-4);
+        assert_eq!(summary.stage_ms[Stage::Hud.index()], 0.0);
+        assert_eq!(summary.counters.fragments_shaded, 400);
+        assert_eq!(summary.frame_ms, [10.0, 20.0]);
+
+        // Pedir más de los que hay usa todos
+        assert_eq!(profiler.summary(10).unwrap().frames, 3);
+        assert!(Profiler::new(10).summary(5).is_none());
+    }
+
+    #[test]
+    fn history_respects_capacity() {
+        let mut profiler = Profiler::new(3);
+        for _ in 0..5 {
+            profiler.begin_frame();
+        }
+        profiler.end_frame();
+        let indices: Vec<u64> = profiler.frames().map(|f| f.index).collect();
+        assert_eq!(indices, [2, 3, 4]);
+
+        // Achicarla descarta los más viejos; agrandarla conserva lo que hay
+        profiler.set_capacity(2);
+        assert_eq!(profiler.frames().map(|f| f.index).collect::<Vec<_>>(), [3, 4]);
+        profiler.set_capacity(10);
+        profiler.begin_frame();
+        profiler.end_frame();
+        assert_eq!(profiler.frames().count(), 3);
+        assert_eq!(profiler.last_frame().unwrap().index, 5);
+    }
+
+    #[test]
+    fn csv_has_header_and_one_row_per_frame() {
+        let profiler = profiler(&[frame(7, 12, 5, 250), frame(8, 14, 6, 260)]);
+        let path = temp_path("perfil.csv");
+        profiler.save(&path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let lines: Vec<Vec<&str>> = text.lines().map(|line| line.split(',').collect()).collect();
+        assert_eq!(lines.len(), 3);
+        let header = &lines[0];
+        assert_eq!(header.len(), 2 + STAGE_COUNT + FrameCounters::NAMES.len());
+        assert_eq!(header[..2], ["cuadro", "total_ms"]);
+        assert_eq!(header[2 + Stage::Raster.index()], "rasterizado_ms");
+        assert_eq!(header[2 + STAGE_COUNT + 3], "fragmentos_sombreados");
+
+        let row = &lines[1];
+        assert_eq!(row.len(), header.len());
+        assert_eq!(row[..2], ["7", "12.000"]);
+        assert_eq!(row[2 + Stage::Raster.index()], "5.000");
+        assert_eq!(row[2 + STAGE_COUNT + 3], "250");
+        assert_eq!(lines[2][0], "8");
+    }
+
+    #[test]
+    fn save_rejects_unknown_extensions() {
+        let profiler = profiler(&[frame(0, 10, 5, 0)]);
+        for name in ["perfil.txt", "perfil"] {
+            let path = temp_path(name);
+            let error = profiler.save(&path).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+            assert!(!path.exists());
+        }
+        // La extensión no distingue mayúsculas
+        let path = temp_path("perfil.JSON");
+        profiler.save(&path).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().starts_with("{\"traceEvents\":["));
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,490 @@
+use crate::framebuffer::{Framebuffer, Color};
+use crate::math::Vec3;
+use crate::shaders::{
+    Uniforms,
+    vertex_shader,
+    FragmentShader,
+    create_model_matrix,
+    create_perspective_matrix,
+    create_viewport_matrix,
+    project_point,
+};
+use crate::vertex::Vertex;
+use crate::triangle::triangle;
+use crate::obj::Obj;
+use crate::camera::Camera;
+use crate::lighting::LightingModel;
+use crate::shadow_map::ShadowMap;
+use crate::tonemap::ToneMapper;
+use crate::bloom::{Bloom, LensFlare, ScreenSun};
+use crate::postprocess::{PostChain, PostContext};
+use crate::antialiasing::AntiAliasing;
+use crate::upscale::{upscale, UpscaleFilter, DynamicResolution};
+use crate::scene::{Scene, CelestialBody};
+use crate::debug_view::{DebugOptions, DebugView, apply_depth_view, apply_overdraw_view, draw_wireframe, draw_gizmos, draw_rotation_axis};
+use crate::math::Mat4;
+use crate::profiler::{Profiler, Stage, PROFILE_HISTORY};
+use std::f32::consts::PI;
+use std::time::Instant;
+
+// Rejilla fija sobre la que se reparten las estrellas del fondo
+const STAR_FIELD_WIDTH: usize = 800;
+const STAR_FIELD_HEIGHT: usize = 600;
+
+const SHADOW_MAP_RESOLUTION: usize = 256;
+
+pub const FIELD_OF_VIEW: f32 = PI / 3.0;
+pub const NEAR_PLANE: f32 = 0.1;
+pub const FAR_PLANE: f32 = 100.0;
+
+// Todo lo necesario para convertir la escena en una imagen del tamaño de salida
+pub struct Renderer {
+    pub framebuffer: Framebuffer,
+    // Con SSAA la geometría se dibuja aquí y luego se reduce
+    ssaa_framebuffer: Option<Framebuffer>,
+    pub anti_aliasing: AntiAliasing,
+    // Imagen final escalada al tamaño de la ventana, donde se dibuja el HUD
+    pub output: Framebuffer,
+    pub output_size: (usize, usize),
+    pub render_scale: f32,
+    pub upscale_filter: UpscaleFilter,
+    pub dynamic_resolution: DynamicResolution,
+    pub lighting_model: LightingModel,
+    pub tone_mapper: ToneMapper,
+    pub bloom: Bloom,
+    pub lens_flare: LensFlare,
+    pub post_chain: PostChain,
+    pub debug: DebugOptions,
+    pub profiler: Profiler,
+    // Tangentes por malla para los gizmos; se calculan la primera vez que se piden
+    tangents: Vec<Vec<Vec3>>,
+    // Profundidad desde el Sol; se presta a los uniforms durante cada cuadro
+    shadow_map: Option<ShadowMap>,
+    frame: u64,
+}
+
+impl Renderer {
+    pub fn new(output_size: (usize, usize), render_scale: f32, anti_aliasing: AntiAliasing, post_chain: PostChain) -> Self {
+        let (width, height) = render_size(output_size, render_scale);
+        let (framebuffer, ssaa_framebuffer) = create_render_targets(width, height, anti_aliasing);
+
+        Renderer {
+            framebuffer,
+            ssaa_framebuffer,
+            anti_aliasing,
+            output: Framebuffer::new_overlay(output_size.0, output_size.1),
+            output_size,
+            render_scale,
+            upscale_filter: UpscaleFilter::Bilinear,
+            dynamic_resolution: DynamicResolution::new(0.016),
+            lighting_model: LightingModel::BlinnPhong,
+            tone_mapper: ToneMapper::new(),
+            bloom: Bloom::new(),
+            lens_flare: LensFlare::new(),
+            post_chain,
+            debug: DebugOptions::new(),
+            profiler: Profiler::new(PROFILE_HISTORY),
+            tangents: Vec::new(),
+            shadow_map: Some(ShadowMap::new(SHADOW_MAP_RESOLUTION)),
+            frame: 0,
+        }
+    }
+
+    // Seguir el tamaño de la ventana: buffer de salida, framebuffer, aspecto y viewport
+    pub fn resize(&mut self, output_size: (usize, usize)) {
+        if output_size == self.output_size {
+            return;
+        }
+        self.output_size = output_size;
+        self.output = Framebuffer::new_overlay(output_size.0, output_size.1);
+        self.recreate_targets();
+    }
+
+    pub fn set_render_scale(&mut self, render_scale: f32) {
+        if render_scale != self.render_scale {
+            self.render_scale = render_scale;
+            self.recreate_targets();
+        }
+    }
+
+    fn recreate_targets(&mut self) {
+        let (width, height) = render_size(self.output_size, self.render_scale);
+        (self.framebuffer, self.ssaa_framebuffer) = create_render_targets(width, height, self.anti_aliasing);
+    }
+
+    // Centro y radio en píxeles de `output` de una esfera del mundo, para marcarla en el HUD.
+    // None si queda detrás de la cámara.
+    pub fn screen_circle(&self, camera: &Camera, center: Vec3, radius: f32) -> Option<(f32, f32, f32)> {
+        let (width, height) = self.output_size;
+        let mut uniforms = Uniforms::new();
+        uniforms.projection_matrix = create_perspective_matrix(FIELD_OF_VIEW, width as f32 / height as f32, NEAR_PLANE, FAR_PLANE);
+        uniforms.view_matrix = camera.get_view_matrix();
+        uniforms.viewport_matrix = create_viewport_matrix(width as f32, height as f32);
+
+        let screen = project_point(center, &uniforms)?;
+        let distance = (center - camera.eye).magnitude().max(radius);
+        let screen_radius = radius / (distance * (FIELD_OF_VIEW / 2.0).tan()) * height as f32 / 2.0;
+        Some((screen.x, screen.y, screen_radius))
+    }
+
+    // Dibuja un cuadro completo y lo deja escalado en `output`
+    pub fn render(&mut self, scene: &Scene, camera: &Camera, time: f32, delta_time: f32) {
+        let render_start = Instant::now();
+        self.profiler.begin_frame();
+        let sun = &scene.sun;
+        let debug = self.debug;
+        if debug.gizmos && self.tangents.len() != scene.meshes.len() {
+            self.tangents = scene.meshes.iter().map(Obj::tangents).collect();
+        }
+
+        // Setup uniforms
+        let mut uniforms = Uniforms::new();
+
+        uniforms.projection_matrix = create_perspective_matrix(
+            FIELD_OF_VIEW,
+            self.framebuffer.width as f32 / self.framebuffer.height as f32,
+            NEAR_PLANE,
+            FAR_PLANE
+        );
+
+        uniforms.view_matrix = camera.get_view_matrix();
+
+        uniforms.time = time;
+        uniforms.camera_position = camera.eye;
+        uniforms.light_position = sun.position;
+        uniforms.lighting_model = self.lighting_model;
+        uniforms.sun_radius = sun.radius();
+        uniforms.occluders = scene.occluders();
+
+        // Pasada de profundidad desde el Sol para las mallas arbitrarias
+        let stage_start = Instant::now();
+        let mut shadow_map = self.shadow_map.take().unwrap_or_else(|| ShadowMap::new(SHADOW_MAP_RESOLUTION));
+        shadow_map.begin(sun.position);
+        for prop in &scene.props {
+            let mesh = &scene.meshes[prop.mesh];
+            shadow_map.add_caster(mesh, &model_matrix(prop), prop.position, mesh.bounding_radius() * prop.scale);
+        }
+        uniforms.shadow_map = Some(shadow_map);
+        self.profiler.record(Stage::ShadowMap, stage_start);
+
+        // Con SSAA la geometría se dibuja en un framebuffer más grande y luego se reduce
+        let target = match self.ssaa_framebuffer.as_mut() {
+            Some(hi_res) => hi_res,
+            None => &mut self.framebuffer,
+        };
+        uniforms.viewport_matrix = create_viewport_matrix(target.width as f32, target.height as f32);
+
+        let stage_start = Instant::now();
+        target.set_overdraw_tracking(debug.view == DebugView::Overdraw);
+        target.clear();
+        self.profiler.record(Stage::Clear, stage_start);
+
+        // Las vistas de depuración muestran solo la geometría
+        if debug.view == DebugView::Off {
+            // Renderizar skybox (estrellas de fondo)
+            let stage_start = Instant::now();
+            render_skybox(target);
+            self.profiler.record(Stage::Skybox, stage_start);
+
+            // Renderizar órbitas (todas siempre visibles)
+            let stage_start = Instant::now();
+            for planet in &scene.planets {
+                render_orbit(planet, scene.orbit_center(planet), target, &uniforms);
+            }
+            self.profiler.record(Stage::Orbits, stage_start);
+        }
+
+        // Renderizar el Sol, los planetas y después naves y asteroides
+        for (body, current_body) in drawables(scene) {
+            uniforms.current_body = current_body;
+            uniforms.model_matrix = model_matrix(body);
+            let shader = debug.view.shader().unwrap_or(body.shader);
+            render_obj_with_shader(&scene.meshes[body.mesh], target, &mut uniforms, shader, &mut self.profiler);
+        }
+        self.profiler.set_counters(target.counters);
+
+        let stage_start = Instant::now();
+        target.resolve_samples();
+
+        match debug.view {
+            DebugView::Depth => apply_depth_view(target, NEAR_PLANE, FAR_PLANE),
+            DebugView::Overdraw => apply_overdraw_view(target),
+            _ => {}
+        }
+
+        if debug.wireframe || debug.gizmos || debug.axes {
+            // Las líneas van sobre la imagen ya resuelta: sin MSAA escriben directo en color y z-buffer
+            let samples = target.samples;
+            target.samples = 1;
+            for (body, _) in drawables(scene) {
+                uniforms.model_matrix = model_matrix(body);
+                let mesh = &scene.meshes[body.mesh];
+                if debug.wireframe {
+                    draw_wireframe(mesh, target, &uniforms);
+                }
+                if debug.gizmos {
+                    draw_gizmos(mesh, &self.tangents[body.mesh], target, &uniforms, body.scale);
+                }
+            }
+            if debug.axes {
+                for body in std::iter::once(sun).chain(&scene.planets) {
+                    draw_rotation_axis(body, target, &uniforms);
+                }
+            }
+            target.samples = samples;
+        }
+
+        let framebuffer = &mut self.framebuffer;
+        if let Some(hi_res) = &self.ssaa_framebuffer {
+            framebuffer.downsample_from(hi_res, self.anti_aliasing.ssaa_factor());
+            uniforms.viewport_matrix = create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32);
+        }
+        self.profiler.record(Stage::Resolve, stage_start);
+
+        let stage_start = Instant::now();
+
+        // Las vistas de depuración ya están en [0, 1]: sin post-proceso que las altere
+        if debug.view != DebugView::Off {
+            framebuffer.resolve();
+        } else {
+            // Post-proceso HDR: bloom y destellos del Sol
+            self.bloom.apply(framebuffer);
+            if let Some(screen_sun) = screen_sun(sun, camera, &uniforms, framebuffer.height) {
+                self.lens_flare.apply(framebuffer, &screen_sun);
+            }
+
+            // HDR -> LDR con la exposición y el operador elegidos
+            self.tone_mapper.apply(framebuffer, delta_time);
+
+            // Post-proceso en LDR y empaquetado final
+            self.post_chain.apply(framebuffer, &PostContext { frame: self.frame });
+            framebuffer.resolve();
+        }
+        self.profiler.record(Stage::PostProcess, stage_start);
+        self.shadow_map = uniforms.shadow_map.take();
+        self.frame += 1;
+
+        // Escalar el framebuffer a la resolución de la ventana
+        let stage_start = Instant::now();
+        upscale(
+            &framebuffer.buffer,
+            framebuffer.width,
+            framebuffer.height,
+            &mut self.output.buffer,
+            self.output_size.0,
+            self.output_size.1,
+            self.upscale_filter
+        );
+        self.profiler.record(Stage::Upscale, stage_start);
+
+        // Resolución dinámica según lo que tardó este cuadro (sin contar la espera de vsync)
+        let render_time = render_start.elapsed().as_secs_f32();
+        let new_scale = self.dynamic_resolution.update(render_time, self.render_scale);
+        self.set_render_scale(new_scale);
+    }
+}
+
+// Todo lo que se dibuja como malla, con el índice de planeta que usan las sombras analíticas
+fn drawables(scene: &Scene) -> impl Iterator<Item = (&CelestialBody, Option<usize>)> {
+    std::iter::once((&scene.sun, None))
+        .chain(scene.planets.iter().enumerate().map(|(i, planet)| (planet, Some(i))))
+        .chain(scene.props.iter().map(|prop| (prop, None)))
+}
+
+fn model_matrix(body: &CelestialBody) -> Mat4 {
+    create_model_matrix(body.position, body.scale, body.orientation())
+}
+
+fn render_skybox(framebuffer: &mut Framebuffer) {
+    // Las estrellas se calculan sobre una rejilla fija para que no cambien con el tamaño de la ventana
+    for y in 0..framebuffer.height {
+        for x in 0..framebuffer.width {
+            let sx = x * STAR_FIELD_WIDTH / framebuffer.width;
+            let sy = y * STAR_FIELD_HEIGHT / framebuffer.height;
+            // Aritmética modular a propósito: en debug un desbordamiento normal haría panic
+            let seed = sx.wrapping_mul(73856093) ^ sy.wrapping_mul(19349663);
+            let rand_val = (seed.wrapping_mul(1103515245).wrapping_add(12345) / 65536) % 10000;
+            
+            if rand_val < 100 {
+                let brightness = ((rand_val % 3) as f32 / 2.0) * 0.6 + 0.6;
+                let color = Color::from_float(brightness, brightness, brightness);
+                framebuffer.point_with_color(x, y, 1000.0, color);
+            }
+        }
+    }
+}
+
+fn render_orbit(planet: &CelestialBody, center: Vec3, framebuffer: &mut Framebuffer, uniforms: &Uniforms) {
+    let segments = 150;
+    let orbit_color = Color::new(60, 60, 80);
+    
+    for i in 0..segments {
+        let angle1 = (i as f32 / segments as f32) * 2.0 * PI;
+        let angle2 = ((i + 1) as f32 / segments as f32) * 2.0 * PI;
+        
+        let p1 = center + Vec3::new(
+            planet.orbit_radius * angle1.cos(),
+            0.0,
+            planet.orbit_radius * angle1.sin()
+        );
+        let p2 = center + Vec3::new(
+            planet.orbit_radius * angle2.cos(),
+            0.0,
+            planet.orbit_radius * angle2.sin()
+        );
+        
+        use crate::math::Vec4;
+        let pos1_4d = Vec4::new(p1.x, p1.y, p1.z, 1.0);
+        let pos2_4d = Vec4::new(p2.x, p2.y, p2.z, 1.0);
+        
+        let transformed1 = uniforms.projection_matrix * uniforms.view_matrix * pos1_4d;
+        let transformed2 = uniforms.projection_matrix * uniforms.view_matrix * pos2_4d;
+        
+        if transformed1.w.abs() < 0.001 || transformed2.w.abs() < 0.001 {
+            continue;
+        }
+        
+        let ndc1 = Vec4::new(
+            transformed1.x / transformed1.w,
+            transformed1.y / transformed1.w,
+            transformed1.z / transformed1.w,
+            1.0
+        );
+        let ndc2 = Vec4::new(
+            transformed2.x / transformed2.w,
+            transformed2.y / transformed2.w,
+            transformed2.z / transformed2.w,
+            1.0
+        );
+        
+        if ndc1.x.abs() > 2.0 || ndc1.y.abs() > 2.0 || ndc2.x.abs() > 2.0 || ndc2.y.abs() > 2.0 {
+            continue;
+        }
+        
+        if ndc1.z < -1.0 || ndc1.z > 1.0 || ndc2.z < -1.0 || ndc2.z > 1.0 {
+            continue;
+        }
+        
+        let screen1 = uniforms.viewport_matrix * ndc1;
+        let screen2 = uniforms.viewport_matrix * ndc2;
+        
+        if !screen1.x.is_finite() || !screen1.y.is_finite() || 
+           !screen2.x.is_finite() || !screen2.y.is_finite() ||
+           !screen1.z.is_finite() || !screen2.z.is_finite() {
+            continue;
+        }
+        framebuffer.line_3d(
+            Vec3::new(screen1.x, screen1.y, screen1.z),
+            Vec3::new(screen2.x, screen2.y, screen2.z),
+            orbit_color
+        );
+    }
+}
+
+fn render_obj_with_shader(
+    obj: &Obj, 
+    framebuffer: &mut Framebuffer, 
+    uniforms: &mut Uniforms,
+    shader: FragmentShader,
+    profiler: &mut Profiler
+) {
+    let stage_start = Instant::now();
+    let transformed_vertices: Vec<Vertex> = obj.vertices
+        .iter()
+        .map(|v| vertex_shader(v, uniforms))
+        .collect();
+    profiler.record(Stage::Vertex, stage_start);
+
+    let stage_start = Instant::now();
+    let (max_x, max_y) = (framebuffer.width as f32, framebuffer.height as f32);
+
+    for i in (0..obj.indices.len()).step_by(3) {
+        if i + 2 >= obj.indices.len() {
+            break;
+        }
+        
+        let idx1 = obj.indices[i];
+        let idx2 = obj.indices[i + 1];
+        let idx3 = obj.indices[i + 2];
+        
+        if idx1 >= transformed_vertices.len() || 
+           idx2 >= transformed_vertices.len() || 
+           idx3 >= transformed_vertices.len() {
+            continue;
+        }
+
+        let v1 = &transformed_vertices[idx1];
+        let v2 = &transformed_vertices[idx2];
+        let v3 = &transformed_vertices[idx3];
+        framebuffer.counters.triangles_submitted += 1;
+
+        if v1.position.z < 0.0 || v2.position.z < 0.0 || v3.position.z < 0.0 {
+            framebuffer.counters.triangles_culled += 1;
+            continue;
+        }
+
+        // Fuera de la pantalla no hay nada que rasterizar
+        let (a, b, c) = (v1.position, v2.position, v3.position);
+        if a.x.max(b.x).max(c.x) < 0.0
+            || a.y.max(b.y).max(c.y) < 0.0
+            || a.x.min(b.x).min(c.x) >= max_x
+            || a.y.min(b.y).min(c.y) >= max_y
+        {
+            framebuffer.counters.triangles_culled += 1;
+            continue;
+        }
+
+        uniforms.triangle_id = i / 3;
+        framebuffer.counters.triangles_drawn += 1;
+        triangle(v1, v2, v3, framebuffer, uniforms, shader);
+    }
+    profiler.record(Stage::Raster, stage_start);
+}
+
+// Posición y tamaño del Sol en pantalla, si está delante de la cámara
+fn screen_sun(sun: &CelestialBody, camera: &Camera, uniforms: &Uniforms, screen_height: usize) -> Option<ScreenSun> {
+    let to_camera = camera.eye - sun.position;
+    let distance = to_camera.magnitude();
+    if distance <= sun.radius() {
+        return None;
+    }
+
+    let center = project_point(sun.position, uniforms)?;
+    let nearest = project_point(sun.position + to_camera.normalize() * sun.radius(), uniforms)?;
+    let radius = sun.radius() / (distance * (FIELD_OF_VIEW / 2.0).tan()) * screen_height as f32 / 2.0;
+
+    Some(ScreenSun {
+        x: center.x,
+        y: center.y,
+        depth: nearest.z,
+        radius,
+    })
+}
+
+
+pub fn render_size(window_size: (usize, usize), scale: f32) -> (usize, usize) {
+    (
+        ((window_size.0 as f32 * scale).round() as usize).max(1),
+        ((window_size.1 as f32 * scale).round() as usize).max(1),
+    )
+}
+
+
+// Framebuffer principal y, con SSAA, el de alta resolución donde se dibuja la geometría
+fn create_render_targets(width: usize, height: usize, anti_aliasing: AntiAliasing) -> (Framebuffer, Option<Framebuffer>) {
+    let background = Color::new(0, 0, 10);
+
+    let mut framebuffer = Framebuffer::new(width, height);
+    framebuffer.set_background_color(background);
+    framebuffer.set_samples(anti_aliasing.msaa_samples());
+
+    let ssaa_factor = anti_aliasing.ssaa_factor();
+    let ssaa_framebuffer = (ssaa_factor > 1).then(|| {
+        let mut hi_res = Framebuffer::new(width * ssaa_factor, height * ssaa_factor);
+        hi_res.set_background_color(background);
+        hi_res
+    });
+
+    (framebuffer, ssaa_framebuffer)
+}
+
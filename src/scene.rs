@@ -0,0 +1,370 @@
+use crate::math::{Quat, Vec3};
+use crate::obj::Obj;
+use crate::shaders::FragmentShader;
+use crate::planet_shaders::shader_by_name;
+use crate::shadows::Occluder;
+use std::path::Path;
+
+// Radio del modelo assets/sphere.obj con escala 1
+pub const SPHERE_RADIUS: f32 = 0.5;
+
+// La esfera siempre es la primera malla cargada
+pub const SPHERE_MESH: usize = 0;
+
+pub struct CelestialBody {
+    pub name: String,
+    pub position: Vec3,
+    pub scale: f32,
+    pub rotation_speed: f32,
+    pub orbit_speed: f32,
+    pub orbit_radius: f32,
+    pub rotation: f32,
+    pub orbit_angle: f32,
+    // Inclinación del eje de rotación respecto de la normal de la órbita, en radianes
+    pub axial_tilt: f32,
+    // Hacia dónde se inclina el polo norte, medido alrededor del eje Y del mundo
+    pub tilt_direction: f32,
+    // El eje gira alrededor del Y del mundo a esta velocidad (radianes por segundo)
+    pub precession_speed: f32,
+    pub precession_angle: f32,
+    pub shader: FragmentShader,
+    // Índice del planeta alrededor del cual orbita (None = el Sol)
+    pub parent: Option<usize>,
+    pub mesh: usize,
+}
+
+impl CelestialBody {
+    pub fn new(name: &str, orbit_radius: f32, scale: f32, rotation_speed: f32, orbit_speed: f32, shader: FragmentShader) -> Self {
+        CelestialBody {
+            name: name.to_string(),
+            position: Vec3::new(orbit_radius, 0.0, 0.0),
+            scale,
+            rotation_speed,
+            orbit_speed,
+            orbit_radius,
+            rotation: 0.0,
+            orbit_angle: 0.0,
+            axial_tilt: 0.0,
+            tilt_direction: 0.0,
+            precession_speed: 0.0,
+            precession_angle: 0.0,
+            shader,
+            parent: None,
+            mesh: SPHERE_MESH,
+        }
+    }
+
+    pub fn with_parent(mut self, parent: usize) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    pub fn with_mesh(mut self, mesh: usize) -> Self {
+        self.mesh = mesh;
+        self
+    }
+
+    pub fn with_orbit_angle(mut self, angle: f32) -> Self {
+        self.orbit_angle = angle;
+        self
+    }
+
+    pub fn with_axial_tilt(mut self, tilt: f32) -> Self {
+        self.axial_tilt = tilt;
+        self
+    }
+
+    pub fn with_tilt_direction(mut self, direction: f32) -> Self {
+        self.tilt_direction = direction;
+        self
+    }
+
+    pub fn with_precession(mut self, speed: f32) -> Self {
+        self.precession_speed = speed;
+        self
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.scale * SPHERE_RADIUS
+    }
+
+    // Orientación del eje sin el giro diario: el ecuador del modelo queda sobre el del cuerpo.
+    // No depende de la órbita, así que el eje apunta siempre al mismo lado y hay estaciones.
+    pub fn axis_orientation(&self) -> Quat {
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        Quat::from_axis_angle(&up, self.tilt_direction + self.precession_angle)
+            * Quat::from_axis_angle(&Vec3::new(1.0, 0.0, 0.0), self.axial_tilt)
+    }
+
+    // Eje inclinado y giro sobre sí mismo alrededor de él
+    pub fn orientation(&self) -> Quat {
+        self.axis_orientation() * Quat::from_axis_angle(&Vec3::new(0.0, 1.0, 0.0), self.rotation)
+    }
+
+    // Dirección del polo norte en el mundo
+    pub fn rotation_axis(&self) -> Vec3 {
+        self.axis_orientation() * Vec3::new(0.0, 1.0, 0.0)
+    }
+
+    pub fn update(&mut self, delta_time: f32, orbit_center: Vec3) {
+        self.rotation += self.rotation_speed * delta_time;
+        self.precession_angle += self.precession_speed * delta_time;
+        self.orbit_angle += self.orbit_speed * delta_time;
+
+        self.position.x = orbit_center.x + self.orbit_radius * self.orbit_angle.cos();
+        self.position.y = orbit_center.y;
+        self.position.z = orbit_center.z + self.orbit_radius * self.orbit_angle.sin();
+    }
+}
+
+// Esfera que la cámara no puede atravesar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Obstacle {
+    pub position: Vec3,
+    pub radius: f32,
+}
+
+// Sol, planetas (con sus lunas) y mallas sueltas, junto con las mallas que usan
+pub struct Scene {
+    pub sun: CelestialBody,
+    pub planets: Vec<CelestialBody>,
+    // Mallas no esféricas: usan el shadow map en vez de sombras analíticas.
+    // `parent` se refiere a la lista de planetas.
+    pub props: Vec<CelestialBody>,
+    pub meshes: Vec<Obj>,
+    mesh_names: Vec<String>,
+}
+
+impl Scene {
+    // Lee un archivo de escena; las mallas se buscan en `asset_dir`
+    pub fn load(path: &Path, asset_dir: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("no se pudo leer {}: {}", path.display(), e))?;
+        Scene::parse(&text, asset_dir).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    // Una línea por cuerpo: `tipo nombre órbita escala rotación vel_órbita shader [clave=valor...]`
+    pub fn parse(text: &str, asset_dir: &Path) -> Result<Self, String> {
+        let mut scene = Scene {
+            sun: CelestialBody::new("Sol", 0.0, 2.0, 0.2, 0.0, crate::planet_shaders::sun_shader),
+            planets: Vec::new(),
+            props: Vec::new(),
+            meshes: Vec::new(),
+            mesh_names: Vec::new(),
+        };
+        scene.mesh_index("sphere", asset_dir)?;
+
+        let mut has_sun = false;
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let body = scene
+                .parse_body(&fields, asset_dir)
+                .map_err(|e| format!("línea {}: {}", number + 1, e))?;
+            match fields[0] {
+                "sun" if has_sun => return Err(format!("línea {}: la escena ya tiene un Sol", number + 1)),
+                "sun" => {
+                    scene.sun = body;
+                    has_sun = true;
+                }
+                "planet" => scene.planets.push(body),
+                _ => scene.props.push(body),
+            }
+        }
+
+        if !has_sun {
+            return Err("la escena no tiene Sol".to_string());
+        }
+        Ok(scene)
+    }
+
+    fn parse_body(&mut self, fields: &[&str], asset_dir: &Path) -> Result<CelestialBody, String> {
+        if !matches!(fields[0], "sun" | "planet" | "prop") {
+            return Err(format!("tipo desconocido: {} (sun, planet o prop)", fields[0]));
+        }
+        if fields.len() < 7 {
+            return Err("faltan campos (tipo nombre órbita escala rotación vel_órbita shader)".to_string());
+        }
+
+        let number = |index: usize| {
+            fields[index]
+                .parse::<f32>()
+                .map_err(|_| format!("número inválido: {}", fields[index]))
+        };
+        let shader = shader_by_name(fields[6]).ok_or_else(|| format!("shader desconocido: {}", fields[6]))?;
+        let mut body = CelestialBody::new(fields[1], number(2)?, number(3)?, number(4)?, number(5)?, shader);
+
+        let degrees = |value: &str| {
+            value
+                .parse::<f32>()
+                .map(f32::to_radians)
+                .map_err(|_| format!("número inválido: {}", value))
+        };
+        for option in &fields[7..] {
+            let (key, value) = option
+                .split_once('=')
+                .ok_or_else(|| format!("opción inválida: {} (formato clave=valor)", option))?;
+            match key {
+                "parent" => {
+                    // Solo se puede orbitar un planeta ya declarado, así se actualiza antes
+                    let parent = self
+                        .planets
+                        .iter()
+                        .position(|p| p.name == value)
+                        .ok_or_else(|| format!("planeta desconocido: {}", value))?;
+                    body = body.with_parent(parent);
+                }
+                "mesh" => {
+                    let mesh = self.mesh_index(value, asset_dir)?;
+                    body = body.with_mesh(mesh);
+                }
+                "phase" => body = body.with_orbit_angle(degrees(value)?),
+                "tilt" => body = body.with_axial_tilt(degrees(value)?),
+                "tilt_dir" => body = body.with_tilt_direction(degrees(value)?),
+                "precession" => body = body.with_precession(degrees(value)?),
+                _ => return Err(format!("opción desconocida: {}", key)),
+            }
+        }
+
+        Ok(body)
+    }
+
+    // Carga `asset_dir/NOMBRE.obj` la primera vez que se pide
+    fn mesh_index(&mut self, name: &str, asset_dir: &Path) -> Result<usize, String> {
+        if let Some(index) = self.mesh_names.iter().position(|n| n == name) {
+            return Ok(index);
+        }
+        let path = asset_dir.join(format!("{}.obj", name));
+        let mesh = Obj::load(&path.to_string_lossy())
+            .map_err(|e| format!("no se pudo cargar {}: {}", path.display(), e))?;
+        self.meshes.push(mesh);
+        self.mesh_names.push(name.to_string());
+        Ok(self.meshes.len() - 1)
+    }
+
+    pub fn orbit_center(&self, body: &CelestialBody) -> Vec3 {
+        body.parent.map_or(self.sun.position, |p| self.planets[p].position)
+    }
+
+    // Actualizar posiciones (cada luna va después de su planeta)
+    pub fn update(&mut self, delta_time: f32) {
+        self.sun.update(delta_time, Vec3::zeros());
+        for i in 0..self.planets.len() {
+            let orbit_center = self.orbit_center(&self.planets[i]);
+            self.planets[i].update(delta_time, orbit_center);
+        }
+        for i in 0..self.props.len() {
+            let orbit_center = self.orbit_center(&self.props[i]);
+            self.props[i].update(delta_time, orbit_center);
+        }
+    }
+
+    // Los planetas como esferas que pueden tapar el Sol
+    pub fn occluders(&self) -> Vec<Occluder> {
+        self.planets
+            .iter()
+            .enumerate()
+            .map(|(i, planet)| Occluder { body: i, position: planet.position, radius: planet.radius() })
+            .collect()
+    }
+
+    // El Sol, los planetas y las mallas sueltas, como esferas que la cámara no atraviesa
+    pub fn obstacles(&self) -> Vec<Obstacle> {
+        let bodies = std::iter::once(&self.sun)
+            .chain(&self.planets)
+            .map(|body| Obstacle { position: body.position, radius: body.radius() });
+        // Las mallas no son esferas: se usa la esfera que las envuelve
+        let props = self.props.iter().map(|prop| Obstacle {
+            position: prop.position,
+            radius: self.meshes[prop.mesh].bounding_radius() * prop.scale,
+        });
+        bodies.chain(props).collect()
+    }
+
+    // Segundos de simulación que dura un año: el período orbital de la Tierra, si está en la escena
+    pub fn year_length(&self) -> Option<f32> {
+        let earth = self.find("Tierra")?;
+        (earth.orbit_speed != 0.0).then(|| 2.0 * std::f32::consts::PI / earth.orbit_speed.abs())
+    }
+
+    // Sol o planeta por nombre, sin distinguir mayúsculas
+    pub fn find(&self, name: &str) -> Option<&CelestialBody> {
+        std::iter::once(&self.sun)
+            .chain(&self.planets)
+            .find(|body| body.name.to_lowercase() == name.to_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::{Camera, CameraMode, BODY_CLEARANCE};
+
+    fn parse(text: &str) -> Result<Scene, String> {
+        Scene::parse(text, &Path::new(env!("CARGO_MANIFEST_DIR")).join("assets"))
+    }
+
+    fn error(text: &str) -> String {
+        parse(text).err().expect("se esperaba un error")
+    }
+
+    const SUN: &str = "sun Sol 0 2 0.2 0 sun\n";
+
+    #[test]
+    fn parses_bodies_and_options() {
+        let scene = parse(&format!(
+            "{}# comentario\n\nplanet Tierra 8 0.7 2 0.3 earth tilt=23.4 phase=90\nplanet Luna 1.3 0.2 1 2 moon parent=Tierra\n",
+            SUN
+        ))
+        .unwrap();
+        assert_eq!(scene.planets.len(), 2);
+        assert_eq!(scene.planets[1].parent, Some(0));
+        assert!((scene.planets[0].axial_tilt - 23.4f32.to_radians()).abs() < 1e-6);
+        assert!(scene.find("luna").is_some());
+    }
+
+    #[test]
+    fn camera_stops_at_props() {
+        let mut scene = parse(&format!("{}prop Nave 6 0.5 0 0 spacecraft mesh=spacecraft phase=90\n", SUN)).unwrap();
+        scene.update(0.0);
+        let ship = &scene.props[0];
+        let radius = scene.meshes[ship.mesh].bounding_radius() * ship.scale;
+        let obstacles = scene.obstacles();
+        assert_eq!(obstacles.len(), 2);
+        assert_eq!(obstacles[1], Obstacle { position: ship.position, radius });
+
+        // Vuelo libre en línea recta a través de la nave: se frena antes de tocarla
+        let from = ship.position - Vec3::new(5.0, 0.0, 0.0);
+        let to = ship.position + Vec3::new(5.0, 0.0, 0.0);
+        let mut camera = Camera::new(from, from + Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0));
+        camera.set_mode(CameraMode::FreeFly);
+        camera.move_center(to - from);
+        camera.avoid_bodies(from, &obstacles);
+        let expected = ship.position - Vec3::new(radius + BODY_CLEARANCE, 0.0, 0.0);
+        assert!(camera.eye.distance(&expected) < 1e-3, "{:?} != {:?}", camera.eye, expected);
+    }
+
+    #[test]
+    fn rejects_bad_lines() {
+        assert_eq!(error(""), "la escena no tiene Sol");
+        assert_eq!(error(&format!("{}{}", SUN, SUN)), "línea 2: la escena ya tiene un Sol");
+        let cases = [
+            ("moon Luna 1 1 1 1 moon", "tipo desconocido"),
+            ("planet Tierra 8 0.7 2 0.3", "faltan campos"),
+            ("planet Tierra ocho 0.7 2 0.3 earth", "número inválido: ocho"),
+            ("planet Tierra 8 0.7 2 0.3 plasma", "shader desconocido: plasma"),
+            ("planet Luna 1 1 1 1 moon parent=Tierra", "planeta desconocido: Tierra"),
+            ("planet Tierra 8 0.7 2 0.3 earth tilt", "opción inválida"),
+            ("planet Tierra 8 0.7 2 0.3 earth tilt=mucho", "número inválido: mucho"),
+            ("planet Tierra 8 0.7 2 0.3 earth color=azul", "opción desconocida: color"),
+            ("prop Nave 5 1 0 1 rocky mesh=no_existe", "no se pudo cargar"),
+        ];
+        for (line, message) in cases {
+            let error = error(&format!("{}{}\n", SUN, line));
+            assert!(error.starts_with("línea 2: ") && error.contains(message), "{}: {}", line, error);
+        }
+    }
+}
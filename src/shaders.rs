@@ -1,6 +1,11 @@
-use crate::math::{Vec3, Vec4, Mat4};
+use crate::math::{Vec3, Vec4, Mat4, Quat};
 use crate::vertex::Vertex;
 use crate::framebuffer::Color;
+use crate::lighting::LightingModel;
+use crate::shadows::{Occluder, sun_visibility};
+use crate::shadow_map::ShadowMap;
+
+pub type FragmentShader = fn(&Fragment, &Uniforms) -> Color;
 
 pub struct Uniforms {
     pub model_matrix: Mat4,
@@ -8,6 +13,17 @@ pub struct Uniforms {
     pub projection_matrix: Mat4,
     pub viewport_matrix: Mat4,
     pub time: f32,
+    pub camera_position: Vec3,
+    pub light_position: Vec3,
+    pub lighting_model: LightingModel,
+    pub sun_radius: f32,
+    pub occluders: Vec<Occluder>,
+    // Cuerpo que se está dibujando, para que no se haga sombra a sí mismo
+    pub current_body: Option<usize>,
+    // Sombras de mallas arbitrarias (naves, asteroides)
+    pub shadow_map: Option<ShadowMap>,
+    // Índice del triángulo que se está rasterizando (vista de IDs de triángulo)
+    pub triangle_id: usize,
 }
 
 impl Uniforms {
@@ -18,6 +34,23 @@ impl Uniforms {
             projection_matrix: Mat4::identity(),
             viewport_matrix: Mat4::identity(),
             time: 0.0,
+            camera_position: Vec3::zeros(),
+            light_position: Vec3::zeros(),
+            lighting_model: LightingModel::BlinnPhong,
+            sun_radius: 0.0,
+            occluders: Vec::new(),
+            current_body: None,
+            shadow_map: None,
+            triangle_id: 0,
+        }
+    }
+
+    // Fracción de luz solar que recibe un punto del mundo
+    pub fn sun_visibility(&self, point: Vec3) -> f32 {
+        let analytic = sun_visibility(point, self.light_position, self.sun_radius, &self.occluders, self.current_body);
+        match &self.shadow_map {
+            Some(shadow_map) if analytic > 0.0 => analytic * shadow_map.visibility(point),
+            _ => analytic,
         }
     }
 }
@@ -25,19 +58,17 @@ impl Uniforms {
 pub struct Fragment {
     pub position: Vec3,
     pub normal: Vec3,
-    pub depth: f32,
-    pub color: Color,
     pub tex_coords: Vec3,
+    pub world_position: Vec3,
 }
 
 impl Fragment {
-    pub fn new(position: Vec3, normal: Vec3, depth: f32, tex_coords: Vec3) -> Self {
+    pub fn new(position: Vec3, normal: Vec3, tex_coords: Vec3, world_position: Vec3) -> Self {
         Fragment {
             position,
             normal,
-            depth,
-            color: Color::new(255, 255, 255),
             tex_coords,
+            world_position,
         }
     }
 }
@@ -50,10 +81,12 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
         1.0
     );
 
+    let world = uniforms.model_matrix * position;
+    let world_position = Vec3::new(world.x, world.y, world.z);
+
     let transformed = uniforms.projection_matrix 
         * uniforms.view_matrix 
-        * uniforms.model_matrix 
-        * position;
+        * world;
 
     let w = transformed.w;
     
@@ -62,6 +95,7 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
             position: Vec3::new(-10000.0, -10000.0, -10000.0),
             normal: vertex.normal,
             tex_coords: vertex.tex_coords,
+            world_position,
         };
     }
     
@@ -74,10 +108,8 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
 
     let screen_position = uniforms.viewport_matrix * ndc_position;
 
-    let model_mat3 = Mat4::identity(); // Simplificado por ahora
-    let normal_matrix = model_mat3;
-    
-    let transformed_normal = normal_matrix * Vec4::new(
+    // La escala es uniforme, así que la matriz de modelo sirve para las normales (w = 0 ignora la traslación)
+    let transformed_normal = uniforms.model_matrix * Vec4::new(
         vertex.normal.x,
         vertex.normal.y,
         vertex.normal.z,
@@ -88,27 +120,30 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
         position: Vec3::new(screen_position.x, screen_position.y, screen_position.z),
         normal: Vec3::new(transformed_normal.x, transformed_normal.y, transformed_normal.z).normalize(),
         tex_coords: vertex.tex_coords,
+        world_position,
     }
 }
 
-pub fn create_model_matrix(translation: Vec3, scale_val: f32, rotation: Vec3) -> Mat4 {
-    use crate::math::{translate, scale, rotate_x, rotate_y, rotate_z};
+// Proyecta un punto del mundo a pantalla (x, y en píxeles, z = profundidad NDC).
+// Devuelve None si queda detrás de la cámara.
+pub fn project_point(point: Vec3, uniforms: &Uniforms) -> Option<Vec3> {
+    let clip = uniforms.projection_matrix * uniforms.view_matrix * Vec4::new(point.x, point.y, point.z, 1.0);
+    if clip.w <= 0.0001 {
+        return None;
+    }
+
+    let ndc = Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+    let screen = uniforms.viewport_matrix * ndc;
+    Some(Vec3::new(screen.x, screen.y, screen.z))
+}
+
+pub fn create_model_matrix(translation: Vec3, scale_val: f32, orientation: Quat) -> Mat4 {
+    use crate::math::{translate, scale};
     
     let translation_matrix = translate(&translation);
     let scale_matrix = scale(&Vec3::new(scale_val, scale_val, scale_val));
     
-    let rotation_x_mat = rotate_x(rotation.x);
-    let rotation_y_mat = rotate_y(rotation.y);
-    let rotation_z_mat = rotate_z(rotation.z);
-    
-    let rotation_matrix = rotation_z_mat * rotation_y_mat * rotation_x_mat;
-    
-    translation_matrix * rotation_matrix * scale_matrix
-}
-
-pub fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
-    use crate::math::look_at;
-    look_at(&eye, &center, &up)
+    translation_matrix * orientation.to_mat4() * scale_matrix
 }
 
 pub fn create_perspective_matrix(fov: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
@@ -122,15 +157,7 @@ pub fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
 }
 
 // Fragment shaders básicos
-pub fn basic_fragment_shader(fragment: &Fragment) -> Color {
-    // Shader básico con iluminación simple
-    let light_dir = Vec3::new(0.0, 0.0, 1.0).normalize();
-    let intensity = fragment.normal.dot(&light_dir).max(0.0);
-    
-    Color::from_float(intensity, intensity, intensity)
-}
-
-pub fn color_fragment_shader(fragment: &Fragment) -> Color {
+pub fn color_fragment_shader(fragment: &Fragment, _uniforms: &Uniforms) -> Color {
     // Shader que usa las normales como color
     Color::from_float(
         (fragment.normal.x + 1.0) * 0.5,
@@ -0,0 +1,206 @@
+use crate::framebuffer::Framebuffer;
+use crate::math::{Vec3, Vec4, Mat4, look_at, perspective, viewport};
+use crate::obj::Obj;
+use crate::triangle::depth_triangle;
+
+// Radio del filtro PCF en texels (1 = 3x3 muestras)
+const PCF_RADIUS: i32 = 1;
+
+// Profundidad de un objeto visto desde el Sol con un frustum ajustado a su esfera envolvente.
+// El z-buffer guarda la distancia lineal a la luz.
+struct CasterShadow {
+    view_projection: Mat4,
+    viewport: Mat4,
+    depth: Framebuffer,
+    bias: f32,
+}
+
+// Los mapas de profundidad se conservan entre cuadros: cada cuadro los limpia y los vuelve a llenar
+pub struct ShadowMap {
+    resolution: usize,
+    light_position: Vec3,
+    casters: Vec<CasterShadow>,
+    // Cuántos de `casters` se dibujaron en este cuadro
+    count: usize,
+}
+
+impl ShadowMap {
+    pub fn new(resolution: usize) -> Self {
+        ShadowMap {
+            resolution,
+            light_position: Vec3::zeros(),
+            casters: Vec::new(),
+            count: 0,
+        }
+    }
+
+    // Empieza un cuadro nuevo con la luz en `light_position`
+    pub fn begin(&mut self, light_position: Vec3) {
+        self.light_position = light_position;
+        self.count = 0;
+    }
+
+    // Dibuja la profundidad de `mesh` (transformado por `model_matrix`) vista desde la luz.
+    // `center` y `radius` son la esfera envolvente del objeto en el mundo.
+    pub fn add_caster(&mut self, mesh: &Obj, model_matrix: &Mat4, center: Vec3, radius: f32) {
+        let to_center = center - self.light_position;
+        let distance = to_center.magnitude();
+        if distance <= radius * 1.05 {
+            return;
+        }
+
+        // Margen extra para que el PCF no se salga del mapa
+        let half_angle = (radius / distance).asin() * 1.1;
+        let near = (distance - radius) * 0.9;
+        let far = distance + radius * 2.0;
+        let up = if to_center.normalize().y.abs() > 0.99 {
+            Vec3::new(0.0, 0.0, 1.0)
+        } else {
+            Vec3::new(0.0, 1.0, 0.0)
+        };
+
+        let size = self.resolution as f32;
+        let view_projection = perspective(2.0 * half_angle, 1.0, near, far) * look_at(&self.light_position, &center, &up);
+        let viewport_matrix = viewport(size, size);
+        if self.count == self.casters.len() {
+            self.casters.push(CasterShadow {
+                view_projection,
+                viewport: viewport_matrix,
+                depth: Framebuffer::new_depth_only(self.resolution, self.resolution),
+                bias: 0.0,
+            });
+        }
+        let depth = &mut self.casters[self.count].depth;
+        depth.clear();
+
+        let projected: Vec<Option<Vec3>> = mesh
+            .vertices
+            .iter()
+            .map(|v| {
+                let world = *model_matrix * Vec4::new(v.position.x, v.position.y, v.position.z, 1.0);
+                let clip = view_projection * world;
+                if clip.w <= 0.0001 {
+                    return None;
+                }
+                let ndc = Vec4::new(clip.x / clip.w, clip.y / clip.w, 0.0, 1.0);
+                let screen = viewport_matrix * ndc;
+                let light_distance = (Vec3::new(world.x, world.y, world.z) - self.light_position).magnitude();
+                Some(Vec3::new(screen.x, screen.y, light_distance))
+            })
+            .collect();
+
+        for tri in mesh.indices.chunks_exact(3) {
+            let corners = (projected.get(tri[0]), projected.get(tri[1]), projected.get(tri[2]));
+            if let (Some(Some(a)), Some(Some(b)), Some(Some(c))) = corners {
+                depth_triangle(a, b, c, depth);
+            }
+        }
+
+        // Tamaño de un texel en el mundo a la distancia del objeto
+        let texel = 2.0 * distance * half_angle.tan() / size;
+
+        let caster = &mut self.casters[self.count];
+        caster.view_projection = view_projection;
+        caster.viewport = viewport_matrix;
+        caster.bias = texel * 2.0;
+        self.count += 1;
+    }
+
+    // Fracción de luz que llega a `point` (1 = iluminado, 0 = en sombra), filtrada con PCF
+    pub fn visibility(&self, point: Vec3) -> f32 {
+        let light_distance = (point - self.light_position).magnitude();
+        let taps = ((2 * PCF_RADIUS + 1) * (2 * PCF_RADIUS + 1)) as f32;
+        let mut visibility = 1.0;
+
+        for caster in &self.casters[..self.count] {
+            let clip = caster.view_projection * Vec4::new(point.x, point.y, point.z, 1.0);
+            if clip.w <= 0.0001 {
+                continue;
+            }
+            let ndc_x = clip.x / clip.w;
+            let ndc_y = clip.y / clip.w;
+            if ndc_x.abs() > 1.0 || ndc_y.abs() > 1.0 {
+                continue;
+            }
+
+            let screen = caster.viewport * Vec4::new(ndc_x, ndc_y, 0.0, 1.0);
+            let cx = screen.x.floor() as i32;
+            let cy = screen.y.floor() as i32;
+
+            // Las muestras que caen fuera del mapa repiten el texel del borde
+            let last = self.resolution as i32 - 1;
+            let mut shadowed = 0;
+            for dy in -PCF_RADIUS..=PCF_RADIUS {
+                for dx in -PCF_RADIUS..=PCF_RADIUS {
+                    let x = (cx + dx).clamp(0, last) as usize;
+                    let y = (cy + dy).clamp(0, last) as usize;
+                    if caster.depth.depth_at(x, y) < light_distance - caster.bias {
+                        shadowed += 1;
+                    }
+                }
+            }
+
+            visibility *= 1.0 - shadowed as f32 / taps;
+        }
+
+        visibility
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vertex::Vertex;
+
+    const RESOLUTION: usize = 64;
+
+    // Cuadrado en el plano x = `x`, de lado 2·`half`, de frente a una luz en el origen
+    fn wall(x: f32, half: f32) -> Obj {
+        let normal = Vec3::new(-1.0, 0.0, 0.0);
+        let corner = |y: f32, z: f32| Vertex::new(Vec3::new(x, y, z), normal, Vec3::zeros());
+        Obj {
+            vertices: vec![corner(-half, -half), corner(half, -half), corner(half, half), corner(-half, half)],
+            indices: vec![0, 1, 2, 0, 2, 3],
+        }
+    }
+
+    // El muro tapa todo el mapa: la esfera envolvente declarada es menor que el cuadrado
+    fn shadow_map() -> ShadowMap {
+        let mut shadow_map = ShadowMap::new(RESOLUTION);
+        shadow_map.begin(Vec3::zeros());
+        shadow_map.add_caster(&wall(10.0, 2.0), &Mat4::identity(), Vec3::new(10.0, 0.0, 0.0), 1.0);
+        shadow_map
+    }
+
+    #[test]
+    fn point_behind_caster_is_occluded() {
+        let shadow_map = shadow_map();
+        assert_eq!(shadow_map.visibility(Vec3::new(20.0, 0.0, 0.0)), 0.0);
+        assert_eq!(shadow_map.visibility(Vec3::new(20.0, 0.5, -0.5)), 0.0);
+    }
+
+    #[test]
+    fn points_in_front_or_outside_the_map_are_lit() {
+        let shadow_map = shadow_map();
+        // Entre la luz y el objeto
+        assert_eq!(shadow_map.visibility(Vec3::new(5.0, 0.0, 0.0)), 1.0);
+        // Detrás del objeto pero fuera del frustum de la luz
+        assert_eq!(shadow_map.visibility(Vec3::new(20.0, 10.0, 0.0)), 1.0);
+        // Sin objetos en este cuadro no hay sombra
+        let mut empty = ShadowMap::new(RESOLUTION);
+        empty.begin(Vec3::zeros());
+        assert_eq!(empty.visibility(Vec3::new(20.0, 0.0, 0.0)), 1.0);
+    }
+
+    #[test]
+    fn shadow_stays_dark_at_map_edges() {
+        let shadow_map = shadow_map();
+        // Puntos detrás del muro que se proyectan sobre los cuatro bordes del mapa
+        let half_angle = (1.0f32 / 10.0).asin() * 1.1;
+        let edge = 20.0 * half_angle.tan() * 0.999;
+        for offset in [Vec3::new(0.0, edge, 0.0), Vec3::new(0.0, -edge, 0.0), Vec3::new(0.0, 0.0, edge), Vec3::new(0.0, 0.0, -edge)] {
+            let point = Vec3::new(20.0, 0.0, 0.0) + offset;
+            assert_eq!(shadow_map.visibility(point), 0.0, "{:?}", point);
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use crate::math::Vec3;
+use std::f32::consts::PI;
+
+// Esfera que puede tapar la luz del Sol
+#[derive(Debug, Clone, Copy)]
+pub struct Occluder {
+    pub body: usize,
+    pub position: Vec3,
+    pub radius: f32,
+}
+
+// Área de intersección entre dos discos de radios r1 y r2 separados por d
+pub fn disc_overlap_area(r1: f32, r2: f32, d: f32) -> f32 {
+    if d >= r1 + r2 {
+        return 0.0;
+    }
+    if d <= (r1 - r2).abs() {
+        let r = r1.min(r2);
+        return PI * r * r;
+    }
+
+    let a1 = ((d * d + r1 * r1 - r2 * r2) / (2.0 * d * r1)).clamp(-1.0, 1.0).acos();
+    let a2 = ((d * d + r2 * r2 - r1 * r1) / (2.0 * d * r2)).clamp(-1.0, 1.0).acos();
+    let k = (-d + r1 + r2) * (d + r1 - r2) * (d - r1 + r2) * (d + r1 + r2);
+
+    r1 * r1 * a1 + r2 * r2 * a2 - 0.5 * k.max(0.0).sqrt()
+}
+
+// Fracción del disco solar tapada por un oclusor, vista desde `point` (0 = nada, 1 = total)
+pub fn sun_coverage(point: Vec3, sun_position: Vec3, sun_radius: f32, occluder: &Occluder) -> f32 {
+    let to_sun = sun_position - point;
+    let to_occluder = occluder.position - point;
+    let sun_distance = to_sun.magnitude();
+    let occluder_distance = to_occluder.magnitude();
+
+    // El oclusor tiene que estar entre el punto y el Sol
+    if occluder_distance <= occluder.radius || occluder_distance >= sun_distance {
+        return 0.0;
+    }
+
+    let sun_angular = (sun_radius / sun_distance).min(1.0).asin();
+    let occluder_angular = (occluder.radius / occluder_distance).min(1.0).asin();
+    let cos_separation = to_sun.dot(&to_occluder) / (sun_distance * occluder_distance);
+    let separation = cos_separation.clamp(-1.0, 1.0).acos();
+
+    if separation >= sun_angular + occluder_angular {
+        return 0.0;
+    }
+
+    let sun_area = PI * sun_angular * sun_angular;
+    (disc_overlap_area(sun_angular, occluder_angular, separation) / sun_area).min(1.0)
+}
+
+// Fracción de luz solar que llega a `point` considerando todos los oclusores (umbra = 0, penumbra entre 0 y 1)
+pub fn sun_visibility(
+    point: Vec3,
+    sun_position: Vec3,
+    sun_radius: f32,
+    occluders: &[Occluder],
+    skip_body: Option<usize>,
+) -> f32 {
+    let mut visibility = 1.0;
+    for occluder in occluders {
+        if Some(occluder.body) == skip_body {
+            continue;
+        }
+        visibility *= 1.0 - sun_coverage(point, sun_position, sun_radius, occluder);
+        if visibility <= 0.0 {
+            return 0.0;
+        }
+    }
+    visibility
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EclipseKind {
+    // El oclusor se ve más grande que el Sol: puede taparlo por completo
+    Eclipse,
+    // El oclusor cruza el disco solar sin llegar a cubrirlo
+    Transit,
+}
+
+#[derive(Debug, Clone)]
+pub struct EclipseEvent {
+    pub time: f32,
+    pub kind: EclipseKind,
+    pub target: usize,
+    pub occluder: usize,
+    pub started: bool,
+    // Máxima fracción del Sol tapada mientras duró el evento
+    pub max_coverage: f32,
+}
+
+struct ActiveEclipse {
+    target: usize,
+    occluder: usize,
+    kind: EclipseKind,
+    max_coverage: f32,
+}
+
+// Punto de la superficie de `target` más cercano al eje de sombra de `occluder` (la recta
+// que sale del Sol y pasa por su centro); es el primero del cuerpo en entrar en la penumbra
+fn limb_toward_shadow_axis(target: &Occluder, sun_position: Vec3, occluder: &Occluder) -> Vec3 {
+    let axis = (occluder.position - sun_position).normalize();
+    let along = (target.position - sun_position).dot(&axis).max(0.0);
+    let to_axis = (sun_position + axis * along) - target.position;
+    let distance = to_axis.magnitude();
+    if distance <= 1e-6 {
+        return target.position;
+    }
+    target.position + to_axis * (distance.min(target.radius) / distance)
+}
+
+// Detecta inicio y fin de eclipses/tránsitos evaluando la cobertura desde el punto de cada cuerpo
+// más cercano al eje de sombra del oclusor, así se registran también los eclipses parciales
+pub struct EclipseTracker {
+    active: Vec<ActiveEclipse>,
+}
+
+impl EclipseTracker {
+    pub fn new() -> Self {
+        EclipseTracker {
+            active: Vec::new(),
+        }
+    }
+
+    // `bodies` son los oclusores de la escena; cada uno se evalúa también como objetivo.
+    // Devuelve los eventos generados en esta llamada.
+    pub fn update(&mut self, time: f32, sun_position: Vec3, sun_radius: f32, bodies: &[Occluder]) -> Vec<EclipseEvent> {
+        let mut events = Vec::new();
+
+        for target in bodies {
+            for occluder in bodies {
+                if occluder.body == target.body {
+                    continue;
+                }
+
+                let point = limb_toward_shadow_axis(target, sun_position, occluder);
+                let coverage = sun_coverage(point, sun_position, sun_radius, occluder);
+                let index = self
+                    .active
+                    .iter()
+                    .position(|a| a.target == target.body && a.occluder == occluder.body);
+
+                match (index, coverage > 0.0) {
+                    (None, true) => {
+                        let sun_angular = sun_radius / (sun_position - point).magnitude();
+                        let occluder_angular = occluder.radius / (occluder.position - point).magnitude();
+                        let kind = if occluder_angular >= sun_angular {
+                            EclipseKind::Eclipse
+                        } else {
+                            EclipseKind::Transit
+                        };
+                        self.active.push(ActiveEclipse {
+                            target: target.body,
+                            occluder: occluder.body,
+                            kind,
+                            max_coverage: coverage,
+                        });
+                        events.push(EclipseEvent {
+                            time,
+                            kind,
+                            target: target.body,
+                            occluder: occluder.body,
+                            started: true,
+                            max_coverage: coverage,
+                        });
+                    }
+                    (Some(i), true) => {
+                        let active = &mut self.active[i];
+                        active.max_coverage = active.max_coverage.max(coverage);
+                    }
+                    (Some(i), false) => {
+                        let active = self.active.remove(i);
+                        events.push(EclipseEvent {
+                            time,
+                            kind: active.kind,
+                            target: active.target,
+                            occluder: active.occluder,
+                            started: false,
+                            max_coverage: active.max_coverage,
+                        });
+                    }
+                    (None, false) => {}
+                }
+            }
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUN: Vec3 = Vec3::new(0.0, 0.0, 0.0);
+    const SUN_RADIUS: f32 = 1.0;
+
+    fn occluder(body: usize, position: Vec3, radius: f32) -> Occluder {
+        Occluder { body, position, radius }
+    }
+
+    #[test]
+    fn overlap_area_limits() {
+        // Separados, tangentes, uno dentro del otro y concéntricos
+        assert_eq!(disc_overlap_area(1.0, 0.5, 2.0), 0.0);
+        assert_eq!(disc_overlap_area(1.0, 0.5, 1.5), 0.0);
+        assert!((disc_overlap_area(1.0, 0.5, 0.3) - PI * 0.25).abs() < 1e-5);
+        assert!((disc_overlap_area(1.0, 1.0, 0.0) - PI).abs() < 1e-5);
+        // Dos discos iguales a un radio de distancia: 2π/3 - √3/2
+        let lens = 2.0 * PI / 3.0 - 3.0f32.sqrt() / 2.0;
+        assert!((disc_overlap_area(1.0, 1.0, 1.0) - lens).abs() < 1e-4);
+        // Simétrica y decreciente con la distancia
+        assert!((disc_overlap_area(1.0, 0.4, 0.9) - disc_overlap_area(0.4, 1.0, 0.9)).abs() < 1e-5);
+        let mut previous = disc_overlap_area(1.0, 0.4, 0.6);
+        for i in 1..=20 {
+            let area = disc_overlap_area(1.0, 0.4, 0.6 + i as f32 * 0.04);
+            assert!(area <= previous + 1e-6);
+            previous = area;
+        }
+    }
+
+    #[test]
+    fn coverage_needs_occluder_between_point_and_sun() {
+        let point = Vec3::new(10.0, 0.0, 0.0);
+        // Alineado entre el punto y el Sol: tapa algo
+        assert!(sun_coverage(point, SUN, SUN_RADIUS, &occluder(0, Vec3::new(5.0, 0.0, 0.0), 0.3)) > 0.0);
+        // Detrás del punto o más allá del Sol no tapa nada
+        assert_eq!(sun_coverage(point, SUN, SUN_RADIUS, &occluder(0, Vec3::new(15.0, 0.0, 0.0), 0.3)), 0.0);
+        assert_eq!(sun_coverage(point, SUN, SUN_RADIUS, &occluder(0, Vec3::new(-5.0, 0.0, 0.0), 0.3)), 0.0);
+        // Fuera de la línea al Sol tampoco
+        assert_eq!(sun_coverage(point, SUN, SUN_RADIUS, &occluder(0, Vec3::new(5.0, 3.0, 0.0), 0.3)), 0.0);
+    }
+
+    #[test]
+    fn umbra_and_penumbra() {
+        let point = Vec3::new(10.0, 0.0, 0.0);
+        // Más grande que el Sol visto desde el punto y centrado: umbra
+        let big = occluder(0, Vec3::new(5.0, 0.0, 0.0), 1.0);
+        assert!((sun_coverage(point, SUN, SUN_RADIUS, &big) - 1.0).abs() < 1e-5);
+        // Chico y centrado: tapa la proporción de las áreas angulares
+        let small = occluder(0, Vec3::new(5.0, 0.0, 0.0), 0.25);
+        let expected = ((0.25f32 / 5.0).asin() / (1.0f32 / 10.0).asin()).powi(2);
+        assert!((sun_coverage(point, SUN, SUN_RADIUS, &small) - expected).abs() < 1e-3);
+        // Corriéndolo de costado la cobertura baja hasta cero
+        let mut previous = 1.0;
+        for i in 0..=20 {
+            let shifted = occluder(0, Vec3::new(5.0, i as f32 * 0.1, 0.0), 1.0);
+            let coverage = sun_coverage(point, SUN, SUN_RADIUS, &shifted);
+            assert!((0.0..=1.0).contains(&coverage) && coverage <= previous + 1e-5);
+            previous = coverage;
+        }
+        assert_eq!(previous, 0.0);
+    }
+
+    #[test]
+    fn visibility_combines_occluders() {
+        let point = Vec3::new(10.0, 0.0, 0.0);
+        let half = occluder(0, Vec3::new(5.0, 0.0, 0.0), 0.35);
+        let other = occluder(1, Vec3::new(7.0, 0.0, 0.0), 0.2);
+        let blocked = [half, other];
+        let a = 1.0 - sun_coverage(point, SUN, SUN_RADIUS, &half);
+        let b = 1.0 - sun_coverage(point, SUN, SUN_RADIUS, &other);
+        assert!((sun_visibility(point, SUN, SUN_RADIUS, &blocked, None) - a * b).abs() < 1e-5);
+        // El cuerpo al que pertenece el punto no se hace sombra a sí mismo
+        assert!((sun_visibility(point, SUN, SUN_RADIUS, &blocked, Some(1)) - a).abs() < 1e-5);
+        // En la umbra no llega luz
+        let total = [occluder(0, Vec3::new(5.0, 0.0, 0.0), 1.0)];
+        assert_eq!(sun_visibility(point, SUN, SUN_RADIUS, &total, None), 0.0);
+        assert_eq!(sun_visibility(point, SUN, SUN_RADIUS, &[], None), 1.0);
+    }
+
+    #[test]
+    fn tracker_reports_each_event_once() {
+        let mut tracker = EclipseTracker::new();
+        let target = occluder(0, Vec3::new(10.0, 0.0, 0.0), 0.3);
+        let blocking = occluder(1, Vec3::new(5.0, 0.0, 0.0), 1.0);
+        let clear = occluder(1, Vec3::new(5.0, 5.0, 0.0), 1.0);
+
+        let started = tracker.update(0.0, SUN, SUN_RADIUS, &[target, blocking]);
+        assert_eq!(started.len(), 1);
+        assert!(started[0].started && started[0].kind == EclipseKind::Eclipse);
+        assert!(tracker.update(1.0, SUN, SUN_RADIUS, &[target, blocking]).is_empty());
+        let ended = tracker.update(2.0, SUN, SUN_RADIUS, &[target, clear]);
+        assert_eq!(ended.len(), 1);
+        assert!(!ended[0].started && ended[0].max_coverage > 0.99);
+        assert!(tracker.update(3.0, SUN, SUN_RADIUS, &[target, clear]).is_empty());
+    }
+
+    #[test]
+    fn tracker_reports_eclipses_that_only_reach_the_limb() {
+        // El centro del objetivo queda fuera de la penumbra, pero su borde más cercano al eje no
+        let target = occluder(0, Vec3::new(20.0, 2.5, 0.0), 1.0);
+        let blocking = occluder(1, Vec3::new(10.0, 0.0, 0.0), 0.5);
+        assert_eq!(sun_coverage(target.position, SUN, SUN_RADIUS, &blocking), 0.0);
+        let limb = limb_toward_shadow_axis(&target, SUN, &blocking);
+        assert!((limb - Vec3::new(20.0, 1.5, 0.0)).magnitude() < 1e-5);
+
+        let mut tracker = EclipseTracker::new();
+        let started = tracker.update(0.0, SUN, SUN_RADIUS, &[target, blocking]);
+        assert_eq!(started.len(), 1);
+        assert!(started[0].started && started[0].target == 0 && started[0].occluder == 1);
+        assert!(started[0].max_coverage > 0.0 && started[0].max_coverage < 1.0);
+
+        // Con el eje atravesando el cuerpo se evalúa sobre el propio eje
+        let centered = occluder(0, Vec3::new(20.0, 0.5, 0.0), 1.0);
+        assert!((limb_toward_shadow_axis(&centered, SUN, &blocking) - Vec3::new(20.0, 0.0, 0.0)).magnitude() < 1e-5);
+    }
+}
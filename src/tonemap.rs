@@ -0,0 +1,202 @@
+use crate::framebuffer::{Color, Framebuffer};
+
+// Histograma de log2(luminancia) usado por la exposición automática
+const HISTOGRAM_BINS: usize = 64;
+const MIN_LOG_LUMINANCE: f32 = -8.0;
+const MAX_LOG_LUMINANCE: f32 = 4.0;
+
+// Luminancia media a la que la exposición automática lleva la imagen
+const AUTO_EXPOSURE_KEY: f32 = 0.5;
+const MIN_EXPOSURE_EV: f32 = -4.0;
+const MAX_EXPOSURE_EV: f32 = 4.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToneMapOperator {
+    Clamp,
+    Reinhard,
+    AcesFilmic,
+}
+
+impl ToneMapOperator {
+    pub fn next(self) -> Self {
+        match self {
+            ToneMapOperator::Clamp => ToneMapOperator::Reinhard,
+            ToneMapOperator::Reinhard => ToneMapOperator::AcesFilmic,
+            ToneMapOperator::AcesFilmic => ToneMapOperator::Clamp,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ToneMapOperator::Clamp => "Clamp",
+            ToneMapOperator::Reinhard => "Reinhard",
+            ToneMapOperator::AcesFilmic => "ACES filmic",
+        }
+    }
+
+    pub fn apply(self, x: f32) -> f32 {
+        match self {
+            ToneMapOperator::Clamp => x.clamp(0.0, 1.0),
+            ToneMapOperator::Reinhard => x / (1.0 + x),
+            // Aproximación de Narkowicz a la curva ACES
+            ToneMapOperator::AcesFilmic => {
+                let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+                ((x * (a * x + b)) / (x * (c * x + d) + e)).clamp(0.0, 1.0)
+            }
+        }
+    }
+}
+
+pub struct ToneMapper {
+    pub operator: ToneMapOperator,
+    pub auto_exposure: bool,
+    // Exposición manual en stops: el color se multiplica por 2^ev
+    pub exposure_ev: f32,
+    // Velocidad de adaptación de la exposición automática (1/s)
+    pub adaptation_speed: f32,
+    adapted_ev: f32,
+}
+
+impl ToneMapper {
+    pub fn new() -> Self {
+        ToneMapper {
+            operator: ToneMapOperator::AcesFilmic,
+            auto_exposure: false,
+            exposure_ev: 0.0,
+            adaptation_speed: 2.0,
+            adapted_ev: 0.0,
+        }
+    }
+
+    pub fn current_ev(&self) -> f32 {
+        if self.auto_exposure {
+            self.adapted_ev
+        } else {
+            self.exposure_ev
+        }
+    }
+
+    pub fn adjust_exposure(&mut self, delta_ev: f32) {
+        self.exposure_ev = (self.exposure_ev + delta_ev).clamp(MIN_EXPOSURE_EV, MAX_EXPOSURE_EV);
+    }
+
+    // Lleva el buffer HDR del framebuffer a [0, 1] en el mismo buffer de color
+    pub fn apply(&mut self, framebuffer: &mut Framebuffer, delta_time: f32) {
+        if self.auto_exposure {
+            let histogram = luminance_histogram(&framebuffer.color_buffer);
+            let target = auto_exposure_target(&histogram).unwrap_or(self.adapted_ev);
+            let blend = 1.0 - (-delta_time * self.adaptation_speed).exp();
+            self.adapted_ev += (target - self.adapted_ev) * blend;
+        } else {
+            self.adapted_ev = self.exposure_ev;
+        }
+
+        let exposure = self.current_ev().exp2();
+        let operator = self.operator;
+        for color in framebuffer.color_buffer.iter_mut() {
+            *color = Color::from_float(
+                operator.apply(color.r * exposure),
+                operator.apply(color.g * exposure),
+                operator.apply(color.b * exposure),
+            );
+        }
+    }
+}
+
+// Histograma de log2(luminancia) de los píxeles iluminados; los negros (el fondo) se cuentan aparte
+pub struct LuminanceHistogram {
+    pub black: u32,
+    pub bins: [u32; HISTOGRAM_BINS],
+}
+
+pub fn luminance_histogram(colors: &[Color]) -> LuminanceHistogram {
+    let mut histogram = LuminanceHistogram { black: 0, bins: [0; HISTOGRAM_BINS] };
+    let range = MAX_LOG_LUMINANCE - MIN_LOG_LUMINANCE;
+
+    for color in colors {
+        let luminance = color.luminance();
+        if luminance <= 0.0 {
+            histogram.black += 1;
+            continue;
+        }
+        // Cada bin cubre 1/HISTOGRAM_BINS del rango; lo que se sale cae en el primero o el último
+        let t = ((luminance.log2() - MIN_LOG_LUMINANCE) / range).clamp(0.0, 1.0);
+        let bin = ((t * HISTOGRAM_BINS as f32) as usize).min(HISTOGRAM_BINS - 1);
+        histogram.bins[bin] += 1;
+    }
+
+    histogram
+}
+
+// EV que lleva la luminancia media de la escena a AUTO_EXPOSURE_KEY.
+// Ignora el fondo negro y promedia entre los percentiles 50 y 95 de los píxeles iluminados
+// para que ni el espacio vacío ni el Sol dominen el resultado.
+pub fn auto_exposure_target(histogram: &LuminanceHistogram) -> Option<f32> {
+    let total: u32 = histogram.bins.iter().sum();
+    if total == 0 {
+        return None;
+    }
+
+    let low = total as f32 * 0.5;
+    let high = total as f32 * 0.95;
+    let range = MAX_LOG_LUMINANCE - MIN_LOG_LUMINANCE;
+
+    let mut seen = 0.0;
+    let mut weighted = 0.0;
+    let mut count = 0.0;
+    for (bin, &n) in histogram.bins.iter().enumerate() {
+        let start = seen;
+        seen += n as f32;
+        // Parte de este bin que cae dentro de [low, high]
+        let inside = (seen.min(high) - start.max(low)).max(0.0);
+        if inside > 0.0 {
+            let log_luminance = MIN_LOG_LUMINANCE + (bin as f32 + 0.5) / HISTOGRAM_BINS as f32 * range;
+            weighted += log_luminance * inside;
+            count += inside;
+        }
+    }
+
+    if count == 0.0 {
+        return None;
+    }
+
+    let average_log = weighted / count;
+    Some((AUTO_EXPOSURE_KEY.log2() - average_log).clamp(MIN_EXPOSURE_EV, MAX_EXPOSURE_EV))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(luminance: f32) -> Color {
+        Color { r: luminance, g: luminance, b: luminance }
+    }
+
+    #[test]
+    fn black_pixels_are_counted_apart() {
+        let dim = 2.0f32.powf(MIN_LOG_LUMINANCE + 0.05);
+        let histogram = luminance_histogram(&[gray(0.0), gray(0.0), gray(dim)]);
+        assert_eq!(histogram.black, 2);
+        assert_eq!(histogram.bins[0], 1);
+        // El píxel más oscuro con luz sí cuenta para la exposición
+        let target = auto_exposure_target(&histogram).unwrap();
+        assert!((target - MAX_EXPOSURE_EV).abs() < 1e-5);
+        assert_eq!(auto_exposure_target(&luminance_histogram(&[gray(0.0)])), None);
+    }
+
+    #[test]
+    fn bins_cover_the_whole_range() {
+        let range = MAX_LOG_LUMINANCE - MIN_LOG_LUMINANCE;
+        for bin in 0..HISTOGRAM_BINS {
+            // Centro del bin: cae en ese bin y da la exposición que lleva su luminancia a la clave
+            let log_luminance = MIN_LOG_LUMINANCE + (bin as f32 + 0.5) / HISTOGRAM_BINS as f32 * range;
+            let histogram = luminance_histogram(&[gray(log_luminance.exp2())]);
+            assert_eq!(histogram.bins[bin], 1, "bin {}", bin);
+            let expected = (AUTO_EXPOSURE_KEY.log2() - log_luminance).clamp(MIN_EXPOSURE_EV, MAX_EXPOSURE_EV);
+            assert!((auto_exposure_target(&histogram).unwrap() - expected).abs() < 1e-4);
+        }
+        // El último bin tiene su centro dentro del rango
+        let last_center = MIN_LOG_LUMINANCE + (HISTOGRAM_BINS as f32 - 0.5) / HISTOGRAM_BINS as f32 * range;
+        assert!(last_center < MAX_LOG_LUMINANCE);
+    }
+}
@@ -1,10 +1,16 @@
 use crate::vertex::Vertex;
-use crate::shaders::Fragment;
-use crate::framebuffer::{Framebuffer, Color};
+use crate::shaders::{Fragment, FragmentShader, Uniforms};
+use crate::framebuffer::Framebuffer;
 use crate::math::Vec3;
+use crate::antialiasing::sample_offsets;
 
-pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex, framebuffer: &mut Framebuffer, fragment_shader: fn(&Fragment) -> Color) {
+pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex, framebuffer: &mut Framebuffer, uniforms: &Uniforms, fragment_shader: FragmentShader) {
     let (a, b, c) = (v1, v2, v3);
+
+    if framebuffer.samples > 1 {
+        triangle_msaa(a, b, c, framebuffer, uniforms, fragment_shader);
+        return;
+    }
     
     let min_x = a.position.x.min(b.position.x).min(c.position.x).max(0.0) as usize;
     let min_y = a.position.y.min(b.position.y).min(c.position.y).max(0.0) as usize;
@@ -26,16 +32,119 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex, framebuffer: &mut Framebu
                 
                 let normal = (w1 * a.normal + w2 * b.normal + w3 * c.normal).normalize();
                 let tex_coords = w1 * a.tex_coords + w2 * b.tex_coords + w3 * c.tex_coords;
+                let world_position = w1 * a.world_position + w2 * b.world_position + w3 * c.world_position;
                 
                 let fragment = Fragment::new(
                     Vec3::new(x as f32, y as f32, depth),
                     normal,
-                    depth,
                     tex_coords,
+                    world_position,
                 );
                 
-                let color = fragment_shader(&fragment);
-                framebuffer.point_with_color(x, y, depth, color);
+                let color = fragment_shader(&fragment, uniforms);
+                framebuffer.count_fragment(x, y);
+                if !framebuffer.point_with_color(x, y, depth, color) {
+                    framebuffer.counters.depth_rejects += 1;
+                }
+            }
+        }
+    }
+}
+
+// MSAA: la cobertura y la profundidad se evalúan por muestra, pero el shader corre una vez por píxel
+fn triangle_msaa(a: &Vertex, b: &Vertex, c: &Vertex, framebuffer: &mut Framebuffer, uniforms: &Uniforms, fragment_shader: FragmentShader) {
+    let offsets = sample_offsets(framebuffer.samples);
+
+    let min_x = (a.position.x.min(b.position.x).min(c.position.x) - 0.5).max(0.0) as usize;
+    let min_y = (a.position.y.min(b.position.y).min(c.position.y) - 0.5).max(0.0) as usize;
+    let max_x = (a.position.x.max(b.position.x).max(c.position.x) + 0.5).min((framebuffer.width - 1) as f32);
+    let max_y = (a.position.y.max(b.position.y).max(c.position.y) + 0.5).min((framebuffer.height - 1) as f32);
+    if max_x < 0.0 || max_y < 0.0 {
+        return;
+    }
+
+    let a2 = Vec3::new(a.position.x, a.position.y, 0.0);
+    let b2 = Vec3::new(b.position.x, b.position.y, 0.0);
+    let c2 = Vec3::new(c.position.x, c.position.y, 0.0);
+
+    let mut covered = [(0.0f32, false); 8];
+
+    for y in min_y..=max_y as usize {
+        for x in min_x..=max_x as usize {
+            let center = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0);
+            let mut any = false;
+            let mut shade_at = None;
+
+            for (s, (ox, oy)) in offsets.iter().enumerate() {
+                let p = Vec3::new(center.x + ox, center.y + oy, 0.0);
+                let (w1, w2, w3) = barycentric_coordinates(&p, &a2, &b2, &c2);
+                let inside = w1 >= 0.0 && w2 >= 0.0 && w3 >= 0.0;
+                let depth = w1 * a.position.z + w2 * b.position.z + w3 * c.position.z;
+                covered[s] = (depth, inside && depth.is_finite() && depth >= 0.0);
+                if covered[s].1 {
+                    any = true;
+                    if shade_at.is_none() {
+                        shade_at = Some((w1, w2, w3));
+                    }
+                }
+            }
+
+            if !any {
+                continue;
+            }
+
+            // Sombrear en el centro si está dentro del triángulo; si no, en la primera muestra cubierta
+            let (cw1, cw2, cw3) = barycentric_coordinates(&center, &a2, &b2, &c2);
+            let (w1, w2, w3) = if cw1 >= 0.0 && cw2 >= 0.0 && cw3 >= 0.0 {
+                (cw1, cw2, cw3)
+            } else {
+                shade_at.unwrap()
+            };
+
+            let depth = w1 * a.position.z + w2 * b.position.z + w3 * c.position.z;
+            let fragment = Fragment::new(
+                Vec3::new(x as f32, y as f32, depth),
+                (w1 * a.normal + w2 * b.normal + w3 * c.normal).normalize(),
+                w1 * a.tex_coords + w2 * b.tex_coords + w3 * c.tex_coords,
+                w1 * a.world_position + w2 * b.world_position + w3 * c.world_position,
+            );
+            let color = fragment_shader(&fragment, uniforms);
+            framebuffer.count_fragment(x, y);
+
+            // Rechazado si no ganó en ninguna de sus muestras
+            let mut written = false;
+            for (s, &(sample_depth, inside)) in covered.iter().enumerate().take(offsets.len()) {
+                if inside {
+                    written |= framebuffer.sample_point(x, y, s, sample_depth, color);
+                }
+            }
+            if !written {
+                framebuffer.counters.depth_rejects += 1;
+            }
+        }
+    }
+}
+
+// Rasteriza solo profundidad: x, y en pantalla y z = valor a guardar en el z-buffer
+pub fn depth_triangle(a: &Vec3, b: &Vec3, c: &Vec3, framebuffer: &mut Framebuffer) {
+    let min_x = a.x.min(b.x).min(c.x).max(0.0) as usize;
+    let min_y = a.y.min(b.y).min(c.y).max(0.0) as usize;
+    let max_x = a.x.max(b.x).max(c.x).min((framebuffer.width - 1) as f32);
+    let max_y = a.y.max(b.y).max(c.y).min((framebuffer.height - 1) as f32);
+    if max_x < 0.0 || max_y < 0.0 {
+        return;
+    }
+
+    // Las baricéntricas se calculan en 2D; z puede estar en cualquier escala
+    let (a2, b2, c2) = (Vec3::new(a.x, a.y, 0.0), Vec3::new(b.x, b.y, 0.0), Vec3::new(c.x, c.y, 0.0));
+
+    for y in min_y..=max_y as usize {
+        for x in min_x..=max_x as usize {
+            let p = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0);
+            let (w1, w2, w3) = barycentric_coordinates(&p, &a2, &b2, &c2);
+
+            if w1 >= 0.0 && w2 >= 0.0 && w3 >= 0.0 {
+                framebuffer.depth_point(x, y, w1 * a.z + w2 * b.z + w3 * c.z);
             }
         }
     }
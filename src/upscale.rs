@@ -0,0 +1,394 @@
+use std::f32::consts::PI;
+
+// Límites de la escala de render, manual o dinámica
+pub const MIN_RENDER_SCALE: f32 = 0.25;
+pub const MAX_RENDER_SCALE: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpscaleFilter {
+    Nearest,
+    Bilinear,
+    Bicubic,
+    Lanczos,
+    EdgeAware,
+}
+
+impl UpscaleFilter {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "nearest" => Ok(UpscaleFilter::Nearest),
+            "bilinear" => Ok(UpscaleFilter::Bilinear),
+            "bicubic" => Ok(UpscaleFilter::Bicubic),
+            "lanczos" => Ok(UpscaleFilter::Lanczos),
+            "edge" => Ok(UpscaleFilter::EdgeAware),
+            _ => Err(format!(
+                "filtro desconocido: {} (opciones: nearest, bilinear, bicubic, lanczos, edge)",
+                text
+            )),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            UpscaleFilter::Nearest => UpscaleFilter::Bilinear,
+            UpscaleFilter::Bilinear => UpscaleFilter::Bicubic,
+            UpscaleFilter::Bicubic => UpscaleFilter::Lanczos,
+            UpscaleFilter::Lanczos => UpscaleFilter::EdgeAware,
+            UpscaleFilter::EdgeAware => UpscaleFilter::Nearest,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            UpscaleFilter::Nearest => "nearest",
+            UpscaleFilter::Bilinear => "bilinear",
+            UpscaleFilter::Bicubic => "bicubic",
+            UpscaleFilter::Lanczos => "lanczos",
+            UpscaleFilter::EdgeAware => "edge",
+        }
+    }
+}
+
+fn unpack(c: u32) -> [f32; 3] {
+    [((c >> 16) & 0xFF) as f32, ((c >> 8) & 0xFF) as f32, (c & 0xFF) as f32]
+}
+
+fn pack(c: [f32; 3]) -> u32 {
+    let r = c[0].round().clamp(0.0, 255.0) as u32;
+    let g = c[1].round().clamp(0.0, 255.0) as u32;
+    let b = c[2].round().clamp(0.0, 255.0) as u32;
+    (r << 16) | (g << 8) | b
+}
+
+fn luma(c: [f32; 3]) -> f32 {
+    (0.299 * c[0] + 0.587 * c[1] + 0.114 * c[2]) / 255.0
+}
+
+// Reescala `src` (src_w x src_h) a `dst` (dst_w x dst_h), alineando los centros de los píxeles
+pub fn upscale(src: &[u32], src_w: usize, src_h: usize, dst: &mut [u32], dst_w: usize, dst_h: usize, filter: UpscaleFilter) {
+    if src_w == dst_w && src_h == dst_h {
+        dst.copy_from_slice(src);
+        return;
+    }
+
+    match filter {
+        UpscaleFilter::Nearest => nearest(src, src_w, src_h, dst, dst_w, dst_h),
+        UpscaleFilter::Bilinear => bilinear(src, src_w, src_h, dst, dst_w, dst_h),
+        UpscaleFilter::Bicubic => separable(src, src_w, src_h, dst, dst_w, dst_h, 2, catmull_rom),
+        UpscaleFilter::Lanczos => separable(src, src_w, src_h, dst, dst_w, dst_h, 3, lanczos3),
+        UpscaleFilter::EdgeAware => edge_aware(src, src_w, src_h, dst, dst_w, dst_h),
+    }
+}
+
+fn nearest(src: &[u32], src_w: usize, src_h: usize, dst: &mut [u32], dst_w: usize, dst_h: usize) {
+    for y in 0..dst_h {
+        let sy = ((y * src_h) / dst_h).min(src_h - 1);
+        for x in 0..dst_w {
+            let sx = ((x * src_w) / dst_w).min(src_w - 1);
+            dst[y * dst_w + x] = src[sy * src_w + sx];
+        }
+    }
+}
+
+fn bilinear(src: &[u32], src_w: usize, src_h: usize, dst: &mut [u32], dst_w: usize, dst_h: usize) {
+    // Al reducir, la tienda tiene que cubrir todos los texels que caen bajo el píxel destino
+    if src_w > dst_w || src_h > dst_h {
+        separable(src, src_w, src_h, dst, dst_w, dst_h, 1, tent);
+        return;
+    }
+
+    let x_ratio = src_w as f32 / dst_w as f32;
+    let y_ratio = src_h as f32 / dst_h as f32;
+
+    for y in 0..dst_h {
+        let src_y = ((y as f32 + 0.5) * y_ratio - 0.5).max(0.0);
+        let y0 = (src_y.floor() as usize).min(src_h - 1);
+        let y1 = (y0 + 1).min(src_h - 1);
+        let fy = src_y - y0 as f32;
+
+        for x in 0..dst_w {
+            let src_x = ((x as f32 + 0.5) * x_ratio - 0.5).max(0.0);
+            let x0 = (src_x.floor() as usize).min(src_w - 1);
+            let x1 = (x0 + 1).min(src_w - 1);
+            let fx = src_x - x0 as f32;
+
+            let c00 = unpack(src[y0 * src_w + x0]);
+            let c10 = unpack(src[y0 * src_w + x1]);
+            let c01 = unpack(src[y1 * src_w + x0]);
+            let c11 = unpack(src[y1 * src_w + x1]);
+
+            let mut out = [0.0; 3];
+            for i in 0..3 {
+                let top = c00[i] * (1.0 - fx) + c10[i] * fx;
+                let bottom = c01[i] * (1.0 - fx) + c11[i] * fx;
+                out[i] = top * (1.0 - fy) + bottom * fy;
+            }
+            dst[y * dst_w + x] = pack(out);
+        }
+    }
+}
+
+fn tent(x: f32) -> f32 {
+    (1.0 - x.abs()).max(0.0)
+}
+
+fn catmull_rom(x: f32) -> f32 {
+    let x = x.abs();
+    if x < 1.0 {
+        1.5 * x * x * x - 2.5 * x * x + 1.0
+    } else if x < 2.0 {
+        -0.5 * x * x * x + 2.5 * x * x - 4.0 * x + 2.0
+    } else {
+        0.0
+    }
+}
+
+fn lanczos3(x: f32) -> f32 {
+    let x = x.abs();
+    if x < 1e-5 {
+        1.0
+    } else if x < 3.0 {
+        let px = PI * x;
+        3.0 * px.sin() * (px / 3.0).sin() / (px * px)
+    } else {
+        0.0
+    }
+}
+
+// Filtro separable: primero horizontal (dst_w x src_h) y luego vertical.
+// Al reducir, el núcleo se estira por la razón de escala para no producir aliasing.
+#[allow(clippy::too_many_arguments)]
+fn separable(
+    src: &[u32],
+    src_w: usize,
+    src_h: usize,
+    dst: &mut [u32],
+    dst_w: usize,
+    dst_h: usize,
+    radius: i32,
+    kernel: fn(f32) -> f32,
+) {
+    let taps = |dst_size: usize, src_size: usize| -> Vec<(i32, Vec<f32>)> {
+        let ratio = src_size as f32 / dst_size as f32;
+        let stretch = ratio.max(1.0);
+        let support = radius as f32 * stretch;
+        (0..dst_size)
+            .map(|i| {
+                let center = (i as f32 + 0.5) * ratio - 0.5;
+                let first = (center - support).floor() as i32 + 1;
+                let last = (center + support).floor() as i32;
+                let mut weights: Vec<f32> = (first..=last).map(|j| kernel((center - j as f32) / stretch)).collect();
+                let sum: f32 = weights.iter().sum();
+                for w in weights.iter_mut() {
+                    *w /= sum;
+                }
+                (first, weights)
+            })
+            .collect()
+    };
+
+    let horizontal_taps = taps(dst_w, src_w);
+    let vertical_taps = taps(dst_h, src_h);
+
+    let mut horizontal = vec![[0.0f32; 3]; dst_w * src_h];
+    for y in 0..src_h {
+        for (x, (first, weights)) in horizontal_taps.iter().enumerate() {
+            let mut sum = [0.0; 3];
+            for (k, w) in weights.iter().enumerate() {
+                let sx = (first + k as i32).clamp(0, src_w as i32 - 1) as usize;
+                let c = unpack(src[y * src_w + sx]);
+                for i in 0..3 {
+                    sum[i] += c[i] * w;
+                }
+            }
+            horizontal[y * dst_w + x] = sum;
+        }
+    }
+
+    for (y, (first, weights)) in vertical_taps.iter().enumerate() {
+        for x in 0..dst_w {
+            let mut sum = [0.0; 3];
+            for (k, w) in weights.iter().enumerate() {
+                let sy = (first + k as i32).clamp(0, src_h as i32 - 1) as usize;
+                let c = horizontal[sy * dst_w + x];
+                for i in 0..3 {
+                    sum[i] += c[i] * w;
+                }
+            }
+            dst[y * dst_w + x] = pack(sum);
+        }
+    }
+}
+
+// Interpolación bilateral: pesos bilineales atenuados por la diferencia de luma con el
+// texel más cercano, así los bordes no se emborronan pero los degradados siguen suaves
+fn edge_aware(src: &[u32], src_w: usize, src_h: usize, dst: &mut [u32], dst_w: usize, dst_h: usize) {
+    const RANGE_SHARPNESS: f32 = 40.0;
+
+    let x_ratio = src_w as f32 / dst_w as f32;
+    let y_ratio = src_h as f32 / dst_h as f32;
+
+    for y in 0..dst_h {
+        let src_y = ((y as f32 + 0.5) * y_ratio - 0.5).max(0.0);
+        let y0 = (src_y.floor() as usize).min(src_h - 1);
+        let y1 = (y0 + 1).min(src_h - 1);
+        let fy = src_y - y0 as f32;
+
+        for x in 0..dst_w {
+            let src_x = ((x as f32 + 0.5) * x_ratio - 0.5).max(0.0);
+            let x0 = (src_x.floor() as usize).min(src_w - 1);
+            let x1 = (x0 + 1).min(src_w - 1);
+            let fx = src_x - x0 as f32;
+
+            let corners = [
+                (unpack(src[y0 * src_w + x0]), (1.0 - fx) * (1.0 - fy)),
+                (unpack(src[y0 * src_w + x1]), fx * (1.0 - fy)),
+                (unpack(src[y1 * src_w + x0]), (1.0 - fx) * fy),
+                (unpack(src[y1 * src_w + x1]), fx * fy),
+            ];
+
+            let nearest = corners
+                .iter()
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|c| luma(c.0))
+                .unwrap_or(0.0);
+
+            let mut sum = [0.0; 3];
+            let mut total = 0.0;
+            for (color, spatial) in corners {
+                let diff = luma(color) - nearest;
+                let weight = spatial * (-RANGE_SHARPNESS * diff * diff).exp();
+                for i in 0..3 {
+                    sum[i] += color[i] * weight;
+                }
+                total += weight;
+            }
+
+            let out = if total > 0.0 {
+                [sum[0] / total, sum[1] / total, sum[2] / total]
+            } else {
+                corners[0].0
+            };
+            dst[y * dst_w + x] = pack(out);
+        }
+    }
+}
+
+// Ajusta la escala de render para mantener un tiempo por cuadro objetivo
+pub struct DynamicResolution {
+    pub enabled: bool,
+    pub target_frame_time: f32,
+    pub min_scale: f32,
+    pub max_scale: f32,
+    smoothed_frame_time: f32,
+}
+
+impl DynamicResolution {
+    pub fn new(target_frame_time: f32) -> Self {
+        DynamicResolution {
+            enabled: false,
+            target_frame_time,
+            min_scale: MIN_RENDER_SCALE,
+            max_scale: MAX_RENDER_SCALE,
+            smoothed_frame_time: target_frame_time,
+        }
+    }
+
+    // Recibe el tiempo de render del último cuadro y la escala actual; devuelve la escala propuesta.
+    // Se cuantiza a pasos de 1/16 para no recrear el framebuffer por cambios mínimos.
+    pub fn update(&mut self, frame_time: f32, current_scale: f32) -> f32 {
+        if !self.enabled {
+            return current_scale;
+        }
+
+        self.smoothed_frame_time += (frame_time - self.smoothed_frame_time) * 0.1;
+
+        // El costo es proporcional al área, así que la escala lineal va con la raíz
+        let ratio = (self.target_frame_time / self.smoothed_frame_time.max(1e-4)).sqrt();
+        if (0.92..=1.08).contains(&ratio) {
+            return current_scale;
+        }
+
+        // Se redondea en el sentido del cambio: con round() un paso chico volvería a la misma escala
+        let proposed = current_scale * ratio.clamp(0.9, 1.1) * 16.0;
+        let steps = if ratio > 1.0 { proposed.ceil() } else { proposed.floor() };
+        let quantized = (steps / 16.0).clamp(self.min_scale, self.max_scale);
+        if quantized != current_scale {
+            // Se espera a medir con la nueva escala antes de volver a ajustar
+            self.smoothed_frame_time = self.target_frame_time;
+        }
+        quantized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(resolution: &mut DynamicResolution, frame_time: f32, scale: f32, frames: usize) -> f32 {
+        (0..frames).fold(scale, |scale, _| resolution.update(frame_time, scale))
+    }
+
+    // Franjas verticales de un píxel, alternando blanco y negro
+    fn stripes(width: usize, height: usize) -> Vec<u32> {
+        (0..width * height).map(|i| if (i % width).is_multiple_of(2) { 0xFFFFFF } else { 0 }).collect()
+    }
+
+    #[test]
+    fn downsampling_filters_do_not_alias() {
+        let (src_w, src_h) = (48, 6);
+        let (dst_w, dst_h) = (16, 2);
+        let src = stripes(src_w, src_h);
+        for filter in [UpscaleFilter::Bilinear, UpscaleFilter::Bicubic, UpscaleFilter::Lanczos] {
+            let mut dst = vec![0u32; dst_w * dst_h];
+            upscale(&src, src_w, src_h, &mut dst, dst_w, dst_h, filter);
+            // Lejos de los bordes las franjas se promedian en gris en vez de alternar blanco y negro
+            let reds: Vec<u32> = dst[2..dst_w - 2].iter().map(|c| c >> 16).collect();
+            let (min, max) = (*reds.iter().min().unwrap(), *reds.iter().max().unwrap());
+            assert!(max - min < 64 && min > 64 && max < 192, "{}: {:?}", filter.name(), reds);
+        }
+    }
+
+    #[test]
+    fn upsampling_keeps_source_texels() {
+        // Al ampliar al doble el núcleo no se estira y las franjas conservan su contraste
+        let src = stripes(8, 2);
+        for filter in [UpscaleFilter::Bilinear, UpscaleFilter::Bicubic, UpscaleFilter::Lanczos] {
+            let mut dst = vec![0u32; 16 * 4];
+            upscale(&src, 8, 2, &mut dst, 16, 4, filter);
+            let reds: Vec<u32> = dst[..16].iter().map(|c| c >> 16).collect();
+            assert!(reds.iter().max().unwrap() - reds.iter().min().unwrap() > 96, "{}: {:?}", filter.name(), reds);
+        }
+    }
+
+    #[test]
+    fn dynamic_resolution_rises_from_minimum() {
+        let mut resolution = DynamicResolution::new(0.016);
+        resolution.enabled = true;
+        let min_scale = resolution.min_scale;
+        let scale = run(&mut resolution, 0.002, min_scale, 200);
+        assert!(scale > resolution.min_scale, "la escala quedó en {}", scale);
+        // Con cuadros rápidos de sobra termina en el máximo
+        assert_eq!(run(&mut resolution, 0.001, scale, 2000), resolution.max_scale);
+    }
+
+    #[test]
+    fn dynamic_resolution_falls_with_slow_frames() {
+        let mut resolution = DynamicResolution::new(0.016);
+        resolution.enabled = true;
+        let scale = run(&mut resolution, 0.05, 1.0, 200);
+        assert!(scale < 1.0);
+        assert_eq!(run(&mut resolution, 0.5, scale, 2000), resolution.min_scale);
+        // Siempre en pasos de 1/16
+        assert_eq!((scale * 16.0).fract(), 0.0);
+    }
+
+    #[test]
+    fn dynamic_resolution_holds_near_target() {
+        let mut resolution = DynamicResolution::new(0.016);
+        resolution.enabled = true;
+        assert_eq!(run(&mut resolution, 0.0165, 0.75, 200), 0.75);
+        resolution.enabled = false;
+        assert_eq!(run(&mut resolution, 0.001, 0.75, 200), 0.75);
+    }
+}
@@ -5,6 +5,7 @@ pub struct Vertex {
     pub position: Vec3,
     pub normal: Vec3,
     pub tex_coords: Vec3,
+    pub world_position: Vec3,
 }
 
 impl Vertex {
@@ -13,6 +14,7 @@ impl Vertex {
             position,
             normal,
             tex_coords,
+            world_position: position,
         }
     }
 }
@@ -0,0 +1,273 @@
+use crate::camera::{Camera, CameraMode};
+use crate::math::{Quat, Vec3};
+use crate::scene::{CelestialBody, Scene};
+
+// Vista general del sistema (tecla 0 y --camera overview)
+pub const OVERVIEW_EYE: Vec3 = Vec3::new(0.0, 15.0, 35.0);
+
+// Llegada a un cuerpo: lo bastante lejos como para verlo entero, desde arriba y adelante
+const ARRIVAL_DISTANCE: f32 = 3.0;
+const ARRIVAL_RADII: f32 = 6.0;
+const ARRIVAL_DIRECTION: Vec3 = Vec3::new(0.0, 0.5, 1.0);
+
+// Duración según la distancia recorrida
+const MIN_DURATION: f32 = 1.2;
+const MAX_DURATION: f32 = 3.0;
+const SECONDS_PER_UNIT: f32 = 0.03;
+
+// El recorrido no pasa a menos de esto de la superficie de ningún cuerpo (radios + margen fijo)
+const CLEARANCE_RADII: f32 = 2.0;
+const CLEARANCE_MARGIN: f32 = 0.5;
+const PATH_SAMPLES: usize = 48;
+const AVOIDANCE_ITERATIONS: usize = 8;
+// La mirada llega al destino antes que la cámara
+const LOOK_AHEAD: f32 = 1.25;
+
+// Destino de warp: la vista general o un cuerpo de la escena
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarpTarget {
+    pub name: String,
+    // None = vista general
+    pub body: Option<String>,
+}
+
+// La vista general, el Sol y después los planetas y lunas en el orden de la escena
+pub fn warp_targets(scene: &Scene) -> Vec<WarpTarget> {
+    let overview = WarpTarget { name: "Vista general".to_string(), body: None };
+    let bodies = std::iter::once(&scene.sun)
+        .chain(&scene.planets)
+        .map(|body| WarpTarget { name: body.name.clone(), body: Some(body.name.clone()) });
+    std::iter::once(overview).chain(bodies).collect()
+}
+
+// Dónde queda la cámara al llegar a un cuerpo, según su tamaño
+pub fn arrival_eye(body: &CelestialBody) -> Vec3 {
+    let distance = ARRIVAL_DISTANCE + body.radius() * ARRIVAL_RADII;
+    body.position + ARRIVAL_DIRECTION.normalize() * distance
+}
+
+// Vuelo por una curva de Bézier cúbica que esquiva los cuerpos, mientras el punto mirado pasa del
+// centro anterior al destino. El final de la curva se guarda relativo al destino, que se sigue
+// moviendo durante el vuelo.
+pub struct Warp {
+    pub target: WarpTarget,
+    progress: f32,
+    duration: f32,
+    start: Vec3,
+    start_control: Vec3,
+    end_control: Vec3,
+    end: Vec3,
+    start_center: Vec3,
+    start_up: Vec3,
+}
+
+impl Warp {
+    pub fn start(target: &WarpTarget, scene: &Scene, camera: &mut Camera) -> Self {
+        // El warp maneja la cámara por su cuenta hasta llegar
+        camera.reset_to_orbit();
+
+        let center = target_center(target, scene);
+        let arrival = match target.body.as_deref().and_then(|name| scene.find(name)) {
+            Some(body) => arrival_eye(body),
+            None => OVERVIEW_EYE,
+        };
+
+        let start = camera.eye;
+        let length = (arrival - start).magnitude();
+        let handle = length / 3.0;
+        // Sale mitad hacia donde miraba y mitad hacia el destino; llega desde afuera del cuerpo
+        let forward = (camera.center - camera.eye).normalize();
+        let toward = (arrival - start).normalize();
+        let start_control = start + (forward + toward).normalize() * handle;
+        let end_control = arrival + (arrival - center).normalize() * handle;
+
+        let mut warp = Warp {
+            target: target.clone(),
+            progress: 0.0,
+            duration: (MIN_DURATION + length * SECONDS_PER_UNIT).min(MAX_DURATION),
+            start,
+            start_control,
+            end_control: end_control - center,
+            end: arrival - center,
+            start_center: camera.center,
+            start_up: camera.up,
+        };
+        warp.avoid_bodies(scene, center, 0.0);
+        warp
+    }
+
+    // Puntos de control en el mundo, con el destino en `center`
+    fn controls(&self, center: Vec3) -> [Vec3; 4] {
+        [self.start, self.start_control, center + self.end_control, center + self.end]
+    }
+
+    // Empuja los puntos de control intermedios hacia afuera del cuerpo que más se invade, varias veces.
+    // Solo se mira el tramo de la curva que falta recorrer a partir de `from`.
+    fn avoid_bodies(&mut self, scene: &Scene, center: Vec3, from: f32) {
+        let controls = self.controls(center);
+        let (current, end) = (bezier(&controls, from), controls[3]);
+        // Los cuerpos junto a la posición actual o la llegada no se pueden esquivar
+        let obstacles: Vec<(Vec3, f32)> = scene
+            .obstacles()
+            .into_iter()
+            .map(|obstacle| (obstacle.position, obstacle.radius * CLEARANCE_RADII + CLEARANCE_MARGIN))
+            .filter(|(position, clearance)| current.distance(position) > *clearance && end.distance(position) > *clearance)
+            .collect();
+
+        for _ in 0..AVOIDANCE_ITERATIONS {
+            let controls = self.controls(center);
+            let mut worst: Option<(f32, f32, Vec3)> = None;
+            for i in 1..PATH_SAMPLES {
+                let t = i as f32 / PATH_SAMPLES as f32;
+                if t <= from {
+                    continue;
+                }
+                let point = bezier(&controls, t);
+                for (position, clearance) in &obstacles {
+                    let penetration = clearance - point.distance(position);
+                    if penetration > worst.map_or(0.0, |(p, _, _)| p) {
+                        let away = point - *position;
+                        // Si la curva pasa justo por el centro, se esquiva por arriba
+                        let away = if away.magnitude() > 1e-4 { away.normalize() } else { Vec3::new(0.0, 1.0, 0.0) };
+                        worst = Some((penetration, t, away));
+                    }
+                }
+            }
+            let Some((penetration, t, away)) = worst else {
+                break;
+            };
+            // Mover los dos controles intermedios por D mueve el punto t en 3t(1-t)·D
+            let push = away * (penetration * 1.1 / (3.0 * t * (1.0 - t)));
+            self.start_control += push;
+            self.end_control += push;
+        }
+    }
+
+    // Avanza el vuelo; devuelve true al llegar, con la cámara ya en modo órbita o seguimiento
+    pub fn update(&mut self, scene: &Scene, camera: &mut Camera, delta_time: f32) -> bool {
+        self.progress = (self.progress + delta_time / self.duration).min(1.0);
+        let center = target_center(&self.target, scene);
+
+        // Los planetas siguen orbitando: se vuelve a revisar lo que falta del recorrido cada cuadro
+        let t = smoothstep(self.progress);
+        self.avoid_bodies(scene, center, t);
+        camera.eye = bezier(&self.controls(center), t);
+
+        let look_t = smoothstep((self.progress * LOOK_AHEAD).min(1.0));
+        let look_at = self.start_center.lerp(&center, look_t);
+        let up = self.start_up.lerp(&Vec3::new(0.0, 1.0, 0.0), t).normalize();
+        let distance = (look_at - camera.eye).magnitude();
+        if distance > 1e-3 {
+            camera.set_orientation(Quat::look_rotation(&(look_at - camera.eye), &up), distance);
+        }
+
+        if self.progress < 1.0 {
+            return false;
+        }
+        // Al llegar a un cuerpo la cámara queda pegada a él; a la vista general, orbitando
+        camera.center = center;
+        camera.up = Vec3::new(0.0, 1.0, 0.0);
+        camera.sync_orbit();
+        if self.target.body.is_some() {
+            camera.set_mode(CameraMode::Follow);
+        }
+        true
+    }
+}
+
+fn target_center(target: &WarpTarget, scene: &Scene) -> Vec3 {
+    target
+        .body
+        .as_deref()
+        .and_then(|name| scene.find(name))
+        .map_or(Vec3::zeros(), |body| body.position)
+}
+
+fn bezier(points: &[Vec3; 4], t: f32) -> Vec3 {
+    let s = 1.0 - t;
+    points[0] * (s * s * s) + points[1] * (3.0 * s * s * t) + points[2] * (3.0 * s * t * t) + points[3] * (t * t * t)
+}
+
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    const SCENE: &str = "sun Sol 0 2 0.2 0 sun\nplanet Tierra 8 0.5 2 0.3 earth\n";
+
+    #[test]
+    fn flight_goes_around_the_sun_and_lands_on_the_moving_target() {
+        let mut scene = Scene::parse(SCENE, &Path::new(env!("CARGO_MANIFEST_DIR")).join("assets")).unwrap();
+        let target = warp_targets(&scene).into_iter().find(|target| target.name == "Tierra").unwrap();
+        let clearance = scene.sun.radius() * CLEARANCE_RADII + CLEARANCE_MARGIN;
+
+        // Del otro lado del Sol, mirándolo: el camino recto lo atraviesa por el centro
+        let arrival = arrival_eye(scene.find("Tierra").unwrap());
+        let mut camera = Camera::new(-arrival, Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+        let mut warp = Warp::start(&target, &scene, &mut camera);
+
+        let controls = warp.controls(arrival - warp.end);
+        for i in 0..=PATH_SAMPLES * 4 {
+            let point = bezier(&controls, i as f32 / (PATH_SAMPLES * 4) as f32);
+            assert!(point.magnitude() > clearance, "{:?} a {} del Sol", point, point.magnitude());
+        }
+
+        let mut arrived = false;
+        for _ in 0..600 {
+            scene.update(1.0 / 60.0);
+            arrived = warp.update(&scene, &mut camera, 1.0 / 60.0);
+            assert!(camera.eye.magnitude() > clearance);
+            if arrived {
+                break;
+            }
+        }
+        assert!(arrived);
+        let earth = scene.find("Tierra").unwrap().position;
+        assert!(earth.distance(&(arrival - warp.end)) > 0.1, "la Tierra no se movió durante el vuelo");
+        assert!(camera.eye.distance(&(earth + warp.end)) < 1e-4);
+        assert_eq!(camera.center, earth);
+        assert_eq!(camera.mode, CameraMode::Follow);
+    }
+
+    #[test]
+    fn flight_dodges_planets_that_move_into_the_path() {
+        // Venus cruza de lado a lado el camino hacia Marte mientras dura el vuelo, empiece donde empiece
+        for phase in (0..360).step_by(15) {
+            let text = format!(
+                "sun Sol 0 2 0.2 0 sun\nplanet Venus 10 4 1 1.5 rocky phase={}\nplanet Marte 30 0.5 1 0 red phase=90\n",
+                phase
+            );
+            let mut scene = Scene::parse(&text, &Path::new(env!("CARGO_MANIFEST_DIR")).join("assets")).unwrap();
+            let target = warp_targets(&scene).into_iter().find(|target| target.name == "Marte").unwrap();
+            let mut camera = Camera::new(Vec3::new(0.0, 0.0, -30.0), Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+            let mut warp = Warp::start(&target, &scene, &mut camera);
+
+            let mut arrived = false;
+            for _ in 0..600 {
+                scene.update(1.0 / 60.0);
+                arrived = warp.update(&scene, &mut camera, 1.0 / 60.0);
+                for body in std::iter::once(&scene.sun).chain(&scene.planets) {
+                    let distance = camera.eye.distance(&body.position);
+                    assert!(distance > body.radius() * 1.5, "fase {}: a {} del centro de {}", phase, distance, body.name);
+                }
+                if arrived {
+                    break;
+                }
+            }
+            assert!(arrived);
+        }
+    }
+
+    #[test]
+    fn bezier_passes_through_its_ends() {
+        let points = [Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 2.0, 0.0), Vec3::new(3.0, 2.0, 0.0), Vec3::new(4.0, 0.0, 0.0)];
+        assert_eq!(bezier(&points, 0.0), points[0]);
+        assert_eq!(bezier(&points, 1.0), points[3]);
+        // Simétrica: la mitad queda a 3/4 de la altura de los controles
+        assert!(bezier(&points, 0.5).distance(&Vec3::new(2.0, 1.5, 0.0)) < 1e-6);
+    }
+}